@@ -1,14 +1,15 @@
 use std::time::Duration;
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion};
+use rs_ec_perf::novel_poly_basis::{bitsliced, f2e8};
 use rs_ec_perf::*;
 
 /// Create a new testset for a particular RS encoding.
 macro_rules! instanciate_test {
 	($name:literal, $mp:ident) => {
 		pub mod $mp {
-			use super::super::$mp::{encode, reconstruct};
-			use super::super::{roundtrip, BYTES};
+			use super::super::$mp::{encode, encode_into, reconstruct};
+			use super::super::{roundtrip, shard_len, BYTES, DATA_SHARDS, N_VALIDATORS};
 			use criterion::{black_box, Criterion};
 
 			pub fn bench_roundtrip(crit: &mut Criterion) {
@@ -26,24 +27,88 @@ macro_rules! instanciate_test {
 					})
 				});
 			}
+
+			pub fn bench_encode_into(crit: &mut Criterion) {
+				let mut buffers = vec![vec![0u8; shard_len(256, DATA_SHARDS)]; N_VALIDATORS];
+				crit.bench_function(concat!($name, " encode into"), |b| {
+					b.iter(|| {
+						let mut shards = buffers.iter_mut().map(|buf| &mut buf[..]).collect::<Vec<_>>();
+						encode_into(black_box(&BYTES[..256]), &mut shards[..]);
+					})
+				});
+			}
 		}
 	};
 }
 
 pub mod tests {
 	instanciate_test!("novel poly basis", novel_poly_basis);
+	instanciate_test!("novel poly basis gf(2^8)", f2e8);
+	instanciate_test!("novel poly basis bitsliced", bitsliced);
 	instanciate_test!("status quo", status_quo);
+	instanciate_test!("additive fft", additive_fft);
+}
+
+/// End to end through the `polkadot` compatibility functions, for a realistic validator count.
+pub fn bench_polkadot(crit: &mut Criterion) {
+	use rs_ec_perf::polkadot::{recovery_threshold, Backend};
+
+	const N_VALIDATORS: usize = 200;
+	let threshold = recovery_threshold(N_VALIDATORS).unwrap();
+	for (name, backend) in [("status quo", Backend::StatusQuo), ("novel poly basis", Backend::NovelPolyBasis)] {
+		crit.bench_function(&format!("polkadot {} roundtrip", name), |b| {
+			b.iter(|| {
+				let chunks = backend.obtain_chunks(N_VALIDATORS, criterion::black_box(&BYTES[..1 << 16])).unwrap();
+				let available = chunks.iter().enumerate().rev().take(threshold).map(|(i, chunk)| (&chunk[..], i));
+				backend.reconstruct(N_VALIDATORS, available).unwrap()
+			})
+		});
+	}
+}
+
+/// The FFT butterflies with either multiplier, for a code whose factors leave the cache.
+pub fn bench_multiplier(crit: &mut Criterion) {
+	use rs_ec_perf::novel_poly_basis::{tables, Multiplier};
+
+	const N: usize = 1 << 16;
+	const K: usize = 1 << 14;
+	let payload = &BYTES[..(1 << 20)];
+	let mut buffers = vec![vec![0u8; shard_len(payload.len(), K)]; N];
+	for (name, multiplier) in [("table", Multiplier::Table), ("clmul", Multiplier::Clmul)] {
+		let tables = tables().with_multiplier(multiplier);
+		crit.bench_function(&format!("novel poly basis {} multiplier encode", name), |b| {
+			b.iter(|| {
+				let mut shards = buffers.iter_mut().map(|buf| &mut buf[..]).collect::<Vec<_>>();
+				tables.encode_into(criterion::black_box(payload), &mut shards[..], N, K);
+			})
+		});
+	}
 }
 
 fn adjusted_criterion() -> Criterion {
-	let crit = Criterion::default()
+	Criterion::default()
 		.sample_size(10)
 		.warm_up_time(Duration::from_secs(1))
-		.measurement_time(Duration::from_secs(60));
-	crit
+		.measurement_time(Duration::from_secs(60))
 }
 
-criterion_group!(name = acc_novel_poly_basis; config = adjusted_criterion(); targets =  tests::novel_poly_basis::bench_roundtrip, tests::novel_poly_basis::bench_encode);
-criterion_group!(name = acc_status_quo; config = adjusted_criterion(); targets =  tests::status_quo::bench_roundtrip, tests::status_quo::bench_encode);
+criterion_group!(name = acc_novel_poly_basis; config = adjusted_criterion(); targets =  tests::novel_poly_basis::bench_roundtrip, tests::novel_poly_basis::bench_encode, tests::novel_poly_basis::bench_encode_into);
+criterion_group!(name = acc_f2e8; config = adjusted_criterion(); targets =  tests::f2e8::bench_roundtrip, tests::f2e8::bench_encode, tests::f2e8::bench_encode_into);
+criterion_group!(name = acc_bitsliced; config = adjusted_criterion(); targets =  tests::bitsliced::bench_roundtrip, tests::bitsliced::bench_encode, tests::bitsliced::bench_encode_into);
+criterion_group!(name = acc_status_quo; config = adjusted_criterion(); targets =  tests::status_quo::bench_roundtrip, tests::status_quo::bench_encode, tests::status_quo::bench_encode_into);
+
+criterion_group!(name = acc_additive_fft; config = adjusted_criterion(); targets =  tests::additive_fft::bench_roundtrip, tests::additive_fft::bench_encode, tests::additive_fft::bench_encode_into);
+
+criterion_group!(name = acc_polkadot; config = adjusted_criterion(); targets = bench_polkadot);
+
+criterion_group!(name = acc_multiplier; config = adjusted_criterion(); targets = bench_multiplier);
 
-criterion_main!(acc_novel_poly_basis, acc_status_quo);
+criterion_main!(
+	acc_novel_poly_basis,
+	acc_f2e8,
+	acc_bitsliced,
+	acc_status_quo,
+	acc_additive_fft,
+	acc_polkadot,
+	acc_multiplier
+);
@@ -9,4 +9,28 @@ fn bench_status_quo_encode() {
 	let _ = status_quo::encode(black_box(BYTES));
 }
 
-iai::main!(bench_status_quo_roundtrip, bench_status_quo_encode);
+fn bench_novel_poly_basis_roundtrip() {
+	roundtrip(novel_poly_basis::encode, novel_poly_basis::reconstruct, black_box(BYTES));
+}
+
+fn bench_novel_poly_basis_encode() {
+	let _ = novel_poly_basis::encode(black_box(BYTES));
+}
+
+// interpolating every byte offset is too slow to simulate for all of `BYTES`
+fn bench_additive_fft_roundtrip() {
+	roundtrip(additive_fft::encode, additive_fft::reconstruct, black_box(&BYTES[..1 << 16]));
+}
+
+fn bench_additive_fft_encode() {
+	let _ = additive_fft::encode(black_box(&BYTES[..1 << 16]));
+}
+
+iai::main!(
+	bench_status_quo_roundtrip,
+	bench_status_quo_encode,
+	bench_novel_poly_basis_roundtrip,
+	bench_novel_poly_basis_encode,
+	bench_additive_fft_roundtrip,
+	bench_additive_fft_encode
+);
@@ -3,14 +3,20 @@ use rand::{self, distributions::Uniform, prelude::Distribution};
 use std::env;
 use std::io::Write;
 
+#[allow(dead_code)]
+#[path = "src/novel_poly_basis/cantor.rs"]
+mod cantor;
+
+fn out_dir() -> std::path::PathBuf {
+	std::path::PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is set by cargo after process launch. qed"))
+}
+
 fn gen_10mb_rand_data() -> Result<(), std::io::Error> {
 	let mut rng = rand::thread_rng();
 	let dice = Uniform::<u8>::new_inclusive(0, 255);
 	let data = dice.sample_iter(&mut rng).take(10_000_000).collect::<Vec<_>>();
 
-	let dest =
-		std::path::PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is set by cargo after process launch. qed"))
-			.join("rand_data.bin");
+	let dest = out_dir().join("rand_data.bin");
 
 	let mut f = OpenOptions::new().truncate(true).write(true).create(true).open(&dest)?;
 
@@ -20,6 +26,17 @@ fn gen_10mb_rand_data() -> Result<(), std::io::Error> {
 	Ok(())
 }
 
+/// Tables of the GF(2^8) novel polynomial basis codec, for x^8 + x^4 + x^3 + x^2 + 1.
+fn gen_f2e8_tables() -> Result<(), std::io::Error> {
+	const MODULUS: u64 = 0x11D;
+	let base = cantor::cantor_basis(MODULUS).expect("Degree 8 is a power of two. qed");
+	let tables = cantor::Tables::new(MODULUS, &base[..]);
+	fs_err::write(out_dir().join("novel_poly_basis_f2e8.rs"), tables.to_rust("u8"))
+}
+
 fn main() -> Result<(), std::io::Error> {
-	gen_10mb_rand_data()
+	println!("cargo:rerun-if-changed=build.rs");
+	println!("cargo:rerun-if-changed=src/novel_poly_basis/cantor.rs");
+	gen_10mb_rand_data()?;
+	gen_f2e8_tables()
 }
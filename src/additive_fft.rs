@@ -0,0 +1,253 @@
+// Encoding/erasure decoding for Reed-Solomon codes via additive FFTs in monomial form
+//
+// Shard `i` carries the evaluations at the point `i` of polynomials of degree below `k`,
+// one polynomial per byte offset within the shards. The points form the subspace `0..n`
+// of GF(2^8), so encoding and recovery can use the subspace FFTs and the vanishing polynomial
+// based interpolation of `binaryfield`.
+
+use super::*;
+
+use binaryfield::{AdditiveFft, BinaryField, Element};
+
+/// x^8 + x^4 + x^3 + x^2 + 1, one byte per symbol
+const MODULUS: u64 = binaryfield::moduli::GF_2_8;
+
+fn field() -> &'static BinaryField {
+	use std::sync::OnceLock;
+
+	static FIELD: OnceLock<BinaryField> = OnceLock::new();
+	FIELD.get_or_init(|| BinaryField::new(MODULUS).expect("x^8 + x^4 + x^3 + x^2 + 1 is primitive. qed"))
+}
+
+fn domain(n: usize) -> Vec<Element> {
+	(0..n as u32).map(Element::from).collect()
+}
+
+/// Transforms over the data points `0..k` and over all points `0..n`.
+fn transforms() -> &'static (AdditiveFft<'static>, AdditiveFft<'static>) {
+	use std::sync::OnceLock;
+
+	static TRANSFORMS: OnceLock<(AdditiveFft<'static>, AdditiveFft<'static>)> = OnceLock::new();
+	TRANSFORMS.get_or_init(|| {
+		(AdditiveFft::new(field(), &domain(DATA_SHARDS)[..]), AdditiveFft::new(field(), &domain(N_VALIDATORS)[..]))
+	})
+}
+
+pub fn encode(data: &[u8]) -> Vec<WrappedShard> {
+	let mut shards = vec![WrappedShard::new(vec![0u8; shard_len(data.len(), DATA_SHARDS)]); N_VALIDATORS];
+	let mut views = shards.iter_mut().map(|shard| shard.as_mut()).collect::<Vec<&mut [u8]>>();
+	encode_into(data, &mut views[..]);
+	shards
+}
+
+backend_wrappers!();
+
+/// [`encode_into`] for `n` up to 128 points and `k` data shards, both powers of two.
+pub fn encode_into_sub(data: &[u8], shards: &mut [&mut [u8]], n: usize, k: usize) {
+	let shard_len = shard_len(data.len(), k);
+	encode_into_with(data, shards, n, k, shard_len, |data_shards, parity_shards| {
+		encode_parity_sub(data_shards, parity_shards, n, k)
+	});
+}
+
+/// The points `0..n` must leave room for the interpolation domain of twice the size in GF(2^8).
+fn check_params(n: usize, k: usize) {
+	assert!(k < n && n <= 1 << 7, "The points are the subspace 0..n of GF(2^8), with n up to 128");
+}
+
+/// [`encode_parity`] for `n` up to 128 points and `k` data shards, both powers of two.
+pub fn encode_parity_sub(data_shards: &[&[u8]], parity_shards: &mut [&mut [u8]], n: usize, k: usize) {
+	check_params(n, k);
+	assert_eq!(data_shards.len(), k);
+	assert_eq!(parity_shards.len(), n - k);
+	let shard_len = data_shards[0].len();
+	assert!(data_shards.iter().all(|shard| shard.len() == shard_len));
+	assert!(parity_shards.iter().all(|shard| shard.len() == shard_len));
+
+	// only the transforms of the default code are kept around
+	let owned;
+	let (data_transform, transform) = if (n, k) == (N_VALIDATORS, DATA_SHARDS) {
+		let (data_transform, transform) = transforms();
+		(data_transform, transform)
+	} else {
+		owned = (AdditiveFft::new(field(), &domain(k)[..]), AdditiveFft::new(field(), &domain(n)[..]));
+		(&owned.0, &owned.1)
+	};
+
+	let mut poly = vec![Element::zero(); n];
+	for byte_idx in 0..shard_len {
+		for (coeff, shard) in poly.iter_mut().zip(data_shards) {
+			*coeff = Element::from(shard[byte_idx] as u16);
+		}
+
+		// the data points `0..k` form a subspace, so the polynomial of degree < k through the data
+		// is the inverse transform, evaluated at all points by the forward one
+		data_transform.inverse(&mut poly[..k]);
+		poly[k..].iter_mut().for_each(|coeff| *coeff = Element::zero());
+		transform.forward(&mut poly[..]);
+
+		for (evaluation, shard) in poly[k..].iter().zip(parity_shards.iter_mut()) {
+			shard[byte_idx] = evaluation.value() as u8;
+		}
+	}
+}
+
+pub fn reconstruct(received_shards: Vec<Option<WrappedShard>>) -> Option<Vec<u8>> {
+	reconstruct_sub(received_shards, N_VALIDATORS, DATA_SHARDS)
+}
+
+/// Recover the payload from at least `k` of the `n` shards, for the codes [`encode_into_sub`] supports.
+///
+/// Yields `None` if too few shards are present or their lengths do not match.
+/// The result contains the zero padding added when encoding.
+pub fn reconstruct_sub(received_shards: Vec<Option<WrappedShard>>, n: usize, k: usize) -> Option<Vec<u8>> {
+	reconstruct_shards_sub(&received_shards[..], n, k)
+}
+
+/// [`reconstruct`] of borrowed shards.
+pub(crate) fn reconstruct_shards(received_shards: &mut [Option<WrappedShard>]) -> Option<Vec<u8>> {
+	reconstruct_shards_sub(received_shards, N_VALIDATORS, DATA_SHARDS)
+}
+
+fn reconstruct_shards_sub(received_shards: &[Option<WrappedShard>], n: usize, k: usize) -> Option<Vec<u8>> {
+	check_params(n, k);
+	if received_shards.len() != n {
+		return None;
+	}
+
+	// any `k` shards determine the polynomials
+	let (indices, shards): (Vec<usize>, Vec<&[u8]>) = received_shards
+		.iter()
+		.enumerate()
+		.filter_map(|(idx, shard)| shard.as_ref().map(|shard| (idx, AsRef::<[u8]>::as_ref(shard))))
+		.take(k)
+		.unzip();
+	if shards.len() < k {
+		return None;
+	}
+	let shard_len = shards[0].len();
+	if shards.iter().any(|shard| shard.len() != shard_len) {
+		return None;
+	}
+
+	let xs = indices.iter().map(|&idx| Element::from(idx as u32)).collect::<Vec<_>>();
+	let interpolator = binaryfield::Interpolator::new(field(), &xs[..]);
+	// the data points `0..k` are a subspace, the polynomials only need to be evaluated there
+	let owned;
+	let data_transform = if k == DATA_SHARDS {
+		&transforms().0
+	} else {
+		owned = AdditiveFft::new(field(), &domain(k)[..]);
+		&owned
+	};
+
+	let mut recovered = vec![0u8; k * shard_len];
+	let mut ys = vec![Element::zero(); k];
+	for byte_idx in 0..shard_len {
+		for (y, shard) in ys.iter_mut().zip(&shards) {
+			*y = Element::from(shard[byte_idx] as u16);
+		}
+
+		let mut poly = interpolator.interpolate(&ys[..]);
+		poly.resize(k, Element::zero());
+		data_transform.forward(&mut poly[..]);
+
+		for (i, evaluation) in poly.iter().enumerate() {
+			recovered[i * shard_len + byte_idx] = evaluation.value() as u8;
+		}
+	}
+
+	Some(recovered)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn shards_are_evaluations() {
+		let payload = &BYTES[..64];
+		let shards = encode(payload);
+		let shard_len = shard_len(payload.len(), DATA_SHARDS);
+
+		// systematic, the data shards are the payload
+		for (i, shard) in shards[..DATA_SHARDS].iter().enumerate() {
+			assert_eq!(AsRef::<[u8]>::as_ref(shard), &payload[(i * shard_len)..((i + 1) * shard_len)]);
+		}
+
+		// all shards lie on one polynomial of degree < k per byte offset
+		let field = field();
+		let xs = domain(DATA_SHARDS);
+		for byte_idx in 0..shard_len {
+			let ys = shards[..DATA_SHARDS]
+				.iter()
+				.map(|shard| Element::from(AsRef::<[u8]>::as_ref(shard)[byte_idx] as u16))
+				.collect::<Vec<_>>();
+			let poly = field.lagrange_interp(xs.clone(), ys);
+			for (x, shard) in shards.iter().enumerate() {
+				let y = field.eval_poly_at(&poly, Element::from(x as u32));
+				assert_eq!(y.value() as u8, AsRef::<[u8]>::as_ref(shard)[byte_idx]);
+			}
+		}
+	}
+
+	#[test]
+	fn other_code_parameters() {
+		let (n, k) = (32, 8);
+		let payload = &BYTES[..80];
+		let mut shards = vec![vec![0u8; shard_len(payload.len(), k)]; n];
+		let mut views = shards.iter_mut().map(|shard| &mut shard[..]).collect::<Vec<_>>();
+		encode_into_sub(payload, &mut views[..], n, k);
+
+		let field = field();
+		let xs = domain(k);
+		let ys = shards[..k].iter().map(|shard| Element::from(shard[0] as u16)).collect::<Vec<_>>();
+		let poly = field.lagrange_interp(xs, ys);
+		for (x, shard) in shards.iter().enumerate() {
+			assert_eq!(field.eval_poly_at(&poly, Element::from(x as u32)).value() as u8, shard[0]);
+		}
+	}
+
+	#[test]
+	fn other_code_roundtrip() {
+		let (n, k) = (64, 16);
+		let payload = &BYTES[..1000];
+		let mut shards = vec![WrappedShard::new(vec![0u8; shard_len(payload.len(), k)]); n];
+		let mut views = shards.iter_mut().map(|shard| shard.as_mut()).collect::<Vec<&mut [u8]>>();
+		encode_into_sub(payload, &mut views[..], n, k);
+
+		// keep `k` shards spread over the points, all data shards are lost
+		let received = shards
+			.into_iter()
+			.enumerate()
+			.map(|(idx, shard)| if idx >= k && idx % 3 == 0 { Some(shard) } else { None })
+			.collect::<Vec<_>>();
+		assert!(received.iter().flatten().count() >= k);
+		let recovered = reconstruct_sub(received, n, k).unwrap();
+		assert_eq!(&recovered[..payload.len()], payload);
+
+		assert!(reconstruct_sub(vec![None; n], n, k).is_none());
+	}
+
+	#[test]
+	fn reconstruct_from_parity_only() {
+		let payload = &BYTES[..100];
+		let received = encode(payload)
+			.into_iter()
+			.enumerate()
+			.map(|(idx, shard)| if idx < N_VALIDATORS - DATA_SHARDS { None } else { Some(shard) })
+			.collect::<Vec<_>>();
+		let recovered = reconstruct(received).unwrap();
+		assert_eq!(&recovered[..payload.len()], payload);
+	}
+
+	#[test]
+	fn too_few_shards() {
+		let received = encode(&BYTES[..32])
+			.into_iter()
+			.enumerate()
+			.map(|(idx, shard)| if idx < DATA_SHARDS - 1 { Some(shard) } else { None })
+			.collect::<Vec<_>>();
+		assert!(reconstruct(received).is_none());
+	}
+}
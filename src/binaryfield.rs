@@ -1,11 +1,32 @@
+// Arithmetic over binary extension fields GF(2^m), with log/antilog tables,
+// polynomial operations and additive FFTs over subspaces of the field.
+//
+// Port of the binary field helpers from https://github.com/ethereum/research/ (`binary_fft`),
+// operating on polynomials in monomial form, lowest degree coefficient first.
+
 use std::cmp;
+use std::convert::TryFrom;
 use std::fmt;
 
+mod barrett;
+pub use barrett::*;
+
+mod poly;
+pub use poly::*;
+
+mod subproduct;
+pub use subproduct::*;
+
 pub type Result<T> = std::result::Result<T, Error>;
 
-#[derive(Clone, Copy, Eq, PartialOrd, Ord)]
-#[repr(align(2))]
-struct Element(u16);
+/// An element of a binary field, in the polynomial basis.
+///
+/// Addition and subtraction are the field operations, i.e. XOR. Multiplication and division
+/// require the field, see [`BinaryField`]. Polynomials over GF(2) such as the reduction
+/// polynomial are [`RawPoly`], exponents, sizes and indices are plain integers.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Element(u32);
 
 impl fmt::Display for Element {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -19,67 +40,24 @@ impl fmt::Debug for Element {
 	}
 }
 
-// impl fmt::Debug for Vec<Element> {
-//     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-//         f.write_str("[")?;
-//         let mut iter = self.iter();
-//         if let Some(first) = iter.next() {
-//             write!(f, "{}", self.0)?;
-//             for element in iter {
-//                 write!(f, ", {}", self.0)?;
-//             }
-//         }
-//         f.write_str("]")?;
-//         Ok(())
-//     }
-// }
-
 impl Element {
 	#[inline(always)]
-	const fn zero() -> Self {
-		Self(0u16)
+	pub const fn zero() -> Self {
+		Self(0u32)
 	}
 
 	#[inline(always)]
-	const fn one() -> Self {
-		Self(1u16)
+	pub const fn one() -> Self {
+		Self(1u32)
 	}
 
+	/// The underlying bit representation.
 	#[inline(always)]
-	fn log2(&self) -> Element {
-		Self(log2(self.0))
-	}
-
-	#[inline(always)]
-	fn is_power_of_2(&self) -> bool {
-		is_power_of_2(self.0)
-	}
-
-	fn pow_mod(&self, mut exp: Element, modulo: u64) -> Self {
-		let mut val = self.0 as u64;
-		let mut res = 1_u64;
-		while exp != 0 {
-			if exp.0 & 0x1 != 0 {
-				res *= val;
-				res %= modulo;
-			}
-			val *= val;
-			val %= modulo;
-
-			exp >>= 1;
-		}
-		Self(res as u16)
+	pub const fn value(&self) -> u32 {
+		self.0
 	}
 }
 
-// #[target_feature = "step_trait"]
-// impl Step for Element {
-//     fn steps_between(start: &Self, end: &Self) -> Option<usize> {
-//         let val = end.0.saturating_sub(start.0);
-//         Some(val as usize + 1_usize)
-//     }
-// }
-
 impl<R> std::ops::BitXor<R> for Element
 where
 	R: Into<Element>,
@@ -99,105 +77,74 @@ where
 	}
 }
 
-impl<R> std::ops::Mul<R> for Element
-where
-	R: Into<Element>,
-{
+impl std::ops::Add for Element {
 	type Output = Self;
-	fn mul(self, rhs: R) -> Self::Output {
-		let a = self.0;
-		let b = rhs.into().0;
-		Self(raw_mul(a, b))
+	#[allow(clippy::suspicious_arithmetic_impl)] // characteristic two
+	fn add(self, rhs: Self) -> Self::Output {
+		self ^ rhs
 	}
 }
 
-impl<R> std::ops::Rem<R> for Element
-where
-	R: Into<Element>,
-{
-	type Output = Self;
-	fn rem(self, rhs: R) -> Self::Output {
-		let a = self.0;
-		let b = rhs.into().0;
-		Self(raw_mod(a, b))
+impl std::ops::AddAssign for Element {
+	#[allow(clippy::suspicious_op_assign_impl)] // characteristic two
+	fn add_assign(&mut self, rhs: Self) {
+		*self ^= rhs
 	}
 }
 
-impl<R> std::ops::Add<R> for Element
-where
-	R: Into<Element>,
-{
+impl std::ops::Sub for Element {
 	type Output = Self;
-	fn add(self, rhs: R) -> Self::Output {
-		let rhs = rhs.into();
-		Self(self.0 + rhs.0)
+	#[allow(clippy::suspicious_arithmetic_impl)] // characteristic two
+	fn sub(self, rhs: Self) -> Self::Output {
+		self ^ rhs
 	}
 }
 
-impl<R> std::ops::Sub<R> for Element
-where
-	R: Into<Element>,
-{
-	type Output = Self;
-	fn sub(self, rhs: R) -> Self::Output {
-		let rhs = rhs.into();
-		Self(self.0 - rhs.0)
+impl std::ops::SubAssign for Element {
+	#[allow(clippy::suspicious_op_assign_impl)] // characteristic two
+	fn sub_assign(&mut self, rhs: Self) {
+		*self ^= rhs
 	}
 }
 
-impl<R> std::ops::Shl<R> for Element
-where
-	R: Into<Element>,
-{
-	type Output = Self;
-	fn shl(self, rhs: R) -> Self::Output {
-		let rhs = rhs.into();
-		Self(self.0 << rhs.0)
-	}
-}
-impl<R> std::ops::Shr<R> for Element
-where
-	R: Into<Element>,
-{
-	type Output = Self;
-	fn shr(self, rhs: R) -> Self::Output {
-		let rhs = rhs.into();
-		Self(self.0 >> rhs.0)
-	}
-}
-impl<R> std::ops::ShrAssign<R> for Element
-where
-	R: Into<Element>,
-{
-	fn shr_assign(&mut self, rhs: R) {
-		let rhs = rhs.into();
-		self.0 >>= rhs.0;
+impl From<&u16> for Element {
+	#[inline(always)]
+	fn from(inner: &u16) -> Self {
+		Self(*inner as u32)
 	}
 }
-impl<R> std::ops::ShlAssign<R> for Element
-where
-	R: Into<Element>,
-{
-	fn shl_assign(&mut self, rhs: R) {
-		let rhs = rhs.into();
-		self.0 <<= rhs.0;
+
+impl From<u16> for Element {
+	#[inline(always)]
+	fn from(inner: u16) -> Self {
+		Self(inner as u32)
 	}
 }
 
-impl From<&u16> for Element {
+impl From<&u32> for Element {
 	#[inline(always)]
-	fn from(inner: &u16) -> Self {
+	fn from(inner: &u32) -> Self {
 		Self(*inner)
 	}
 }
 
-impl From<u16> for Element {
+impl From<u32> for Element {
 	#[inline(always)]
-	fn from(inner: u16) -> Self {
+	fn from(inner: u32) -> Self {
 		Self(inner)
 	}
 }
 
+impl TryFrom<usize> for Element {
+	type Error = std::num::TryFromIntError;
+
+	/// Fails for values beyond `u32`, whether they fit a field is up to the field.
+	#[inline(always)]
+	fn try_from(inner: usize) -> std::result::Result<Self, Self::Error> {
+		u32::try_from(inner).map(Self)
+	}
+}
+
 impl From<&Element> for Element {
 	#[inline(always)]
 	fn from(inner: &Self) -> Self {
@@ -205,49 +152,109 @@ impl From<&Element> for Element {
 	}
 }
 
-impl From<&usize> for Element {
-	#[inline(always)]
-	fn from(inner: &usize) -> Self {
-		Self(*inner as u16)
+/// A polynomial over GF(2) of degree below 64, bit `i` is the coefficient of `x^i`.
+///
+/// Multiplication is carry-less and `%` the polynomial remainder, as required for reduction polynomials.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct RawPoly(u64);
+
+impl fmt::Debug for RawPoly {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{:#x}", self.0)
 	}
 }
 
-impl From<usize> for Element {
-	#[inline(always)]
-	fn from(inner: usize) -> Self {
-		Self(inner as u16)
+impl fmt::LowerHex for RawPoly {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::LowerHex::fmt(&self.0, f)
 	}
 }
 
-impl From<i32> for Element {
-	#[inline(always)]
-	fn from(inner: i32) -> Self {
-		Self(inner as u16)
+impl RawPoly {
+	/// `x`
+	pub const X: Self = Self(0x2);
+
+	pub const fn new(bits: u64) -> Self {
+		Self(bits)
+	}
+
+	/// The underlying bit representation.
+	pub const fn value(&self) -> u64 {
+		self.0
+	}
+
+	/// The degree, `None` for the zero polynomial.
+	pub const fn degree(&self) -> Option<u32> {
+		if self.0 == 0 {
+			None
+		} else {
+			Some(63 - self.0.leading_zeros())
+		}
+	}
+
+	/// Greatest common divisor.
+	pub const fn gcd(self, other: Self) -> Self {
+		Self(poly_gcd(self.0, other.0))
 	}
 }
 
-impl PartialEq<usize> for Element {
-	fn eq(&self, other: &usize) -> bool {
-		self.0.eq(&(*other as u16))
+impl std::ops::Add for RawPoly {
+	type Output = Self;
+	#[allow(clippy::suspicious_arithmetic_impl)] // characteristic two
+	fn add(self, rhs: Self) -> Self::Output {
+		Self(self.0 ^ rhs.0)
 	}
 }
-impl PartialEq<i32> for Element {
-	fn eq(&self, other: &i32) -> bool {
-		self.0.eq(&(*other as u16))
+
+impl std::ops::Sub for RawPoly {
+	type Output = Self;
+	#[allow(clippy::suspicious_arithmetic_impl)] // characteristic two
+	fn sub(self, rhs: Self) -> Self::Output {
+		Self(self.0 ^ rhs.0)
 	}
 }
-impl PartialEq<u16> for Element {
-	fn eq(&self, other: &u16) -> bool {
-		self.0.eq(other)
+
+impl std::ops::Mul for RawPoly {
+	type Output = Self;
+	/// Carry-less product, the degrees must add up to less than 64.
+	fn mul(self, rhs: Self) -> Self::Output {
+		let (Some(a), Some(b)) = (self.degree(), rhs.degree()) else {
+			return Self(0);
+		};
+		assert!(a + b < 64, "Product of degree {} does not fit", a + b);
+		let mut o = 0_u64;
+		for i in 0..=b {
+			if rhs.0 & (1 << i) != 0 {
+				o ^= self.0 << i;
+			}
+		}
+		Self(o)
 	}
 }
-impl PartialEq<Element> for Element {
-	fn eq(&self, other: &Element) -> bool {
-		self.0.eq(&other.0)
+
+impl std::ops::Rem for RawPoly {
+	type Output = Self;
+	/// Remainder of the polynomial division, `rhs` must not be zero.
+	fn rem(self, rhs: Self) -> Self::Output {
+		assert_ne!(rhs.0, 0, "Division by the zero polynomial");
+		Self(poly_rem(self.0, rhs.0))
 	}
 }
 
-const fn log2(mut x: u16) -> u16 {
+impl From<u64> for RawPoly {
+	fn from(bits: u64) -> Self {
+		Self(bits)
+	}
+}
+
+impl From<Element> for RawPoly {
+	fn from(element: Element) -> Self {
+		Self(element.0 as u64)
+	}
+}
+
+const fn log2(mut x: u32) -> u32 {
 	let mut o = 0;
 	while x > 1 {
 		x >>= 1;
@@ -256,176 +263,426 @@ const fn log2(mut x: u16) -> u16 {
 	o
 }
 
-const fn is_power_of_2(x: u16) -> bool {
-	return x > 0_u16 && x & (x - 1) == 0;
+const fn is_power_of_2(x: u32) -> bool {
+	x > 0_u32 && x & (x - 1) == 0
 }
 
+/// Carry-less product of two field elements, before reduction.
 #[inline(always)]
-fn raw_mul(a: u16, b: u16) -> u16 {
-	if a.saturating_mul(b) == 0 {
-		return 0;
-	}
-	let mut o = 0;
-	for i in 0..(log2(b) + 1) {
-		if (b & (1 << i)) != 0x0 {
-			o ^= a << i
+const fn clmul(a: u32, b: u32) -> u64 {
+	let a = a as u64;
+	let mut o = 0_u64;
+	let mut i = 0;
+	while i < 32 {
+		if b & (1 << i) != 0 {
+			o ^= a << i;
 		}
+		i += 1;
 	}
 	o
 }
 
+/// Reduce a carry-less product modulo the reduction polynomial `pd` of degree `height`.
 #[inline(always)]
-const fn raw_mod(mut a: u16, b: u16) -> u16 {
-	let mut alog = log2(a);
-	let blog = log2(b);
-	while alog >= blog {
-		if a & (1 << alog) != 0x0 {
-			a ^= b << (alog - blog);
+const fn reduce(mut a: u64, pd: u64, height: u32) -> u32 {
+	let mut bit = 63;
+	while bit >= height {
+		if a & (1 << bit) != 0 {
+			a ^= pd << (bit - height);
 		}
-		alog -= 1;
+		bit -= 1;
+	}
+	a as u32
+}
+
+/// Remainder of the polynomial division `a / b` over GF(2), `b` must not be zero.
+const fn poly_rem(mut a: u64, b: u64) -> u64 {
+	let blog = 63 - b.leading_zeros();
+	while a != 0 && 63 - a.leading_zeros() >= blog {
+		a ^= b << (63 - a.leading_zeros() - blog);
+	}
+	a
+}
+
+/// Greatest common divisor of two polynomials over GF(2).
+const fn poly_gcd(mut a: u64, mut b: u64) -> u64 {
+	while b != 0 {
+		let r = poly_rem(a, b);
+		a = b;
+		b = r;
 	}
 	a
 }
 
-#[derive(Debug, thiserror::Error)]
-enum Error {
-	#[error("The provided modulues {0:?} is bad")]
-	Badpd(Element),
+/// Degree of the modulus `pd`, if it is a supported one.
+fn degree(pd: u64) -> Result<u32> {
+	if pd < 2 || pd >> 33 != 0 {
+		return Err(Error::UnsupportedDegree(pd));
+	}
+	Ok(63 - pd.leading_zeros())
 }
 
-//[derive(Debug)]
-struct BinaryField {
-	pd: Element,
-	height: Element,
-	order: Element,
+/// Whether `pd` is irreducible over GF(2), so it defines a field.
+///
+/// Ben-Or's test: `pd` of degree `m` is irreducible iff `gcd(pd, x^(2^i) - x) = 1` for all `i <= m / 2`.
+pub fn is_irreducible(pd: u64) -> Result<bool> {
+	let height = degree(pd)?;
+	// x^(2^i) mod pd
+	let mut x_pow = 0x2_u64;
+	for _ in 0..(height / 2) {
+		x_pow = reduce(clmul(x_pow as u32, x_pow as u32), pd, height) as u64;
+		if poly_gcd(pd, x_pow ^ 0x2) != 1 {
+			return Ok(false);
+		}
+	}
+	Ok(true)
+}
 
+/// Distinct prime factors of `n`.
+fn prime_factors(mut n: u64) -> Vec<u64> {
+	let mut factors = Vec::new();
+	let mut p = 2;
+	while p * p <= n {
+		if n.is_multiple_of(p) {
+			factors.push(p);
+			while n.is_multiple_of(p) {
+				n /= p;
+			}
+		}
+		p += 1;
+	}
+	if n > 1 {
+		factors.push(n);
+	}
+	factors
+}
+
+/// Standard primitive reduction polynomials.
+pub mod moduli {
+	/// x^8 + x^4 + x^3 + x^2 + 1
+	pub const GF_2_8: u64 = 0x11D;
+	/// x^10 + x^3 + 1
+	pub const GF_2_10: u64 = 0x409;
+	/// x^16 + x^5 + x^3 + x^2 + 1, as used by `novel_poly_basis`
+	pub const GF_2_16: u64 = 0x1002D;
+	/// x^32 + x^22 + x^2 + x + 1
+	pub const GF_2_32: u64 = 0x1_0040_0007;
+
+	/// A primitive polynomial of low weight for every degree from 1 to 32, indexed by degree.
+	pub const STANDARD: [u64; 33] = [
+		0,
+		0x3,
+		0x7,
+		0xB,
+		0x13,
+		0x25,
+		0x43,
+		0x83,
+		GF_2_8,
+		0x211,
+		GF_2_10,
+		0x805,
+		0x1053,
+		0x201B,
+		0x4443,
+		0x8003,
+		GF_2_16,
+		0x2_0009,
+		0x4_0081,
+		0x8_0027,
+		0x10_0009,
+		0x20_0005,
+		0x40_0003,
+		0x80_0021,
+		0x100_0087,
+		0x200_0009,
+		0x400_0047,
+		0x800_0027,
+		0x1000_0009,
+		0x2000_0005,
+		0x4080_0007,
+		0x8000_0009,
+		GF_2_32,
+	];
+
+	/// The standard modulus for GF(2^height).
+	pub fn standard(height: u32) -> Option<u64> {
+		STANDARD.get(height as usize).copied().filter(|&pd| pd != 0)
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+	#[error("The degree of the modulus {0:#x} is not supported, it must be within 1..=32")]
+	UnsupportedDegree(u64),
+	#[error("The modulus {0:#x} is reducible and does not define a field")]
+	Reducible(u64),
+	#[error("The modulus {0:#x} is irreducible, but x does not generate its multiplicative group")]
+	NotPrimitive(u64),
+	#[error("There is no standard modulus of height {0}, it must be within 1..=32")]
+	UnsupportedHeight(u32),
+}
+
+/// Fields up to this height use log/antilog tables, larger ones multiply without tables.
+pub const MAX_TABLE_HEIGHT: u32 = 16;
+
+/// The field GF(2^height) defined by the reduction polynomial `pd` of degree `height`.
+///
+/// For heights up to [`MAX_TABLE_HEIGHT`], multiplication and division use a cache of all
+/// powers of a generator. Larger fields such as GF(2^32) reduce carry-less products instead,
+/// see [`Barrett`].
+//[derive(Debug)]
+pub struct BinaryField {
+	pd: u64,
+	height: u32,
+	order: u64,
+	generator: Element,
+	// multiplies without tables
+	barrett: Barrett,
+
+	// both empty for table free fields
 	cache: Vec<Element>,
 	invcache: Vec<Option<usize>>,
 }
 
 impl BinaryField {
-	fn setup(&mut self) -> Result<()> {
-		// XXX why 80?
-		let pd = self.pd.0;
-		let order = self.order.0 as usize;
-
-		for base in 2..cmp::min(pd - 1, 80_u16) {
-			let mut powers: Vec<Element> = vec![Element::one()];
-			'p: while powers.len() < order + 2 {
-				let previous = powers.last().unwrap();
-				let val = raw_mod(raw_mul(previous.0, base), pd);
-				powers.push(val.into());
-				if val == 1 {
-					break 'p;
-				}
-			}
-			let _ = powers.pop();
-			if powers.len() == order {
-				self.cache = powers.clone();
-				self.cache.extend(powers.iter().cloned());
-				self.invcache = vec![None; order + 1];
-				for (idx, p) in powers.into_iter().enumerate() {
-					self.invcache[p.0 as usize] = Some(idx);
-				}
-				return Ok(());
-			}
+	/// Whether `g` generates the multiplicative group, given the prime factors of the order.
+	fn is_generator(&self, g: Element, factors: &[u64]) -> bool {
+		g != Element::zero() && factors.iter().all(|&p| self.exp(g, self.order / p) != Element::one())
+	}
+
+	/// Find the smallest primitive element, irreducible moduli always have one.
+	fn find_generator(&self, factors: &[u64]) -> Element {
+		(1..=(self.order as u32))
+			.map(Element)
+			.find(|&g| self.is_generator(g, factors))
+			.expect("The multiplicative group of a field is cyclic. qed")
+	}
+
+	fn setup(&mut self) {
+		let order = self.order as usize;
+
+		let mut powers: Vec<Element> = Vec::with_capacity(order);
+		let mut power = Element::one();
+		for _ in 0..order {
+			powers.push(power);
+			power = self.mul(power, self.generator);
+		}
+		debug_assert_eq!(power, Element::one());
+
+		self.invcache = vec![None; order + 1];
+		for (idx, p) in powers.iter().enumerate() {
+			self.invcache[p.0 as usize] = Some(idx);
 		}
-		Err(Error::Badpd(self.pd))
+		self.cache = powers.clone();
+		self.cache.extend(powers);
 	}
 
-	pub fn new(pd: Element) -> Result<Self> {
-		let height = pd.log2();
+	fn build(pd: u64, tables: bool) -> Result<Self> {
+		let height = degree(pd)?;
+		if !is_irreducible(pd)? {
+			return Err(Error::Reducible(pd));
+		}
 		let mut field = Self {
 			pd,
 			height,
-			order: (Element::one() << height) - Element::one(),
+			order: (1_u64 << height) - 1,
+			generator: Element::one(),
+			barrett: Barrett::new(pd)?,
 			cache: Default::default(),
 			invcache: Default::default(),
 		};
 
-		field.setup()?;
+		let factors = prime_factors(field.order);
+		// prefer x, so the tables are the usual powers of x
+		let x = Element(reduce(0x2, pd, height));
+		field.generator = if field.is_generator(x, &factors[..]) { x } else { field.find_generator(&factors[..]) };
+
+		if tables {
+			field.setup();
+		}
 
 		Ok(field)
 	}
 
+	/// Create the field for the irreducible reduction polynomial `pd` of degree at most 32,
+	/// i.e. `1033` for `x^10 + x^3 + 1`. See [`moduli`] for standard choices.
+	///
+	/// The generator is `x` if `pd` is primitive, the smallest primitive element otherwise.
+	pub fn new(pd: u64) -> Result<Self> {
+		Self::build(pd, pd >> (MAX_TABLE_HEIGHT + 1) == 0)
+	}
+
+	/// Like [`BinaryField::new`], but `pd` must be primitive, so `x` generates the field.
+	pub fn new_primitive(pd: u64) -> Result<Self> {
+		let field = Self::new(pd)?;
+		if field.generator != Element(reduce(0x2, pd, field.height)) {
+			return Err(Error::NotPrimitive(pd));
+		}
+		Ok(field)
+	}
+
+	/// The standard field GF(2^height), see [`moduli::standard`].
+	pub fn standard(height: u32) -> Result<Self> {
+		let pd = moduli::standard(height).ok_or(Error::UnsupportedHeight(height))?;
+		Self::new(pd)
+	}
+
+	fn has_tables(&self) -> bool {
+		!self.cache.is_empty()
+	}
+
+	/// The primitive element whose powers the tables hold.
+	pub fn generator(&self) -> Element {
+		self.generator
+	}
+
+	/// The reduction polynomial.
+	pub fn modulus(&self) -> RawPoly {
+		RawPoly(self.pd)
+	}
+
+	/// Degree of the extension, the number of bits of an element.
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	/// Number of non-zero elements, `2^height - 1`.
+	pub fn order(&self) -> u64 {
+		self.order
+	}
+
 	// binary field special
-	fn add(&self, x: Element, y: Element) -> Element {
+	pub fn add(&self, x: Element, y: Element) -> Element {
 		x ^ y
 	}
 
-	fn sub(&self, x: Element, y: Element) -> Element {
+	pub fn sub(&self, x: Element, y: Element) -> Element {
 		self.add(x, y)
 	}
 
-	fn mul(&self, x: Element, y: Element) -> Element {
-		if x.0 as u32 * y.0 as u32 == 0 {
+	pub fn mul(&self, x: Element, y: Element) -> Element {
+		if x.0 == 0 || y.0 == 0 {
 			Element::zero()
-		} else {
+		} else if self.has_tables() {
 			let idx = self.invcache[x.0 as usize].unwrap() + self.invcache[y.0 as usize].unwrap();
 			self.cache[idx]
+		} else {
+			Element(self.barrett.mul_raw(x.0, y.0))
 		}
 	}
 
-	fn sqr(&self, x: Element) -> Element {
+	pub fn sqr(&self, x: Element) -> Element {
 		if x == Element::zero() {
 			Element::zero()
-		} else {
-			let idx = (self.invcache[x.0 as usize].unwrap() << 1_usize) % self.order.0 as usize;
+		} else if self.has_tables() {
+			let idx = (self.invcache[x.0 as usize].unwrap() << 1_usize) % self.order as usize;
 			self.cache[idx]
+		} else {
+			self.mul(x, x)
 		}
 	}
 
-	fn div(&self, x: Element, y: Element) -> Element {
-		if x == 0 {
+	/// `x / y`, `y` must not be zero.
+	pub fn div(&self, x: Element, y: Element) -> Element {
+		if x == Element::zero() {
 			Element::zero()
+		} else if self.has_tables() {
+			let idx = self.order as usize + self.invcache[x.0 as usize].unwrap() - self.invcache[y.0 as usize].unwrap();
+			self.cache[idx]
 		} else {
-			let idx: Element = self.order + self.invcache[x.0 as usize].unwrap() - self.invcache[y.0 as usize].unwrap();
-			self.cache[idx.0 as usize]
+			self.mul(x, self.inv(y))
 		}
 	}
 
-	fn inv(&self, x: Element) -> Element {
+	/// Multiplicative inverse, `x` must not be zero.
+	pub fn inv(&self, x: Element) -> Element {
 		assert_ne!(x, Element::zero());
-		let idx = ((self.order - self.invcache[x.0 as usize].unwrap()) % self.order).0;
-		self.cache[idx as usize]
+		if self.has_tables() {
+			let order = self.order as usize;
+			let idx = (order - self.invcache[x.0 as usize].unwrap()) % order;
+			self.cache[idx]
+		} else {
+			// x^(2^height - 2) = x^-1
+			self.exp(x, self.order - 1)
+		}
 	}
 
-	fn exp(&self, x: Element, p: Element) -> Element {
-		if p == Element::zero() {
+	/// `x^p`.
+	pub fn exp(&self, x: Element, p: u64) -> Element {
+		if p == 0 {
 			Element::one()
 		} else if x == Element::zero() {
-			Element::one()
+			Element::zero()
+		} else if self.has_tables() {
+			let idx = (self.invcache[x.0 as usize].unwrap() as u64 * (p % self.order)) % self.order;
+			self.cache[idx as usize]
 		} else {
-			let idx = (self.invcache[x.0 as usize].unwrap() * (p.0 as usize)) % self.order.0 as usize;
-			self.cache[idx]
+			let (mut base, mut p, mut res) = (x, p, Element::one());
+			while p != 0 {
+				if p & 0x1 != 0 {
+					res = self.mul(res, base);
+				}
+				base = self.sqr(base);
+				p >>= 1;
+			}
+			res
 		}
 	}
 
-	fn multi_inv(&self, values: Vec<Option<Element>>) -> Vec<Element> {
+	/// Discrete logarithm of `x` to the base [`BinaryField::generator`], `x` must not be zero.
+	///
+	/// Without tables this is a baby-step giant-step search in `O(sqrt(order))`.
+	pub fn log(&self, x: Element) -> u64 {
+		assert_ne!(x, Element::zero());
+		if self.has_tables() {
+			return self.invcache[x.0 as usize].unwrap() as u64;
+		}
+
+		let steps = (self.order as f64).sqrt().ceil() as u64;
+		let mut baby = std::collections::HashMap::with_capacity(steps as usize);
+		let mut power = Element::one();
+		for j in 0..steps {
+			baby.entry(power).or_insert(j);
+			power = self.mul(power, self.generator);
+		}
+		// power is now g^steps
+		let giant = self.inv(power);
+		let mut gamma = x;
+		for i in 0..steps {
+			if let Some(&j) = baby.get(&gamma) {
+				return (i * steps + j) % self.order;
+			}
+			gamma = self.mul(gamma, giant);
+		}
+		unreachable!("The generator spans the multiplicative group. qed")
+	}
+
+	/// Invert all `values` at the cost of a single inversion (Montgomery's trick).
+	///
+	/// Zeros have no inverse and map to zero.
+	pub fn multi_inv(&self, values: &[Element]) -> Vec<Element> {
 		let mut partials = vec![Element::one()];
-		for i in 0..values.len() {
-			let last = partials.last().unwrap().clone();
-			partials.push(self.mul(last, values[i].unwrap_or(Element::zero())))
+		for &value in values {
+			let last = *partials.last().unwrap();
+			partials.push(if value == Element::zero() { last } else { self.mul(last, value) })
 		}
-		let mut inv = self.inv(partials.last().unwrap().clone());
+		let mut inv = self.inv(*partials.last().unwrap());
 		let mut outputs = vec![Element::zero(); values.len()];
-		for (i, value) in values.into_iter().enumerate().rev() {
-			outputs[i] = if value.is_some() { self.mul(partials[i], inv) } else { Element::zero() };
-			inv = self.mul(inv, value.unwrap_or(Element::one()))
+		for (i, &value) in values.iter().enumerate().rev() {
+			if value != Element::zero() {
+				outputs[i] = self.mul(partials[i], inv);
+				inv = self.mul(inv, value);
+			}
 		}
 		outputs
 	}
 
-	// TODO XXX understand why there are two diff impls
-	fn div_(&self, x: Element, y: Element) -> Element {
-		self.mul(x, self.inv(y))
-	}
-
-	// Evaluate a polynomial at a point
-	fn eval_poly_at(&self, p: &[Element], x: Element) -> Element {
+	/// Evaluate the polynomial `p` at `x`.
+	pub fn eval_poly_at(&self, p: &[Element], x: Element) -> Element {
 		let mut y = Element::zero();
 		let mut power_of_x = Element::one();
-		for (i, &p_coeff) in p.into_iter().enumerate() {
+		for &p_coeff in p.iter() {
 			y ^= self.mul(power_of_x, p_coeff);
 			power_of_x = self.mul(power_of_x, x);
 		}
@@ -433,27 +690,26 @@ impl BinaryField {
 	}
 
 	// Arithmetic for polynomials
-	fn add_polys(&self, a: Vec<Element>, b: Vec<Element>) -> Vec<Element> {
+	pub fn add_polys(&self, a: Vec<Element>, b: Vec<Element>) -> Vec<Element> {
 		let deg = cmp::max(a.len(), b.len());
-		let mut res = a.clone();
-		if deg < b.len() {
-			res.extend(std::iter::repeat(Element::zero()).take(b.len() - deg))
-		}
-		for i in 0..deg {
-			res[i] ^= b.get(i).cloned().unwrap_or(Element::zero());
+		let mut res = a;
+		res.resize(deg, Element::zero());
+		for (r, b) in res.iter_mut().zip(b) {
+			*r ^= b;
 		}
 		res
 	}
 
-	fn sub_polys(&self, a: Vec<Element>, b: Vec<Element>) -> Vec<Element> {
+	pub fn sub_polys(&self, a: Vec<Element>, b: Vec<Element>) -> Vec<Element> {
 		self.add_polys(a, b)
 	}
 
-	fn mul_by_const(&self, a: &[Element], c: Element) -> Vec<Element> {
-		a.into_iter().map(move |x| self.mul(*x, c)).collect::<Vec<Element>>()
+	pub fn mul_by_const(&self, a: &[Element], c: Element) -> Vec<Element> {
+		a.iter().map(move |x| self.mul(*x, c)).collect::<Vec<Element>>()
 	}
 
-	fn mul_polys(&self, a: Vec<Element>, b: Vec<Element>) -> Vec<Element> {
+	/// Schoolbook multiplication of two non-empty polynomials.
+	pub fn mul_polys(&self, a: Vec<Element>, b: Vec<Element>) -> Vec<Element> {
 		let mut o = vec![Element::zero(); a.len() + b.len() - 1];
 		for (i, &aval) in a.iter().enumerate() {
 			for (j, &bval) in b.iter().enumerate() {
@@ -463,26 +719,27 @@ impl BinaryField {
 		o
 	}
 
-	fn div_polys(&self, mut a: Vec<Element>, b: Vec<Element>) -> Vec<Element> {
+	/// Quotient of `a / b`, the leading coefficient of `b` must not be zero.
+	pub fn div_polys(&self, mut a: Vec<Element>, b: Vec<Element>) -> Vec<Element> {
 		assert!(a.len() >= b.len());
 		let mut o = vec![];
 		let mut apos = a.len() - 1_usize;
-		let mut bpos = b.len() - 1_usize;
+		let bpos = b.len() - 1_usize;
 		let mut diff = apos as isize - bpos as isize;
 		while diff >= 0_isize {
 			let quot = self.div(a[apos], b[bpos]);
 			o.insert(0, quot);
-			for (i, b) in (0..bpos).into_iter().enumerate().rev() {
-				a[(diff + i as isize) as usize] ^= self.mul(Element::from(b), quot);
+			for i in (0..=bpos).rev() {
+				a[diff as usize + i] ^= self.mul(b[i], quot);
 			}
-			apos -= 1_usize;
+			apos = apos.saturating_sub(1);
 			diff -= 1_isize;
 		}
 		o
 	}
 
-	// Build a polynomial that returns 0 at all specified xs
-	fn zpoly(&self, xs: &[Element]) -> Vec<Element> {
+	/// Build a polynomial that returns 0 at all specified xs, quadratic in `xs.len()`.
+	pub fn zpoly(&self, xs: &[Element]) -> Vec<Element> {
 		let mut roots = vec![Element::one()];
 		for &x in xs {
 			roots.insert(0, Element::zero());
@@ -503,7 +760,7 @@ impl BinaryField {
 	//    y coordinate at that point && 0 at all other points provided.
 	// 3. Add these polynomials together.
 
-	fn lagrange_interp(&self, xs: Vec<Element>, ys: Vec<Element>) -> Vec<Element> {
+	pub fn lagrange_interp(&self, xs: Vec<Element>, ys: Vec<Element>) -> Vec<Element> {
 		// // Generate master numerator polynomial, eg. (x - x1) * (x - x2) * ... * (x - xn)
 		let root = self.zpoly(&xs);
 		assert_eq!(root.len(), ys.len() + 1);
@@ -511,15 +768,11 @@ impl BinaryField {
 		// // Generate per-value numerator polynomials, eg. for x=x2,
 		// // (x - x1) * (x - x3) * ... * (x - xn), by dividing the master
 		// // polynomial back by each x coordinate
-		let mut nums =
+		let nums =
 			xs.iter().map(|&x| self.div_polys(root.clone(), vec![x, Element::one()])).collect::<Vec<Vec<Element>>>();
 		// Generate denominators by evaluating numerator polys at each x
-		let denoms = xs
-			.iter()
-			.zip(nums.iter())
-			.map(|(&x, num)| Some(self.eval_poly_at(num, x)))
-			.collect::<Vec<Option<Element>>>();
-		let invdenoms = self.multi_inv(denoms);
+		let denoms = xs.iter().zip(nums.iter()).map(|(&x, num)| self.eval_poly_at(num, x)).collect::<Vec<Element>>();
+		let invdenoms = self.multi_inv(&denoms);
 		// Generate output polynomial, which is the sum of the per-value numerator
 		// polynomials rescaled to have the right y values
 		let mut b = vec![Element::zero(); ys.len()];
@@ -536,32 +789,82 @@ impl BinaryField {
 	}
 }
 
-fn _simple_ft(field: &BinaryField, domain: &[Element], poly: &[Element]) -> Vec<Element> {
-	domain.into_iter().map(|&item| field.eval_poly_at(poly, item)).collect::<Vec<Element>>()
+impl crate::galois::GaloisField for BinaryField {
+	type Element = Element;
+
+	fn bits(&self) -> u32 {
+		self.height
+	}
+
+	fn element(&self, value: u32) -> Element {
+		debug_assert_eq!(value as u64 & !self.order, 0);
+		Element(value)
+	}
+
+	fn value(&self, a: Element) -> u32 {
+		a.0
+	}
+
+	fn one(&self) -> Element {
+		Element::one()
+	}
+
+	fn add(&self, a: Element, b: Element) -> Element {
+		BinaryField::add(self, a, b)
+	}
+
+	fn mul(&self, a: Element, b: Element) -> Element {
+		BinaryField::mul(self, a, b)
+	}
+
+	fn inv(&self, a: Element) -> Element {
+		BinaryField::inv(self, a)
+	}
+
+	fn div(&self, a: Element, b: Element) -> Element {
+		BinaryField::div(self, a, b)
+	}
+
+	fn generator(&self) -> Element {
+		self.generator
+	}
+
+	fn exp(&self, n: u64) -> Element {
+		BinaryField::exp(self, self.generator, n)
+	}
+
+	fn log(&self, a: Element) -> u64 {
+		BinaryField::log(self, a)
+	}
+
+	fn pow(&self, a: Element, n: u64) -> Element {
+		BinaryField::exp(self, a, n)
+	}
 }
 
-// Returns `evens` && `odds` such that{
-// poly(x) = evens(x**2+kx) + x * odds(x**2+kx)
-// poly(x+k) = evens(x**2+kx) + (x+k) * odds(x**2+kx)
-//
-// Note that this satisfies two other invariants{
-//
-// poly(x+k) - poly(x) = k * odds(x**2+kx)
-// poly(x)*(x+k) - poly(x+k)*x = k * evens(x**2+kx)
+fn _simple_ft(field: &BinaryField, domain: &[Element], poly: &[Element]) -> Vec<Element> {
+	domain.iter().map(|&item| field.eval_poly_at(poly, item)).collect::<Vec<Element>>()
+}
 
-fn cast(field: &BinaryField, poly: &[Element], k: Element) -> (Vec<Element>, Vec<Element>) {
-	println!("cast");
+/// Returns `evens` && `odds` such that
+/// poly(x) = evens(x**2+kx) + x * odds(x**2+kx)
+/// poly(x+k) = evens(x**2+kx) + (x+k) * odds(x**2+kx)
+///
+/// Note that this satisfies two other invariants
+///
+/// poly(x+k) - poly(x) = k * odds(x**2+kx)
+/// poly(x)*(x+k) - poly(x+k)*x = k * evens(x**2+kx)
+///
+/// The length of `poly` must be a power of two.
+pub fn cast(field: &BinaryField, poly: &[Element], k: Element) -> (Vec<Element>, Vec<Element>) {
 	if poly.len() <= 2 {
 		return (vec![poly[0]], vec![if poly.len() == 2 { poly[1] } else { Element::zero() }]);
 	}
-	assert!(is_power_of_2(dbg!(poly.len()) as u16));
+	assert!(is_power_of_2(poly.len() as u32));
 
 	let mod_power = poly.len() >> 1_usize;
 	let half_mod_power = mod_power >> 1_usize;
-	let k_to_half_mod_power = field.exp(k, half_mod_power.into());
-	dbg!(mod_power);
-	dbg!(half_mod_power);
-	dbg!(k_to_half_mod_power);
+	let k_to_half_mod_power = field.exp(k, half_mod_power as u64);
 
 	assert_eq!(mod_power, 2 * half_mod_power); // holds since poly is single val / one bit set / 2^x
 
@@ -569,7 +872,6 @@ fn cast(field: &BinaryField, poly: &[Element], k: Element) -> (Vec<Element>, Vec
 	// && high = poly // (x**2 - k*x)**half_mod_power
 	// Note that (x**2 - k*x)**n = x**2n - k**n * x**n in binary fields
 	let mut low_and_high = poly.to_vec();
-	dbg!(poly.len());
 
 	{
 		let (low, high) = low_and_high.split_at_mut(mod_power + half_mod_power);
@@ -585,23 +887,25 @@ fn cast(field: &BinaryField, poly: &[Element], k: Element) -> (Vec<Element>, Vec
 	}
 	let (low, high) = low_and_high.split_at(mod_power);
 	// Recursively compute two half-size sub-problems, low && high
-	let mut low_cast = dbg!(cast(field, low, k));
-	let high_cast = dbg!(cast(field, high, k));
+	let mut low_cast = cast(field, low, k);
+	let high_cast = cast(field, high, k);
 	// Combine the results
 	(
 		{
-			low_cast.0.extend(high_cast.0.into_iter());
+			low_cast.0.extend(high_cast.0);
 			low_cast.0
 		},
 		{
-			low_cast.1.extend(high_cast.1.into_iter());
+			low_cast.1.extend(high_cast.1);
 			low_cast.1
 		},
 	)
 }
 
-// Returns a polynomial p2 such that p2(x) = poly(x**2+kx)
-fn compose(field: &BinaryField, poly: &[Element], k: Element) -> Vec<Element> {
+/// Returns a polynomial p2 such that p2(x) = poly(x**2+kx)
+///
+/// The length of `poly` must be a power of two.
+pub fn compose(field: &BinaryField, poly: &[Element], k: Element) -> Vec<Element> {
 	if poly.len() == 2 {
 		return vec![poly[0], field.mul(poly[1], k), poly[1], Element::zero()];
 	}
@@ -611,9 +915,9 @@ fn compose(field: &BinaryField, poly: &[Element], k: Element) -> Vec<Element> {
 		return res;
 	}
 	// Largest mod_power=2**k such that mod_power >= poly.len()/2
-	assert!(is_power_of_2(poly.len() as u16));
+	assert!(is_power_of_2(poly.len() as u32));
 	let mod_power: usize = poly.len() >> 1_usize;
-	let k_to_mod_power = field.exp(k, mod_power.into());
+	let k_to_mod_power = field.exp(k, mod_power as u64);
 	// Recursively compute two half-size sub-problems, the bottom && top half
 	// of the polynomial
 	let low = compose(field, &poly[..mod_power], k);
@@ -629,131 +933,257 @@ fn compose(field: &BinaryField, poly: &[Element], k: Element) -> Vec<Element> {
 	o
 }
 
-// Equivalent to [field.eval_poly_at(poly, x) for x in domain]
-// Special thanks to www.math.clemson.edu/~sgao/papers/GM10.pdf for insights
-// though this algorithm is not exactly identical to any algorithm in the paper
-fn fft(field: &BinaryField, domain: &[Element], poly: &[Element]) -> Vec<Element> {
-	println!("fft");
-	// Base case: constant polynomials
-	// if domain.len() == 1{
-	//     return [poly[0]]
-	dbg!(domain.len());
-	dbg!(poly.len());
-	if domain.len() <= 8 {
-		return _simple_ft(field, domain, poly);
-	}
-	// Split the domain into two cosets A && B, where for x in A, x+offset is in B
-	let offset = domain[1];
-	dbg!(offset);
-	// Get evens, odds such that{
-	// poly(x) = evens(x**2+offset*x) + x * odds(x**2+offset*x)
-	// poly(x+k) = evens(x**2+offset*x) + (x+k) * odds(x**2+offset*x)
-	let (evens, odds) = cast(field, poly, offset);
-	dbg!(evens.len());
-	dbg!(odds.len());
-	// The smaller domain D = [x**2 - offset*x for x in A] = [x**2 - offset*x for x in B]
-	let cast_domain = domain.iter().step_by(2).map(|&x| field.mul(x, offset ^ x)).collect::<Vec<Element>>();
-	dbg!(&cast_domain);
-	// Two half-size sub-problems over the smaller domain, recovering
-	// evaluations of evens && odds over the smaller domain
-	let even_points = fft(field, &cast_domain[..], &evens[..]);
-	let odd_points = fft(field, &cast_domain[..], &odds[..]);
-	// Combine the evaluations of evens && odds into evaluations of poly
-	let dl = domain.len() >> 1_usize;
-	let mut o = Vec::with_capacity(dl << 1_usize);
-	for i in 0..dl {
-		o.push(even_points[i] ^ field.mul(domain[i * 2], odd_points[i]));
-		o.push(even_points[i] ^ field.mul(domain[i * 2 + 1], odd_points[i]));
-	}
-	o
+/// Twiddles of one layer of an [`AdditiveFft`].
+struct Layer {
+	// `k` such that `domain[2i + 1] = domain[2i] + k` on this layer
+	offset: Element,
+	inv_offset: Element,
+	// the even points `domain[2i]` of this layer
+	points: Vec<Element>,
+	// `k^(2^t)`, the factors of the Taylor expansion at `x^2 + kx`
+	offset_pows: Vec<Element>,
 }
 
-// The inverse function of fft, does the steps backwards
-fn invfft(field: &BinaryField, domain: &[Element], vals: &[Element]) -> Vec<Element> {
-	// Base case: constant polynomials
-	if domain.len() == 1 {
-		return vals.to_vec();
+/// An iterative, in-place additive FFT over a fixed domain.
+///
+/// The domain must be a subspace of the field in the order `0..2^m`, i.e. `domain[2i + 1] = domain[2i] + domain[1]`,
+/// which then holds for every layer. All twiddles are precomputed, so the transforms do not allocate.
+///
+/// Layer `l` splits each of its `2^l` sub-problems `poly(x) = evens(x^2 + kx) + x * odds(x^2 + kx)` in place,
+/// sub-problem `r` occupies the positions `r + j * 2^l`, so no permutation is required and
+/// the evaluations end up in domain order.
+///
+/// Special thanks to www.math.clemson.edu/~sgao/papers/GM10.pdf for insights
+/// though this algorithm is not exactly identical to any algorithm in the paper
+pub struct AdditiveFft<'a> {
+	field: &'a BinaryField,
+	n: usize,
+	layers: Vec<Layer>,
+}
+
+impl<'a> AdditiveFft<'a> {
+	/// Precompute the twiddles for `domain`, whose length must be a power of two.
+	pub fn new(field: &'a BinaryField, domain: &[Element]) -> Self {
+		let n = domain.len();
+		assert!(is_power_of_2(n as u32), "Domain size {} is not a power of two", n);
+
+		let mut layers = Vec::with_capacity(log2(n as u32) as usize);
+		let mut domain = domain.to_vec();
+		while domain.len() > 1 {
+			let offset = domain[1];
+			assert_ne!(offset, Element::zero(), "The domain is not a subspace");
+
+			let mut offset_pows = Vec::new();
+			let mut pow = offset;
+			for _ in 0..log2(domain.len() as u32) {
+				offset_pows.push(pow);
+				pow = field.sqr(pow);
+			}
+
+			let points = domain.iter().step_by(2).copied().collect::<Vec<_>>();
+			// the smaller domain of the next layer, [x**2 - offset*x for x in domain]
+			domain = points.iter().map(|&x| field.mul(x, offset ^ x)).collect();
+			layers.push(Layer { offset, inv_offset: field.inv(offset), points, offset_pows });
+		}
+
+		Self { field, n, layers }
+	}
+
+	/// Number of points of the domain.
+	pub fn len(&self) -> usize {
+		self.n
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.n == 0
+	}
+
+	/// Taylor expansion of the sub-problem `r` of `layer` at `x^2 + kx`, the in place equivalent of [`cast`].
+	///
+	/// Afterwards the evens are at the even, the odds at the odd positions of the sub-problem.
+	fn cast(&self, layer: &Layer, data: &mut [Element], r: usize, stride: usize) {
+		let field = self.field;
+		let m = self.n / stride;
+		let at = |j: usize| r + j * stride;
+
+		let mut size = m;
+		while size >= 4 {
+			let (half, mod_power) = (size >> 2, size >> 1);
+			let k_to_half = layer.offset_pows[log2(half as u32) as usize];
+			for start in (0..m).step_by(size) {
+				for i in 0..half {
+					let high = data[at(start + mod_power + half + i)];
+					data[at(start + mod_power + i)] ^= field.mul(high, k_to_half);
+				}
+				for i in 0..half {
+					let high = data[at(start + mod_power + i)];
+					data[at(start + half + i)] ^= field.mul(high, k_to_half);
+				}
+			}
+			size >>= 1;
+		}
+	}
+
+	/// Inverse of [`AdditiveFft::cast`], the in place equivalent of [`compose`].
+	fn uncast(&self, layer: &Layer, data: &mut [Element], r: usize, stride: usize) {
+		let field = self.field;
+		let m = self.n / stride;
+		let at = |j: usize| r + j * stride;
+
+		let mut size = 4;
+		while size <= m {
+			let (half, mod_power) = (size >> 2, size >> 1);
+			let k_to_half = layer.offset_pows[log2(half as u32) as usize];
+			for start in (0..m).step_by(size) {
+				for i in 0..half {
+					let high = data[at(start + mod_power + i)];
+					data[at(start + half + i)] ^= field.mul(high, k_to_half);
+				}
+				for i in 0..half {
+					let high = data[at(start + mod_power + half + i)];
+					data[at(start + mod_power + i)] ^= field.mul(high, k_to_half);
+				}
+			}
+			size <<= 1;
+		}
+	}
+
+	/// Replace the coefficients in `data` by the evaluations over the domain.
+	pub fn forward(&self, data: &mut [Element]) {
+		assert_eq!(data.len(), self.n);
+		let field = self.field;
+
+		for (l, layer) in self.layers.iter().enumerate() {
+			let stride = 1 << l;
+			for r in 0..stride {
+				self.cast(layer, data, r, stride);
+			}
+		}
+
+		// combine the evaluations of evens and odds, bottom up
+		for (l, layer) in self.layers.iter().enumerate().rev() {
+			let stride = 1 << l;
+			for r in 0..stride {
+				for (i, &x) in layer.points.iter().enumerate() {
+					let (even, odd) = (r + 2 * i * stride, r + (2 * i + 1) * stride);
+					// poly(x) = evens(x**2+kx) + x * odds(x**2+kx), poly(x+k) = poly(x) + k * odds(x**2+kx)
+					data[even] ^= field.mul(x, data[odd]);
+					data[odd] = data[even] ^ field.mul(layer.offset, data[odd]);
+				}
+			}
+		}
+	}
+
+	/// Replace the evaluations over the domain in `data` by the coefficients.
+	pub fn inverse(&self, data: &mut [Element]) {
+		assert_eq!(data.len(), self.n);
+		let field = self.field;
+
+		for (l, layer) in self.layers.iter().enumerate() {
+			let stride = 1 << l;
+			for r in 0..stride {
+				for (i, &x) in layer.points.iter().enumerate() {
+					let (even, odd) = (r + 2 * i * stride, r + (2 * i + 1) * stride);
+					// poly(x+k) - poly(x) = k * odds(x**2+kx)
+					let odds = field.mul(data[even] ^ data[odd], layer.inv_offset);
+					data[even] ^= field.mul(x, odds);
+					data[odd] = odds;
+				}
+			}
+		}
+
+		for (l, layer) in self.layers.iter().enumerate().rev() {
+			let stride = 1 << l;
+			for r in 0..stride {
+				self.uncast(layer, data, r, stride);
+			}
+		}
 	}
-	// if domain.len() <= 4{
-	//     return field.lagrange_interp(domain, vals)
-	// Split the domain into two cosets A && B, where for x in A, x+offset is in B
-	let offset = domain[1];
-	// Compute the evaluations of the evens && odds polynomials using the invariants{
-	// poly(x+k) - poly(x) = k * odds(x**2+kx)
-	// poly(x)*(x+k) - poly(x+k)*x = k * evens(x**2+kx)
-	let mut even_points = vec![Element::zero(); vals.len() >> 1_usize];
-	let mut odd_points = vec![Element::zero(); vals.len() >> 1_usize];
-	let dl = domain.len() >> 1_usize;
-	for i in 0..dl {
-		let (p_of_x, p_of_x_plus_k) = (vals[i * 2], vals[i * 2 + 1]);
-		let x = domain[i * 2];
-		even_points[i] = field.div(field.mul(p_of_x, x ^ offset) ^ field.mul(p_of_x_plus_k, x), offset);
-		odd_points[i] = field.div(p_of_x ^ p_of_x_plus_k, offset);
-	}
-	let cast_domain = domain.into_iter().step_by(2).map(|&x| field.mul(x, offset ^ x)).collect::<Vec<Element>>();
-	// Two half-size problems over the smaller domains, recovering
-	// the polynomials evens && odds
-	let evens = invfft(field, &cast_domain[..], &even_points[..]);
-	let odds = invfft(field, &cast_domain[..], &odd_points[..]);
-	// Given evens && odds where poly(x) = evens(x**2+offset*x) + x * odds(x**2+offset*x),
-	// recover poly
-	let mut composed_evens = compose(field, &evens[..], offset);
-	composed_evens.push(Element::zero());
-	let mut composed_odds = vec![Element::zero()];
-	composed_odds.extend(compose(field, &odds[..], offset).iter());
-	(0..vals.len()).into_iter().map(|i| composed_evens[i] ^ composed_odds[i]).collect::<Vec<_>>()
-}
-
-// shift_polys[i][j] is the 2**j degree coefficient of the polynomial that
-// evaluates to [1,1...1, 0,0....0] with 2**(i-1) ones && 2**(i-1) zeroes
-static SHIFT_POLYS: &[&[usize]] = &[
-	&[],
-	&[1],
-	&[32755, 32755],
-	&[52774, 60631, 8945],
-	&[38902, 5560, 44524, 12194],
-	&[55266, 46488, 60321, 5401, 40130],
-	&[21827, 32224, 51565, 15072, 8277, 64379],
-	&[59460, 15452, 60370, 24737, 20321, 35516, 39606],
-	&[42623, 56997, 25925, 15351, 16625, 47045, 38250, 17462],
-	&[7575, 27410, 32434, 22187, 28933, 15447, 37964, 38186, 4776],
-	&[39976, 61188, 42456, 2155, 6178, 34033, 52305, 14913, 2896, 48908],
-	&[6990, 12021, 36054, 16198, 17011, 14018, 58553, 13272, 25318, 5288, 21429],
-	&[16440, 34925, 14360, 22561, 43883, 36645, 7613, 26531, 8597, 59502, 61283, 53412],
-];
-
-fn invfft2(field: &BinaryField, vals: &[Element]) -> Vec<Element> {
+}
+
+/// Equivalent to [field.eval_poly_at(poly, x) for x in domain]
+///
+/// `domain` must be a subspace of the field in the order `0..2^m`, `poly` at most as long.
+/// Prefer [`AdditiveFft`] when transforming repeatedly over the same domain.
+pub fn fft(field: &BinaryField, domain: &[Element], poly: &[Element]) -> Vec<Element> {
+	assert!(poly.len() <= domain.len());
+	let mut data = poly.to_vec();
+	data.resize(domain.len(), Element::zero());
+	AdditiveFft::new(field, domain).forward(&mut data[..]);
+	data
+}
+
+/// The inverse function of fft
+pub fn invfft(field: &BinaryField, domain: &[Element], vals: &[Element]) -> Vec<Element> {
+	let mut data = vals.to_vec();
+	AdditiveFft::new(field, domain).inverse(&mut data[..]);
+	data
+}
+
+/// `shift_polys[i][j]` is the `2^j` degree coefficient of the polynomial that
+/// evaluates to [1,1...1, 0,0....0] with 2**(i-1) ones && 2**(i-1) zeroes, for `i <= log_n`.
+///
+/// Over `0..2^i` that is `1 + W(x) / W(2^(i-1))`, with `W` vanishing on the first half.
+/// `W` is linearized, so apart from the constant term only the `2^j` degree coefficients are non-zero.
+pub fn shift_polys(field: &BinaryField, log_n: usize) -> Vec<Vec<Element>> {
+	assert!(log_n <= field.height as usize, "Domain of 2^{} points exceeds the field", log_n);
+	let mut rows = vec![vec![]];
+	for i in 1..=log_n {
+		let half = 1_usize << (i - 1);
+		let domain = (0..(half << 1) as u32).map(Element::from).collect::<Vec<_>>();
+		let mut vals = vec![Element::one(); half];
+		vals.resize(half << 1, Element::zero());
+
+		let poly = invfft(field, &domain[..], &vals[..]);
+		debug_assert_eq!(poly[0], Element::one());
+		rows.push((0..i).map(|j| poly[1 << j]).collect());
+	}
+	rows
+}
+
+fn invfft2_with(field: &BinaryField, vals: &[Element], shift_polys: &[Vec<Element>]) -> Vec<Element> {
 	if vals.len() == 1 {
 		return vals.to_vec();
 	}
 	let len_half = vals.len() >> 1_usize;
-	let left = invfft2(field, &vals[..len_half]);
+	let left = invfft2_with(field, &vals[..len_half], shift_polys);
 
-	let tmp = invfft2(field, &vals[len_half..]);
-	let right = shift(field, &tmp[..], len_half.into());
+	let tmp = invfft2_with(field, &vals[len_half..], shift_polys);
+	let right = shift(field, &tmp[..], Element::from(len_half as u32));
 
 	let mut o = vec![Element::zero(); vals.len()];
-	for (j, (left, right)) in left.into_iter().zip(right.into_iter()).enumerate() {
+	for (j, (left, right)) in left.into_iter().zip(right).enumerate() {
 		o[j] ^= left;
-		for (i, &coeff) in SHIFT_POLYS[log2(vals.len() as u16) as usize].into_iter().enumerate() {
-			o[(1 << i) + j] ^= field.mul(left ^ right, coeff.into());
+		for (i, &coeff) in shift_polys[log2(vals.len() as u32) as usize].iter().enumerate() {
+			o[(1 << i) + j] ^= field.mul(left ^ right, coeff);
 		}
 	}
 	o
 }
 
-// Multiplies two polynomials using the FFT method
-fn mul(field: &BinaryField, domain: &[Element], p1: &[Element], p2: &[Element]) -> Vec<Element> {
+/// Alternative inverse of [`fft`] over the domain `0..vals.len()`, based on [`shift_polys`].
+///
+/// The length of `vals` must be a power of two and at most the field size.
+pub fn invfft2(field: &BinaryField, vals: &[Element]) -> Vec<Element> {
+	assert!(is_power_of_2(vals.len() as u32));
+	let shift_polys = shift_polys(field, log2(vals.len() as u32) as usize);
+	invfft2_with(field, vals, &shift_polys[..])
+}
+
+/// Multiplies two polynomials using the FFT method
+///
+/// `domain` must be larger than the degree of the product.
+pub fn mul(field: &BinaryField, domain: &[Element], p1: &[Element], p2: &[Element]) -> Vec<Element> {
 	assert!(p1.len() <= domain.len() && p2.len() <= domain.len());
 	let values1 = fft(field, domain, p1);
 	let values2 = fft(field, domain, p2);
 	let values3 =
-		values1.into_iter().zip(values2.into_iter()).map(|(v1, v2)| field.mul(v1, v2)).collect::<Vec<Element>>();
+		values1.into_iter().zip(values2).map(|(v1, v2)| field.mul(v1, v2)).collect::<Vec<Element>>();
 	invfft(field, domain, &values3[..])
 }
 
-// Generates the polynomial `p(x) = (x - xs[0]) * (x - xs[1]) * ...`
-fn zpoly(field: &BinaryField, xs: Vec<Element>) -> Vec<Element> {
+/// Generates the polynomial `p(x) = (x - xs[0]) * (x - xs[1]) * ...`
+pub fn zpoly(field: &BinaryField, xs: Vec<Element>) -> Vec<Element> {
 	if xs.is_empty() {
 		return vec![Element::one()];
 	}
@@ -761,22 +1191,23 @@ fn zpoly(field: &BinaryField, xs: Vec<Element>) -> Vec<Element> {
 		return vec![xs[0], Element::one()];
 	}
 	let domain =
-		(0_u16..(2_u16 << log2(xs.iter().max().unwrap().0) + 1)).into_iter().map(Element::from).collect::<Vec<_>>();
-	let offset = domain[1];
+		(0_u32..(2_u32 << (log2(xs.iter().max().unwrap().0) + 1))).map(Element::from).collect::<Vec<_>>();
 	let z_left = zpoly(field, xs.iter().step_by(2).copied().collect());
 	let z_right = zpoly(field, xs.iter().skip(1).step_by(2).copied().collect());
 	mul(field, &domain[..], &z_left, &z_right)
 }
 
-// Returns q(x) = p(x + k)
-fn shift(field: &BinaryField, poly: &[Element], k: Element) -> Vec<Element> {
+/// Returns q(x) = p(x + k)
+///
+/// The length of `poly` must be a power of two.
+pub fn shift(field: &BinaryField, poly: &[Element], k: Element) -> Vec<Element> {
 	if poly.len() == 1 {
 		return poly.to_vec();
 	}
 	// Largest mod_power=2**k such that mod_power >= poly.len()/2
-	assert!(is_power_of_2(poly.len() as u16));
+	assert!(is_power_of_2(poly.len() as u32));
 	let mod_power = poly.len() >> 1_usize;
-	let k_to_mod_power = field.exp(k, Element::from(mod_power));
+	let k_to_mod_power = field.exp(k, mod_power as u64);
 	// Calculate low = poly % (x+k)**mod_power
 	// && high = poly // (x+k)**mod_power
 	// Note that (x+k)**n = x**n + k**n for power-of-two powers in binary fields
@@ -788,36 +1219,66 @@ fn shift(field: &BinaryField, poly: &[Element], k: Element) -> Vec<Element> {
 	[shift(field, low, k), shift(field, high, k)].concat()
 }
 
-// Interpolates the polynomial where `p(xs[i]) = vals[i]`
-fn interpolate(field: &BinaryField, xs: &[Element], vals: &[Element]) -> Vec<Element> {
-	assert!(!xs.is_empty());
-	let domain_size = Element::one() << xs.iter().max().unwrap().log2() + Element::one();
-	assert!((domain_size << 1_usize) <= (Element::one() << field.height));
-	let domain = (0..domain_size.0).into_iter().map(Element::from).collect::<Vec<_>>();
-	let big_domain = (0..(domain_size.0 << 1_usize)).into_iter().map(Element::from).collect::<Vec<_>>();
-	let z = zpoly(field, domain.iter().filter(|&x| !xs.contains(x)).copied().collect());
-	// print("z = ", z)
-	let z_values = fft(field, &big_domain[..], &z);
-	// print("z_values = ", z_values)
-	let mut p_times_z_values = vec![Element::zero(); domain.len()];
-	for (&v, &d) in vals.iter().zip(xs.into_iter()) {
-		let i = d.0 as usize;
-		p_times_z_values[i] = field.mul(v, z_values[i]);
-	}
-	// print("p_times_z_values = ", p_times_z_values)
-	let p_times_z = invfft(field, &domain[..], &p_times_z_values);
-	// print("p_times_z = ", p_times_z)
-	let shifted_p_times_z_values = fft(field, &big_domain[..], &p_times_z[..]);
-	let shifted_p_times_z_values = &shifted_p_times_z_values[domain_size.0 as usize..];
-	// print("shifted_p_times_z_values =", shifted_p_times_z_values)
-	let shifted_p_values = shifted_p_times_z_values
-		.into_iter()
-		.zip(z_values[domain_size.0 as usize..].into_iter())
-		.map(|(&x, &y)| field.div(x, y))
-		.collect::<Vec<Element>>();
-	// print("shifted_p_values =", shifted_p_values)
-	let shifted_p = invfft(field, &domain, &shifted_p_values);
-	shift(field, &shifted_p, domain_size)
+/// Interpolation through fixed points `xs`, for many sets of values.
+///
+/// The vanishing polynomial of the missing points and its evaluations only depend on `xs`,
+/// so each [`Interpolator::interpolate`] is down to the transforms of the values.
+pub struct Interpolator<'a> {
+	field: &'a BinaryField,
+	xs: Vec<usize>,
+	// evaluations of the vanishing polynomial on the domain, and inverted on the shifted domain
+	z_values: Vec<Element>,
+	inv_shifted_z_values: Vec<Element>,
+	domain: AdditiveFft<'a>,
+	big_domain: AdditiveFft<'a>,
+}
+
+impl<'a> Interpolator<'a> {
+	/// All `xs` must be distinct and lie in a subspace `0..2^m` with `2^(m + 1)` at most the field size.
+	pub fn new(field: &'a BinaryField, xs: &[Element]) -> Self {
+		assert!(!xs.is_empty());
+		let domain_size = 1_usize << (log2(xs.iter().max().unwrap().0) + 1);
+		assert!((domain_size as u64) << 1 <= 1_u64 << field.height);
+		let domain = (0..domain_size as u32).map(Element::from).collect::<Vec<_>>();
+		let big_domain = (0..(domain_size << 1) as u32).map(Element::from).collect::<Vec<_>>();
+		let z = zpoly(field, domain.iter().filter(|&x| !xs.contains(x)).copied().collect());
+		let mut z_values = fft(field, &big_domain[..], &z);
+		let inv_shifted_z_values = z_values.split_off(domain_size).into_iter().map(|z| field.inv(z)).collect();
+		Self {
+			field,
+			xs: xs.iter().map(|x| x.0 as usize).collect(),
+			z_values,
+			inv_shifted_z_values,
+			domain: AdditiveFft::new(field, &domain[..]),
+			big_domain: AdditiveFft::new(field, &big_domain[..]),
+		}
+	}
+
+	/// The polynomial where `p(xs[i]) = vals[i]`.
+	pub fn interpolate(&self, vals: &[Element]) -> Vec<Element> {
+		let domain_size = self.z_values.len();
+		let mut p_times_z = vec![Element::zero(); domain_size << 1];
+		for (&v, &i) in vals.iter().zip(&self.xs) {
+			p_times_z[i] = self.field.mul(v, self.z_values[i]);
+		}
+		self.domain.inverse(&mut p_times_z[..domain_size]);
+		self.big_domain.forward(&mut p_times_z[..]);
+
+		let mut shifted_p = p_times_z.split_off(domain_size);
+		for (value, &inv_z) in shifted_p.iter_mut().zip(&self.inv_shifted_z_values) {
+			*value = self.field.mul(*value, inv_z);
+		}
+		self.domain.inverse(&mut shifted_p[..]);
+		shift(self.field, &shifted_p, Element::from(domain_size as u32))
+	}
+}
+
+/// Interpolates the polynomial where `p(xs[i]) = vals[i]`
+///
+/// All `xs` must be distinct and lie in a subspace `0..2^m` with `2^(m + 1)` at most the field size.
+/// See [`Interpolator`] to interpolate through the same points repeatedly.
+pub fn interpolate(field: &BinaryField, xs: &[Element], vals: &[Element]) -> Vec<Element> {
+	Interpolator::new(field, xs).interpolate(vals)
 }
 
 #[cfg(test)]
@@ -844,42 +1305,211 @@ mod tests {
 	}
 
 	#[test]
-	fn test_pow_mod() {
-		assert_eq!(Element::from(1).pow_mod(10.into(), 1024), Element::one());
-		assert_eq!(Element::from(2).pow_mod(10.into(), 1024), Element::zero());
-		assert_eq!(Element::from(3).pow_mod(3.into(), 1024), Element::from(27));
+	fn element_from_usize_is_checked() {
+		assert_eq!(Element::try_from(1023_usize), Ok(Element::from(1023_u32)));
+		assert_eq!(Element::try_from(usize::MAX).is_ok(), usize::BITS <= 32);
+	}
+
+	/// Integer `base^exp % modulo`, used to derive test coefficients.
+	fn pow_mod(base: u64, mut exp: u64, modulo: u64) -> u64 {
+		let mut base = base % modulo;
+		let mut acc = 1 % modulo;
+		while exp > 0 {
+			if exp & 1 == 1 {
+				acc = acc * base % modulo;
+			}
+			base = base * base % modulo;
+			exp >>= 1;
+		}
+		acc
+	}
+
+	/// Carry-less `x * 11 mod x^5`, spreading small points over the first 32 elements.
+	fn spread(x: Element) -> Element {
+		let r = RawPoly::from(x) * RawPoly::new(11) % RawPoly::new(32);
+		Element::from(r.value() as u32)
+	}
+
+	#[test]
+	fn raw_poly_ops() {
+		// (x + 1)^2 = x^2 + 1 over GF(2)
+		let x1 = RawPoly::X + RawPoly::new(1);
+		assert_eq!(x1 * x1, RawPoly::new(0b101));
+		assert_eq!(RawPoly::new(0b101) % x1, RawPoly::default());
+		assert_eq!(RawPoly::new(moduli::GF_2_16) % RawPoly::X, RawPoly::new(1));
+		assert_eq!((x1 * RawPoly::new(0b111)).gcd(x1 * x1), x1);
+		assert_eq!(RawPoly::new(moduli::GF_2_32).degree(), Some(32));
+		assert_eq!(RawPoly::default().degree(), None);
+		assert_eq!(BinaryField::standard(10).unwrap().modulus().degree(), Some(10));
 	}
 
 	#[test]
 	fn test_mul() {
-		let field = BinaryField::new(1033.into()).unwrap();
-		assert_eq!(field.mul(128.into(), 128.into()), Element::from(144));
-		assert_eq!(field.mul(37.into(), 11.into()), Element::from(327));
-		assert_eq!(field.mul(1.into(), 1.into()), Element::one());
-		assert_eq!(field.mul(1023.into(), 2.into()), Element::from(1015));
+		let field = BinaryField::new(1033).unwrap();
+		assert_eq!(field.mul(128_u32.into(), 128_u32.into()), Element::from(144_u32));
+		assert_eq!(field.mul(37_u32.into(), 11_u32.into()), Element::from(327_u32));
+		assert_eq!(field.mul(1_u32.into(), 1_u32.into()), Element::one());
+		assert_eq!(field.mul(1023_u32.into(), 2_u32.into()), Element::from(1015_u32));
+
+
+		assert_eq!(field.mul(256_u32.into(), 256_u32.into()), Element::from(576_u32));
+
+		assert_eq!(field.mul(256_u32.into(), Element::zero()), Element::zero());
+		assert_eq!(field.mul(Element::zero(), 256_u32.into()), Element::zero());
+		assert_eq!(field.mul(256_u32.into(), Element::one()), Element::from(256_u32));
+		assert_eq!(field.mul(Element::one(), 256_u32.into()), Element::from(256_u32));
+	}
+
+	#[test]
+	fn field_ops_are_consistent() {
+		let field = BinaryField::new(1033).unwrap();
+		assert_eq!(field.height(), 10);
+		assert_eq!(field.order(), 1023);
+
+		for x in (1..1024_u16).step_by(7).map(Element::from) {
+			let inv = field.inv(x);
+			assert_eq!(field.mul(x, inv), Element::one());
+			assert_eq!(field.div(Element::one(), x), inv);
+			assert_eq!(field.sqr(x), field.mul(x, x));
+			assert_eq!(field.exp(x, 3), field.mul(x, field.sqr(x)));
+			assert_eq!(field.div(field.mul(x, 77_u32.into()), x), Element::from(77_u32));
+		}
+		assert_eq!(field.exp(Element::zero(), 3), Element::zero());
+		assert_eq!(field.exp(Element::zero(), 0), Element::one());
+	}
+
+	#[test]
+	fn tables_match_table_free() {
+		// x^16 + x^5 + x^3 + x^2 + 1, as used by `novel_poly_basis`
+		let field = BinaryField::new(0x1002D).unwrap();
+		let table_free = BinaryField::build(0x1002D, false).unwrap();
+		assert!(field.has_tables() && !table_free.has_tables());
+		assert_eq!(field.height(), 16);
+		assert_eq!(field.order(), 0xFFFF);
+
+		let xs = (1..0xFFFF_u32).step_by(251).map(Element);
+		let ys = (3..0xFFFF_u32).step_by(97).map(Element);
+		for (x, y) in xs.zip(ys) {
+			assert_eq!(field.mul(x, y), table_free.mul(x, y));
+			assert_eq!(field.div(x, y), table_free.div(x, y));
+			assert_eq!(field.inv(x), table_free.inv(x));
+			assert_eq!(field.exp(x, y.value() as u64), table_free.exp(x, y.value() as u64));
+		}
+	}
+
+	#[test]
+	fn gf_2_32() {
+		// x^32 + x^22 + x^2 + x + 1
+		let field = BinaryField::new(0x1_0040_0007).unwrap();
+		assert_eq!(field.height(), 32);
+		assert_eq!(field.order(), u32::MAX as u64);
+
+		let values = [1_u32, 2, 0xFFFF_FFFF, 0x8000_0000, 0xDEAD_BEEF, 0x1234_5678].map(Element::from);
+		for &x in values.iter() {
+			assert_eq!(field.mul(x, field.inv(x)), Element::one());
+			assert_eq!(field.exp(x, field.order()), Element::one());
+			for &y in values.iter() {
+				assert_eq!(field.mul(x, y), field.mul(y, x));
+				assert_eq!(field.div(field.mul(x, y), y), x);
+				assert_eq!(field.mul(x, y ^ 3_u32), field.mul(x, y) ^ field.mul(x, 3_u32.into()));
+			}
+		}
+		// x^31 * x = x^32 = x^22 + x^2 + x + 1
+		assert_eq!(field.mul(Element(0x8000_0000), Element(2)), Element(0x0040_0007));
+
+		let xs = [7_u32, 1 << 20, 0xFFFF_0000, 42].map(Element::from).to_vec();
+		let a = [9_u32, 0xABCD_EF01, 0, 1].map(Element::from).to_vec();
+		let ys = xs.iter().map(|&x| field.eval_poly_at(&a, x)).collect::<Vec<_>>();
+		assert_eq!(field.lagrange_interp(xs, ys), a);
+	}
+
+	#[test]
+	fn bad_modulus() {
+		assert_eq!(BinaryField::new(0).err(), Some(Error::UnsupportedDegree(0)));
+		assert_eq!(BinaryField::new(1).err(), Some(Error::UnsupportedDegree(1)));
+		assert_eq!(BinaryField::new(1 << 33).err(), Some(Error::UnsupportedDegree(1 << 33)));
+		// x^10 + 1 = (x^5 + 1)^2
+		assert_eq!(BinaryField::new(1025).err(), Some(Error::Reducible(1025)));
+		// x^32 + x^2 + 1 = (x^16 + x + 1)^2
+		assert_eq!(BinaryField::new(0x1_0000_0005).err(), Some(Error::Reducible(0x1_0000_0005)));
+		assert_eq!(BinaryField::standard(0).err(), Some(Error::UnsupportedHeight(0)));
+		assert_eq!(BinaryField::standard(33).err(), Some(Error::UnsupportedHeight(33)));
+		assert_eq!(BinaryField::standard(64).err(), Some(Error::UnsupportedHeight(64)));
+	}
+
+	#[test]
+	fn irreducible_but_not_primitive() {
+		// x^4 + x^3 + x^2 + x + 1 divides x^5 - 1, so x only has order 5
+		let pd = 0x1F;
+		assert_eq!(is_irreducible(pd), Ok(true));
+		assert_eq!(BinaryField::new_primitive(pd).err(), Some(Error::NotPrimitive(pd)));
+
+		let field = BinaryField::new(pd).unwrap();
+		assert_eq!(field.generator(), Element::from(3_u32));
+		for x in (1..16_u32).map(Element::from) {
+			assert_eq!(field.mul(x, field.inv(x)), Element::one());
+		}
+	}
+
+	#[test]
+	fn standard_moduli_are_primitive() {
+		assert_eq!(moduli::standard(0), None);
+		for height in 1..=32 {
+			let pd = moduli::standard(height).unwrap();
+			let field = BinaryField::new_primitive(pd).unwrap();
+			assert_eq!(field.height(), height);
+			assert_eq!(field.generator(), Element::from(if height == 1 { 1_u32 } else { 2 }));
+		}
+		assert_eq!(BinaryField::standard(10).unwrap().modulus(), RawPoly::new(1033));
+	}
+
+	#[test]
+	fn multi_inv_skips_zeros() {
+		let field = BinaryField::new(1033).unwrap();
+		let values = [3, 0, 1000, 1, 0, 512].iter().map(|&x: &u16| Element::from(x)).collect::<Vec<_>>();
+		let inverses = field.multi_inv(&values);
+		for (&value, &inv) in values.iter().zip(inverses.iter()) {
+			if value == Element::zero() {
+				assert_eq!(inv, Element::zero());
+			} else {
+				assert_eq!(inv, field.inv(value));
+			}
+		}
+	}
 
+	#[test]
+	fn poly_div_and_lagrange() {
+		let field = BinaryField::new(1033).unwrap();
+		let a = [5, 0, 17, 900, 3].iter().map(|&x: &u16| Element::from(x)).collect::<Vec<_>>();
+		let b = [7, 1, 33].iter().map(|&x: &u16| Element::from(x)).collect::<Vec<_>>();
+		let product = field.mul_polys(a.clone(), b.clone());
+		assert_eq!(field.div_polys(product.clone(), b.clone()), a);
+		assert_eq!(field.add_polys(b.clone(), a.clone()), field.add_polys(a.clone(), b));
 
-		assert_eq!(field.mul(256.into(), 256.into()), Element::from(576));
+		let xs = [1, 2, 3, 100, 1000].iter().map(|&x: &u16| Element::from(x)).collect::<Vec<_>>();
+		let z = field.zpoly(&xs);
+		assert!(xs.iter().all(|&x| field.eval_poly_at(&z, x) == Element::zero()));
 
-		assert_eq!(field.mul(256.into(), Element::zero()), Element::zero());
-		assert_eq!(field.mul(Element::zero(), 256.into()), Element::zero());
-		assert_eq!(field.mul(256.into(), Element::one()), Element::from(256));
-		assert_eq!(field.mul(Element::one(), 256.into()), Element::from(256));
+		let ys = xs.iter().map(|&x| field.eval_poly_at(&a, x)).collect::<Vec<_>>();
+		assert_eq!(field.lagrange_interp(xs, ys), a);
 	}
 
 	#[test]
 	fn fft_simple_works() {
 		let pd = 512;
-		let field = BinaryField::new(1033.into()).unwrap();
-		let domain = (0_usize..pd).into_iter().map(Element::from).collect::<Vec<_>>();
-		let poly = domain.iter().map(|x| x.pow_mod(9.into(), pd as u64)).collect::<Vec<Element>>();
+		let field = BinaryField::new(1033).unwrap();
+		let domain = (0..pd as u32).map(Element::from).collect::<Vec<_>>();
+		let poly = domain
+			.iter()
+			.map(|x| Element::from(pow_mod(x.value() as u64, 9, pd as u64) as u32))
+			.collect::<Vec<Element>>();
 		let z = _simple_ft(&field, &domain[..], &poly[..]);
 
-		let expected_poly: Vec<_> = vec![0, 1, 0, 227, 0, 357, 0, 327, 0, 329, 0, 299, 0, 429, 0, 143, 0, 145, 0, 371, 0, 501, 0, 471, 0, 473, 0, 443, 0, 61, 0, 287, 0, 289, 0, 3, 0, 133, 0, 103, 0, 105, 0, 75, 0, 205, 0, 431, 0, 433, 0, 147, 0, 277, 0, 247, 0, 249, 0, 219, 0, 349, 0, 63, 0, 65, 0, 291, 0, 421, 0, 391, 0, 393, 0, 363, 0, 493, 0, 207, 0, 209, 0, 435, 0, 53, 0, 23, 0, 25, 0, 507, 0, 125, 0, 351, 0, 353, 0, 67, 0, 197, 0, 167, 0, 169, 0, 139, 0, 269, 0, 495, 0, 497, 0, 211, 0, 341, 0, 311, 0, 313, 0, 283, 0, 413, 0, 127, 0, 129, 0, 355, 0, 485, 0, 455, 0, 457, 0, 427, 0, 45, 0, 271, 0, 273, 0, 499, 0, 117, 0, 87, 0, 89, 0, 59, 0, 189, 0, 415, 0, 417, 0, 131, 0, 261, 0, 231, 0, 233, 0, 203, 0, 333, 0, 47, 0, 49, 0, 275, 0, 405, 0, 375, 0, 377, 0, 347, 0, 477, 0, 191, 0, 193, 0, 419, 0, 37, 0, 7, 0, 9, 0, 491, 0, 109, 0, 335, 0, 337, 0, 51, 0, 181, 0, 151, 0, 153, 0, 123, 0, 253, 0, 479, 0, 481, 0, 195, 0, 325, 0, 295, 0, 297, 0, 267, 0, 397, 0, 111, 0, 113, 0, 339, 0, 469, 0, 439, 0, 441, 0, 411, 0, 29, 0, 255, 0, 257, 0, 483, 0, 101, 0, 71, 0, 73, 0, 43, 0, 173, 0, 399, 0, 401, 0, 115, 0, 245, 0, 215, 0, 217, 0, 187, 0, 317, 0, 31, 0, 33, 0, 259, 0, 389, 0, 359, 0, 361, 0, 331, 0, 461, 0, 175, 0, 177, 0, 403, 0, 21, 0, 503, 0, 505, 0, 475, 0, 93, 0, 319, 0, 321, 0, 35, 0, 165, 0, 135, 0, 137, 0, 107, 0, 237, 0, 463, 0, 465, 0, 179, 0, 309, 0, 279, 0, 281, 0, 251, 0, 381, 0, 95, 0, 97, 0, 323, 0, 453, 0, 423, 0, 425, 0, 395, 0, 13, 0, 239, 0, 241, 0, 467, 0, 85, 0, 55, 0, 57, 0, 27, 0, 157, 0, 383, 0, 385, 0, 99, 0, 229, 0, 199, 0, 201, 0, 171, 0, 301, 0, 15, 0, 17, 0, 243, 0, 373, 0, 343, 0, 345, 0, 315, 0, 445, 0, 159, 0, 161, 0, 387, 0, 5, 0, 487, 0, 489, 0, 459, 0, 77, 0, 303, 0, 305, 0, 19, 0, 149, 0, 119, 0, 121, 0, 91, 0, 221, 0, 447, 0, 449, 0, 163, 0, 293, 0, 263, 0, 265, 0, 235, 0, 365, 0, 79, 0, 81, 0, 307, 0, 437, 0, 407, 0, 409, 0, 379, 0, 509, 0, 223, 0, 225, 0, 451, 0, 69, 0, 39, 0, 41, 0, 11, 0, 141, 0, 367, 0, 369, 0, 83, 0, 213, 0, 183, 0, 185, 0, 155, 0, 285, 0, 511].into_iter().map(Element::from).collect();
+		let expected_poly: Vec<_> = vec![0_u32, 1, 0, 227, 0, 357, 0, 327, 0, 329, 0, 299, 0, 429, 0, 143, 0, 145, 0, 371, 0, 501, 0, 471, 0, 473, 0, 443, 0, 61, 0, 287, 0, 289, 0, 3, 0, 133, 0, 103, 0, 105, 0, 75, 0, 205, 0, 431, 0, 433, 0, 147, 0, 277, 0, 247, 0, 249, 0, 219, 0, 349, 0, 63, 0, 65, 0, 291, 0, 421, 0, 391, 0, 393, 0, 363, 0, 493, 0, 207, 0, 209, 0, 435, 0, 53, 0, 23, 0, 25, 0, 507, 0, 125, 0, 351, 0, 353, 0, 67, 0, 197, 0, 167, 0, 169, 0, 139, 0, 269, 0, 495, 0, 497, 0, 211, 0, 341, 0, 311, 0, 313, 0, 283, 0, 413, 0, 127, 0, 129, 0, 355, 0, 485, 0, 455, 0, 457, 0, 427, 0, 45, 0, 271, 0, 273, 0, 499, 0, 117, 0, 87, 0, 89, 0, 59, 0, 189, 0, 415, 0, 417, 0, 131, 0, 261, 0, 231, 0, 233, 0, 203, 0, 333, 0, 47, 0, 49, 0, 275, 0, 405, 0, 375, 0, 377, 0, 347, 0, 477, 0, 191, 0, 193, 0, 419, 0, 37, 0, 7, 0, 9, 0, 491, 0, 109, 0, 335, 0, 337, 0, 51, 0, 181, 0, 151, 0, 153, 0, 123, 0, 253, 0, 479, 0, 481, 0, 195, 0, 325, 0, 295, 0, 297, 0, 267, 0, 397, 0, 111, 0, 113, 0, 339, 0, 469, 0, 439, 0, 441, 0, 411, 0, 29, 0, 255, 0, 257, 0, 483, 0, 101, 0, 71, 0, 73, 0, 43, 0, 173, 0, 399, 0, 401, 0, 115, 0, 245, 0, 215, 0, 217, 0, 187, 0, 317, 0, 31, 0, 33, 0, 259, 0, 389, 0, 359, 0, 361, 0, 331, 0, 461, 0, 175, 0, 177, 0, 403, 0, 21, 0, 503, 0, 505, 0, 475, 0, 93, 0, 319, 0, 321, 0, 35, 0, 165, 0, 135, 0, 137, 0, 107, 0, 237, 0, 463, 0, 465, 0, 179, 0, 309, 0, 279, 0, 281, 0, 251, 0, 381, 0, 95, 0, 97, 0, 323, 0, 453, 0, 423, 0, 425, 0, 395, 0, 13, 0, 239, 0, 241, 0, 467, 0, 85, 0, 55, 0, 57, 0, 27, 0, 157, 0, 383, 0, 385, 0, 99, 0, 229, 0, 199, 0, 201, 0, 171, 0, 301, 0, 15, 0, 17, 0, 243, 0, 373, 0, 343, 0, 345, 0, 315, 0, 445, 0, 159, 0, 161, 0, 387, 0, 5, 0, 487, 0, 489, 0, 459, 0, 77, 0, 303, 0, 305, 0, 19, 0, 149, 0, 119, 0, 121, 0, 91, 0, 221, 0, 447, 0, 449, 0, 163, 0, 293, 0, 263, 0, 265, 0, 235, 0, 365, 0, 79, 0, 81, 0, 307, 0, 437, 0, 407, 0, 409, 0, 379, 0, 509, 0, 223, 0, 225, 0, 451, 0, 69, 0, 39, 0, 41, 0, 11, 0, 141, 0, 367, 0, 369, 0, 83, 0, 213, 0, 183, 0, 185, 0, 155, 0, 285, 0, 511].into_iter().map(Element::from).collect();
 		assert_eq!(expected_poly.len(), pd);
 		assert_eq!(poly, &expected_poly[0..]);
 
-		let expected_z: Vec<_> = vec![0, 0, 429, 108, 891, 245, 857, 420, 735, 4, 232, 954, 189, 1018, 657, 708, 453, 938, 145, 643, 605, 202, 95, 306, 141, 954, 830, 123, 1012, 429, 339, 702, 579, 352, 169, 363, 929, 167, 961, 270, 154, 854, 787, 275, 346, 534, 165, 462, 780, 360, 538, 656, 65, 396, 588, 642, 292, 725, 84, 704, 833, 278, 634, 563, 563, 813, 44, 960, 768, 905, 936, 97, 102, 59, 635, 246, 209, 713, 1011, 431, 344, 966, 602, 812, 363, 609, 611, 155, 200, 714, 794, 138, 452, 593, 551, 83, 1003, 762, 159, 630, 292, 1017, 789, 791, 487, 1007, 720, 657, 19, 41, 510, 422, 358, 516, 286, 1019, 721, 59, 560, 0, 206, 149, 894, 955, 407, 116, 319, 390, 1020, 402, 767, 91, 711, 555, 477, 891, 509, 346, 993, 787, 871, 843, 833, 427, 183, 484, 434, 531, 942, 103, 513, 997, 396, 243, 749, 835, 729, 575, 206, 284, 597, 73, 159, 684, 910, 561, 650, 191, 182, 924, 354, 56, 854, 11, 584, 819, 726, 972, 875, 661, 981, 1013, 9, 24, 867, 1014, 537, 391, 997, 599, 71, 818, 64, 533, 587, 759, 650, 747, 280, 189, 699, 137, 338, 539, 857, 938, 128, 849, 820, 81, 595, 382, 812, 431, 931, 458, 890, 996, 153, 559, 738, 794, 930, 206, 961, 669, 402, 721, 730, 631, 357, 337, 529, 965, 121, 769, 931, 936, 842, 73, 583, 482, 821, 865, 227, 894, 40, 498, 701, 21, 50, 282, 374, 102, 600, 743, 711, 971, 1021, 238, 241, 264, 160, 911, 346, 422, 32, 340, 892, 929, 562, 535, 804, 626, 825, 855, 287, 833, 757, 306, 646, 172, 2, 246, 317, 1001, 472, 553, 972, 383, 15, 884, 164, 251, 641, 27, 764, 118, 19, 927, 579, 34, 701, 984, 567, 980, 933, 580, 622, 415, 845, 972, 93, 750, 136, 620, 4, 930, 797, 373, 353, 458, 33, 144, 916, 538, 444, 26, 302, 83, 328, 622, 104, 1022, 834, 149, 663, 605, 511, 814, 978, 190, 480, 228, 102, 231, 407, 635, 589, 724, 352, 886, 121, 261, 1018, 509, 387, 514, 532, 255, 926, 111, 129, 408, 957, 18, 390, 209, 324, 339, 1002, 449, 873, 879, 33, 633, 539, 816, 450, 870, 114, 520, 665, 910, 147, 127, 577, 594, 97, 279, 926, 894, 584, 830, 26, 591, 593, 226, 57, 365, 922, 319, 744, 2, 58, 538, 64, 723, 709, 762, 727, 3, 611, 680, 948, 70, 920, 542, 854, 593, 831, 853, 473, 932, 342, 184, 330, 804, 433, 84, 549, 622, 172, 646, 776, 648, 63, 816, 219, 260, 417, 774, 517, 928, 664, 152, 101, 910, 118, 384, 628, 726, 589, 118, 556, 5, 665, 290, 366, 41, 60, 581, 780, 69, 216, 426, 64, 561, 658, 656, 417, 375, 882, 825, 561, 103, 892, 218, 91, 144, 91, 160, 132, 895, 78, 464, 146, 153, 117, 456, 810, 80, 822, 388, 834, 1006, 95, 422, 50, 862, 121, 50, 592, 424, 601, 283, 819, 835, 262, 791, 518, 507].into_iter().map(Element::from).collect();
+		let expected_z: Vec<_> = vec![0_u32, 0, 429, 108, 891, 245, 857, 420, 735, 4, 232, 954, 189, 1018, 657, 708, 453, 938, 145, 643, 605, 202, 95, 306, 141, 954, 830, 123, 1012, 429, 339, 702, 579, 352, 169, 363, 929, 167, 961, 270, 154, 854, 787, 275, 346, 534, 165, 462, 780, 360, 538, 656, 65, 396, 588, 642, 292, 725, 84, 704, 833, 278, 634, 563, 563, 813, 44, 960, 768, 905, 936, 97, 102, 59, 635, 246, 209, 713, 1011, 431, 344, 966, 602, 812, 363, 609, 611, 155, 200, 714, 794, 138, 452, 593, 551, 83, 1003, 762, 159, 630, 292, 1017, 789, 791, 487, 1007, 720, 657, 19, 41, 510, 422, 358, 516, 286, 1019, 721, 59, 560, 0, 206, 149, 894, 955, 407, 116, 319, 390, 1020, 402, 767, 91, 711, 555, 477, 891, 509, 346, 993, 787, 871, 843, 833, 427, 183, 484, 434, 531, 942, 103, 513, 997, 396, 243, 749, 835, 729, 575, 206, 284, 597, 73, 159, 684, 910, 561, 650, 191, 182, 924, 354, 56, 854, 11, 584, 819, 726, 972, 875, 661, 981, 1013, 9, 24, 867, 1014, 537, 391, 997, 599, 71, 818, 64, 533, 587, 759, 650, 747, 280, 189, 699, 137, 338, 539, 857, 938, 128, 849, 820, 81, 595, 382, 812, 431, 931, 458, 890, 996, 153, 559, 738, 794, 930, 206, 961, 669, 402, 721, 730, 631, 357, 337, 529, 965, 121, 769, 931, 936, 842, 73, 583, 482, 821, 865, 227, 894, 40, 498, 701, 21, 50, 282, 374, 102, 600, 743, 711, 971, 1021, 238, 241, 264, 160, 911, 346, 422, 32, 340, 892, 929, 562, 535, 804, 626, 825, 855, 287, 833, 757, 306, 646, 172, 2, 246, 317, 1001, 472, 553, 972, 383, 15, 884, 164, 251, 641, 27, 764, 118, 19, 927, 579, 34, 701, 984, 567, 980, 933, 580, 622, 415, 845, 972, 93, 750, 136, 620, 4, 930, 797, 373, 353, 458, 33, 144, 916, 538, 444, 26, 302, 83, 328, 622, 104, 1022, 834, 149, 663, 605, 511, 814, 978, 190, 480, 228, 102, 231, 407, 635, 589, 724, 352, 886, 121, 261, 1018, 509, 387, 514, 532, 255, 926, 111, 129, 408, 957, 18, 390, 209, 324, 339, 1002, 449, 873, 879, 33, 633, 539, 816, 450, 870, 114, 520, 665, 910, 147, 127, 577, 594, 97, 279, 926, 894, 584, 830, 26, 591, 593, 226, 57, 365, 922, 319, 744, 2, 58, 538, 64, 723, 709, 762, 727, 3, 611, 680, 948, 70, 920, 542, 854, 593, 831, 853, 473, 932, 342, 184, 330, 804, 433, 84, 549, 622, 172, 646, 776, 648, 63, 816, 219, 260, 417, 774, 517, 928, 664, 152, 101, 910, 118, 384, 628, 726, 589, 118, 556, 5, 665, 290, 366, 41, 60, 581, 780, 69, 216, 426, 64, 561, 658, 656, 417, 375, 882, 825, 561, 103, 892, 218, 91, 144, 91, 160, 132, 895, 78, 464, 146, 153, 117, 456, 810, 80, 822, 388, 834, 1006, 95, 422, 50, 862, 121, 50, 592, 424, 601, 283, 819, 835, 262, 791, 518, 507].into_iter().map(Element::from).collect();
 		assert_eq!(expected_z.len(), z.len());
 		assert_slice_eq!(&z[..], &expected_z[0..]);
 	}
@@ -887,9 +1517,12 @@ mod tests {
 	#[test]
 	fn fft_faster_works() {
 		let pd = 1024;
-		let field = BinaryField::new(1033.into()).unwrap();
-		let domain = (0_usize..pd).into_iter().map(Element::from).collect::<Vec<_>>();
-		let poly = domain.iter().map(|x| x.pow_mod(9.into(), pd as u64)).collect::<Vec<Element>>();
+		let field = BinaryField::new(1033).unwrap();
+		let domain = (0..pd as u32).map(Element::from).collect::<Vec<_>>();
+		let poly = domain
+			.iter()
+			.map(|x| Element::from(pow_mod(x.value() as u64, 9, pd as u64) as u32))
+			.collect::<Vec<Element>>();
 		let z = fft(&field, &domain[..], &poly[..]);
 
 		let recovered_poly = invfft(&field, &domain[..], &z[..]);
@@ -899,16 +1532,14 @@ mod tests {
 	#[test]
 	fn fft_encode_and_recover() {
 		let pd = 1024;
-		let field = BinaryField::new(1033.into()).unwrap();
-		let domain = (0_usize..pd).into_iter().map(Element::from).collect::<Vec<_>>();
+		let field = BinaryField::new(1033).unwrap();
 
-		let poly3 = (0..25)
-			.into_iter()
+		let poly3 = (0_u32..25)
 			.map(Element::from)
-			.map(|x: Element| x.pow_mod(9.into(), pd as u64))
+			.map(|x: Element| Element::from(pow_mod(x.value() as u64, 9, pd as u64) as u32))
 			.collect::<Vec<Element>>();
 
-		let xs = (0..25).into_iter().map(Element::from).map(|x| (x * 11) % 32).collect::<Vec<Element>>();
+		let xs = (0_u32..25).map(Element::from).map(spread).collect::<Vec<Element>>();
 
 		let ys = xs.iter().map(|&x| field.eval_poly_at(&poly3, x)).collect::<Vec<Element>>();
 		let poly4 = interpolate(&field, &xs[..], &ys[..]);
@@ -919,40 +1550,105 @@ mod tests {
 	#[test]
 	fn fft_encode_and_recover_subset() {
 		let pd = 1024;
-		let field = BinaryField::new(1033.into()).unwrap();
-		let domain = (0_usize..pd).into_iter().map(Element::from).collect::<Vec<_>>();
+		let field = BinaryField::new(1033).unwrap();
 
-		let poly3 = (0..25)
-			.into_iter()
+		let poly3 = (0_u32..25)
 			.map(Element::from)
-			.map(|x: Element| x.pow_mod(9.into(), pd as u64))
+			.map(|x: Element| Element::from(pow_mod(x.value() as u64, 9, pd as u64) as u32))
 			.collect::<Vec<Element>>();
 
 		// skip the first 1
-		let xs = (1..25).into_iter().map(|x| (x * 11) % 32).map(Element::from).collect::<Vec<Element>>();
+		let xs = (1_u32..25).map(|x| (x * 11) % 32).map(Element::from).collect::<Vec<Element>>();
 		let ys = xs.iter().map(|&x| field.eval_poly_at(&poly3[..], x)).collect::<Vec<_>>();
 		let poly5 = interpolate(&field, &xs[..], &ys[..]);
 
 		assert_eq!(&poly5[..poly3.len()], &poly3);
 	}
 
+	#[test]
+	fn interpolator_reuses_points() {
+		let field = BinaryField::new(moduli::GF_2_8).unwrap();
+		let xs = [3_u32, 9, 12, 14, 1].map(Element::from);
+		let interpolator = Interpolator::new(&field, &xs[..]);
+		for seed in 1..8_u32 {
+			let poly = (0..xs.len() as u32).map(|i| Element::from((seed * 37 + i * 11) & 0xFF)).collect::<Vec<_>>();
+			let ys = xs.iter().map(|&x| field.eval_poly_at(&poly[..], x)).collect::<Vec<_>>();
+			let interpolated = interpolator.interpolate(&ys[..]);
+			assert_eq!(&interpolated[..poly.len()], &poly[..]);
+			assert!(interpolated[poly.len()..].iter().all(|&coeff| coeff == Element::zero()));
+		}
+	}
+
+	#[test]
+	fn additive_fft_in_place() {
+		for pd in [moduli::GF_2_16, moduli::GF_2_32] {
+			let field = BinaryField::new(pd).unwrap();
+			let domain = (0_u32..256).map(Element::from).collect::<Vec<_>>();
+			let plan = AdditiveFft::new(&field, &domain[..]);
+			assert_eq!(plan.len(), 256);
+
+			let poly = (0_u32..256).map(|x| Element::from(x.wrapping_mul(0x9E37_79B9) >> 16)).collect::<Vec<_>>();
+			let mut data = poly.clone();
+			plan.forward(&mut data[..]);
+			assert_eq!(data, _simple_ft(&field, &domain[..], &poly[..]));
+
+			plan.inverse(&mut data[..]);
+			assert_eq!(data, poly);
+		}
+	}
+
+	#[test]
+	fn shift_polys_match_reference() {
+		// the table of the python reference, for x^16 + x^5 + x^3 + x + 1
+		let reference: &[&[u32]] = &[
+			&[],
+			&[1],
+			&[32755, 32755],
+			&[52774, 60631, 8945],
+			&[38902, 5560, 44524, 12194],
+			&[55266, 46488, 60321, 5401, 40130],
+			&[21827, 32224, 51565, 15072, 8277, 64379],
+			&[59460, 15452, 60370, 24737, 20321, 35516, 39606],
+			&[42623, 56997, 25925, 15351, 16625, 47045, 38250, 17462],
+			&[7575, 27410, 32434, 22187, 28933, 15447, 37964, 38186, 4776],
+			&[39976, 61188, 42456, 2155, 6178, 34033, 52305, 14913, 2896, 48908],
+			&[6990, 12021, 36054, 16198, 17011, 14018, 58553, 13272, 25318, 5288, 21429],
+			&[16440, 34925, 14360, 22561, 43883, 36645, 7613, 26531, 8597, 59502, 61283, 53412],
+		];
+		let field = BinaryField::new(0x1002B).unwrap();
+		let rows = shift_polys(&field, reference.len() - 1);
+		for (row, expected) in rows.iter().zip(reference.iter()) {
+			assert_eq!(row, &expected.iter().map(Element::from).collect::<Vec<_>>());
+		}
+	}
+
+	#[test]
+	fn invfft2_matches_invfft() {
+		let field = BinaryField::new(1033).unwrap();
+		for log_n in 0..=10 {
+			let n = 1_usize << log_n;
+			let domain = (0..n as u32).map(Element::from).collect::<Vec<_>>();
+			let vals = (0..n as u32).map(|x| Element::from((x * 661 + 7) % 1024)).collect::<Vec<_>>();
+			assert_eq!(invfft2(&field, &vals[..]), invfft(&field, &domain[..], &vals[..]), "Failed at n = {}", n);
+		}
+	}
+
 	#[test]
 	fn fft_edr() {
 		// for GF(2^16)
 		// let poly = 0x01_02_10 as u32;
 
-		let field = BinaryField::new(1033.into()).unwrap();
+		let field = BinaryField::new(1033).unwrap();
 
 		let pd = 1024;
 		println!("S1");
-		let poly = (0_usize..pd)
-			.into_iter()
+		let poly = (0..pd as u32)
 			.map(Element::from)
-			.map(|x| x.pow_mod(9.into(), pd as u64))
+			.map(|x: Element| Element::from(pow_mod(x.value() as u64, 9, pd as u64) as u32))
 			.collect::<Vec<Element>>();
 
 		println!("S2");
-		let domain = (0_usize..pd).into_iter().map(Element::from).collect::<Vec<_>>();
+		let domain = (0..pd as u32).map(Element::from).collect::<Vec<_>>();
 		let z = fft(&field, &domain[..], &poly[..]);
 		println!("S3");
 		let z2 = _simple_ft(&field, &domain[..], &poly[..]);
@@ -972,20 +1668,19 @@ mod tests {
 		let poly2 = invfft(&field, &domain[..], &z[..]);
 		assert_eq!(&poly2[..], &poly[..]);
 
-		let poly3 = (0..25)
-			.into_iter()
+		let poly3 = (0_u32..25)
 			.map(Element::from)
-			.map(|x: Element| x.pow_mod(9.into(), pd as u64))
+			.map(|x: Element| Element::from(pow_mod(x.value() as u64, 9, pd as u64) as u32))
 			.collect::<Vec<Element>>();
 
-		let xs = (0..25).into_iter().map(Element::from).map(|x| (x * 11) % 32).collect::<Vec<Element>>();
+		let xs = (0_u32..25).map(Element::from).map(spread).collect::<Vec<Element>>();
 
 		let ys = xs.iter().map(|&x| field.eval_poly_at(&poly3, x)).collect::<Vec<Element>>();
 		let poly4 = interpolate(&field, &xs[..], &ys[..]);
 
 		assert_eq!(&poly4[..poly3.len()], &poly3[..]);
 
-		let xs = (1..25).into_iter().map(|x| (x * 11) % 32).map(Element::from).collect::<Vec<Element>>();
+		let xs = (1_u32..25).map(|x| (x * 11) % 32).map(Element::from).collect::<Vec<Element>>();
 		let ys = xs.iter().map(|&x| field.eval_poly_at(&poly3[..], x)).collect::<Vec<_>>();
 		let poly5 = interpolate(&field, &xs[..], &ys[..]);
 
@@ -0,0 +1,194 @@
+// Table free multiplication in GF(2^h), h <= 32, via carry-less products and Barrett reduction.
+//
+// With `mu = floor(x^(2h) / pd)`, the quotient of a product `p` of degree below `2h` by `pd` is
+// `floor(floor(p / x^h) * mu / x^h)`. The remainder then takes two more carry-less products instead
+// of a loop over the bits. On x86_64 all three products use PCLMULQDQ if the CPU supports it,
+// which is detected once per multiplier, with a portable fallback otherwise.
+
+use super::*;
+
+/// Multiplier for the field with reduction polynomial `pd`, without tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Barrett {
+	pd: u64,
+	height: u32,
+	mu: u64,
+	pclmul: bool,
+}
+
+impl Barrett {
+	/// Multiplier modulo `pd`, which is not checked for irreducibility.
+	pub fn new(pd: u64) -> Result<Self> {
+		let height = degree(pd)?;
+		Ok(Self { pd, height, mu: quotient(height, pd), pclmul: pclmul_detected() })
+	}
+
+	/// Like [`Barrett::new`], but never use PCLMULQDQ.
+	pub fn portable(pd: u64) -> Result<Self> {
+		Ok(Self { pclmul: false, ..Self::new(pd)? })
+	}
+
+	/// Whether products use the PCLMULQDQ instruction.
+	pub fn uses_pclmul(&self) -> bool {
+		self.pclmul
+	}
+
+	pub fn mul(&self, a: Element, b: Element) -> Element {
+		Element(self.mul_raw(a.0, b.0))
+	}
+
+	#[inline(always)]
+	pub(crate) fn mul_raw(&self, a: u32, b: u32) -> u32 {
+		#[cfg(target_arch = "x86_64")]
+		if self.pclmul {
+			// SAFETY: only set if the CPU supports the instruction
+			return unsafe { self.mul_pclmul(a, b) };
+		}
+		self.mul_portable(a, b)
+	}
+
+	/// `dst[i] ^= src[i] * factor` for symbols of up to 16 bits, one dispatch for the whole slice.
+	pub(crate) fn mul_add_slice(&self, dst: &mut [u16], src: &[u16], factor: u32) {
+		#[cfg(target_arch = "x86_64")]
+		if self.pclmul {
+			// SAFETY: only set if the CPU supports the instruction
+			return unsafe { self.mul_add_slice_pclmul(dst, src, factor) };
+		}
+		dst.iter_mut().zip(src).for_each(|(dst, &src)| *dst ^= self.mul_portable(src as u32, factor) as u16);
+	}
+
+	#[cfg(target_arch = "x86_64")]
+	#[target_feature(enable = "pclmulqdq")]
+	unsafe fn mul_add_slice_pclmul(&self, dst: &mut [u16], src: &[u16], factor: u32) {
+		use std::arch::x86_64::{_mm_clmulepi64_si128, _mm_cvtsi128_si64, _mm_set_epi64x};
+
+		debug_assert!(self.height <= 16);
+		let clmul = |a: u64, b: u64| {
+			let product = _mm_clmulepi64_si128(_mm_set_epi64x(0, a as i64), _mm_set_epi64x(0, b as i64), 0x00);
+			_mm_cvtsi128_si64(product) as u64
+		};
+		// two symbols per product, in lanes of 32 bits which hold all intermediate products
+		let lanes = self.mask() as u64 | (self.mask() as u64) << 32;
+		let (mut dst, mut src) = (dst.chunks_exact_mut(2), src.chunks_exact(2));
+		for (dst, src) in dst.by_ref().zip(src.by_ref()) {
+			let product = clmul(src[0] as u64 | (src[1] as u64) << 32, factor as u64);
+			let q = clmul((clmul(product >> self.height & lanes, self.mu) >> self.height) & lanes, self.pd);
+			let remainder = (product ^ q) & lanes;
+			dst[0] ^= remainder as u16;
+			dst[1] ^= (remainder >> 32) as u16;
+		}
+		for (dst, &src) in dst.into_remainder().iter_mut().zip(src.remainder()) {
+			*dst ^= self.mul_pclmul(src as u32, factor) as u16;
+		}
+	}
+
+	fn mul_portable(&self, a: u32, b: u32) -> u32 {
+		let product = clmul(a, b);
+		let q = clmul64(clmul64(product >> self.height, self.mu) >> self.height, self.pd);
+		(product ^ q) as u32 & self.mask()
+	}
+
+	#[cfg(target_arch = "x86_64")]
+	#[target_feature(enable = "pclmulqdq")]
+	#[inline]
+	unsafe fn mul_pclmul(&self, a: u32, b: u32) -> u32 {
+		use std::arch::x86_64::{_mm_clmulepi64_si128, _mm_cvtsi128_si64, _mm_set_epi64x};
+
+		// all products have degree below 64, so the low half suffices
+		let clmul = |a: u64, b: u64| {
+			let product = _mm_clmulepi64_si128(_mm_set_epi64x(0, a as i64), _mm_set_epi64x(0, b as i64), 0x00);
+			_mm_cvtsi128_si64(product) as u64
+		};
+		let product = clmul(a as u64, b as u64);
+		let q = clmul(clmul(product >> self.height, self.mu) >> self.height, self.pd);
+		(product ^ q) as u32 & self.mask()
+	}
+
+	#[inline(always)]
+	fn mask(&self) -> u32 {
+		((1_u64 << self.height) - 1) as u32
+	}
+}
+
+fn pclmul_detected() -> bool {
+	#[cfg(target_arch = "x86_64")]
+	{
+		std::is_x86_feature_detected!("pclmulqdq")
+	}
+	#[cfg(not(target_arch = "x86_64"))]
+	{
+		false
+	}
+}
+
+/// `floor(x^(2 * height) / pd)`, of degree `height`.
+fn quotient(height: u32, pd: u64) -> u64 {
+	let (mut rem, mut quot) = (1_u128 << (2 * height), 0_u64);
+	for bit in (0..=height).rev() {
+		if rem >> (bit + height) & 1 != 0 {
+			rem ^= (pd as u128) << bit;
+			quot |= 1 << bit;
+		}
+	}
+	quot
+}
+
+/// Carry-less product of polynomials whose product has degree below 64.
+#[inline(always)]
+fn clmul64(a: u64, mut b: u64) -> u64 {
+	let mut o = 0_u64;
+	let mut shift = 0;
+	while b != 0 {
+		if b & 1 != 0 {
+			o ^= a << shift;
+		}
+		b >>= 1;
+		shift += 1;
+	}
+	o
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn samples(mask: u32) -> impl Iterator<Item = (u32, u32)> {
+		(0..2000_u32).map(move |i| (i.wrapping_mul(0x9E37_79B9) & mask, (i ^ 0x5555).wrapping_mul(0x2F6B_5A3D) & mask))
+	}
+
+	#[test]
+	fn matches_bitwise_reduction() {
+		for pd in [moduli::GF_2_8, moduli::GF_2_10, moduli::GF_2_16, moduli::GF_2_32, 0x1002B] {
+			let barrett = Barrett::new(pd).unwrap();
+			let portable = Barrett::portable(pd).unwrap();
+			let height = degree(pd).unwrap();
+			for (a, b) in samples(barrett.mask()) {
+				let expected = reduce(clmul(a, b), pd, height);
+				assert_eq!(portable.mul_raw(a, b), expected, "{:#x} * {:#x} mod {:#x}", a, b, pd);
+				assert_eq!(barrett.mul_raw(a, b), expected, "{:#x} * {:#x} mod {:#x}", a, b, pd);
+			}
+			// extremes, the product of the largest elements has the maximal degree
+			let max = barrett.mask();
+			assert_eq!(barrett.mul_raw(max, max), reduce(clmul(max, max), pd, height));
+			assert_eq!(barrett.mul_raw(0, max), 0);
+		}
+	}
+
+	#[test]
+	fn quotient_is_floor_division() {
+		// x^16 = (x^8 + x^4 + x^3 + x^2 + 1) * q + r with deg r < 8
+		let q = quotient(8, moduli::GF_2_8);
+		assert_eq!(63 - q.leading_zeros(), 8);
+		let r = (1_u64 << 16) ^ clmul64(q, moduli::GF_2_8);
+		assert!(r < 1 << 8);
+	}
+
+	#[test]
+	fn table_free_field_uses_barrett() {
+		let field = BinaryField::new(moduli::GF_2_32).unwrap();
+		let barrett = Barrett::new(moduli::GF_2_32).unwrap();
+		for (a, b) in samples(u32::MAX) {
+			assert_eq!(field.mul(a.into(), b.into()), barrett.mul(a.into(), b.into()));
+		}
+	}
+}
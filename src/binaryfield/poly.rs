@@ -0,0 +1,504 @@
+// Polynomials over a binary field, lowest degree coefficient first.
+//
+// Trailing zero coefficients are stripped, so the zero polynomial has no coefficients
+// and the degree is always the index of the last one.
+
+use super::*;
+
+use std::ops;
+
+/// Multiply via the additive FFT once both factors have at least this many coefficients.
+pub const FFT_MUL_THRESHOLD: usize = 64;
+
+/// A polynomial over the binary field `field`.
+#[derive(Clone)]
+pub struct Poly<'a> {
+	field: &'a BinaryField,
+	coeffs: Vec<Element>,
+}
+
+impl fmt::Debug for Poly<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_tuple("Poly").field(&self.coeffs).finish()
+	}
+}
+
+impl PartialEq for Poly<'_> {
+	fn eq(&self, other: &Self) -> bool {
+		self.coeffs == other.coeffs
+	}
+}
+
+impl Eq for Poly<'_> {}
+
+impl<'a> Poly<'a> {
+	/// The polynomial with the coefficients `coeffs`, lowest degree first.
+	pub fn new(field: &'a BinaryField, coeffs: Vec<Element>) -> Self {
+		let mut poly = Self { field, coeffs };
+		poly.normalize();
+		poly
+	}
+
+	pub fn zero(field: &'a BinaryField) -> Self {
+		Self { field, coeffs: vec![] }
+	}
+
+	pub fn one(field: &'a BinaryField) -> Self {
+		Self::constant(field, Element::one())
+	}
+
+	pub fn constant(field: &'a BinaryField, c: Element) -> Self {
+		Self::new(field, vec![c])
+	}
+
+	/// `c * x^degree`
+	pub fn monomial(field: &'a BinaryField, c: Element, degree: usize) -> Self {
+		let mut coeffs = vec![Element::zero(); degree + 1];
+		coeffs[degree] = c;
+		Self::new(field, coeffs)
+	}
+
+	/// The monic polynomial vanishing exactly at `roots`.
+	pub fn from_roots(field: &'a BinaryField, roots: &[Element]) -> Self {
+		if roots.len() < FFT_MUL_THRESHOLD {
+			return Self::new(field, field.zpoly(roots));
+		}
+		let (left, right) = roots.split_at(roots.len() / 2);
+		Self::from_roots(field, left).mul(&Self::from_roots(field, right))
+	}
+
+	fn normalize(&mut self) {
+		while self.coeffs.last() == Some(&Element::zero()) {
+			self.coeffs.pop();
+		}
+	}
+
+	pub fn field(&self) -> &'a BinaryField {
+		self.field
+	}
+
+	/// The coefficients, lowest degree first, without trailing zeros.
+	pub fn coeffs(&self) -> &[Element] {
+		&self.coeffs[..]
+	}
+
+	pub fn into_coeffs(self) -> Vec<Element> {
+		self.coeffs
+	}
+
+	/// The coefficient of `x^i`.
+	pub fn coeff(&self, i: usize) -> Element {
+		self.coeffs.get(i).copied().unwrap_or_else(Element::zero)
+	}
+
+	/// The degree, `None` for the zero polynomial.
+	pub fn degree(&self) -> Option<usize> {
+		self.coeffs.len().checked_sub(1)
+	}
+
+	pub fn is_zero(&self) -> bool {
+		self.coeffs.is_empty()
+	}
+
+	/// The coefficient of the highest power, zero for the zero polynomial.
+	pub fn leading(&self) -> Element {
+		self.coeffs.last().copied().unwrap_or_else(Element::zero)
+	}
+
+	/// Evaluate at `x` with Horner's scheme.
+	pub fn eval(&self, x: Element) -> Element {
+		self.coeffs.iter().rev().fold(Element::zero(), |acc, &c| self.field.mul(acc, x) ^ c)
+	}
+
+	pub fn add(&self, other: &Self) -> Self {
+		let (long, short) = if self.coeffs.len() >= other.coeffs.len() { (self, other) } else { (other, self) };
+		let mut coeffs = long.coeffs.clone();
+		for (c, &o) in coeffs.iter_mut().zip(short.coeffs.iter()) {
+			*c ^= o;
+		}
+		Self::new(self.field, coeffs)
+	}
+
+	/// Same as [`Poly::add`] in characteristic 2.
+	pub fn sub(&self, other: &Self) -> Self {
+		self.add(other)
+	}
+
+	/// Multiply every coefficient by `c`.
+	pub fn scale(&self, c: Element) -> Self {
+		Self::new(self.field, self.field.mul_by_const(&self.coeffs[..], c))
+	}
+
+	/// Multiply by `x^n`.
+	pub fn shl(&self, n: usize) -> Self {
+		if self.is_zero() {
+			return self.clone();
+		}
+		let mut coeffs = vec![Element::zero(); n];
+		coeffs.extend_from_slice(&self.coeffs[..]);
+		Self { field: self.field, coeffs }
+	}
+
+	/// Multiply, via the additive FFT for large factors.
+	pub fn mul(&self, other: &Self) -> Self {
+		let product_len = (self.coeffs.len() + other.coeffs.len()).saturating_sub(1);
+		let fits = product_len.next_power_of_two() as u64 <= 1_u64 << self.field.height();
+		if cmp::min(self.coeffs.len(), other.coeffs.len()) >= FFT_MUL_THRESHOLD && fits {
+			self.mul_fft(other)
+		} else {
+			self.mul_schoolbook(other)
+		}
+	}
+
+	/// Quadratic multiplication.
+	pub fn mul_schoolbook(&self, other: &Self) -> Self {
+		if self.is_zero() || other.is_zero() {
+			return Self::zero(self.field);
+		}
+		Self::new(self.field, self.field.mul_polys(self.coeffs.clone(), other.coeffs.clone()))
+	}
+
+	/// Multiply by evaluating both factors over a subspace large enough for the product.
+	///
+	/// The product must have at most as many coefficients as the field has elements.
+	pub fn mul_fft(&self, other: &Self) -> Self {
+		if self.is_zero() || other.is_zero() {
+			return Self::zero(self.field);
+		}
+		let n = (self.coeffs.len() + other.coeffs.len() - 1).next_power_of_two();
+		let domain = (0..n as u32).map(Element::from).collect::<Vec<_>>();
+		let transform = AdditiveFft::new(self.field, &domain[..]);
+
+		let mut a = self.coeffs.clone();
+		a.resize(n, Element::zero());
+		let mut b = other.coeffs.clone();
+		b.resize(n, Element::zero());
+		transform.forward(&mut a[..]);
+		transform.forward(&mut b[..]);
+		for (a, b) in a.iter_mut().zip(b) {
+			*a = self.field.mul(*a, b);
+		}
+		transform.inverse(&mut a[..]);
+		Self::new(self.field, a)
+	}
+
+	/// Keep the coefficients below `x^n`, i.e. reduce modulo `x^n`.
+	pub fn truncate(&self, n: usize) -> Self {
+		Self::new(self.field, self.coeffs.iter().take(n).copied().collect())
+	}
+
+	/// Reverse the lowest `n` coefficients, `x^(n - 1) * self(1 / x)` for degrees below `n`.
+	fn reverse(&self, n: usize) -> Self {
+		Self::new(self.field, (0..n).rev().map(|i| self.coeff(i)).collect())
+	}
+
+	/// The power series inverse modulo `x^n`, the constant coefficient must not be zero.
+	///
+	/// Newton iteration `g <- 2g - f g^2`, which is just `f g^2` in characteristic 2.
+	pub fn inverse_series(&self, n: usize) -> Self {
+		assert_ne!(self.coeff(0), Element::zero(), "Only units have a power series inverse");
+		let mut g = Self::constant(self.field, self.field.inv(self.coeff(0)));
+		let mut precision = 1;
+		while precision < n {
+			precision = cmp::min(precision << 1, n);
+			g = self.truncate(precision).mul(&g.mul(&g)).truncate(precision);
+		}
+		g.truncate(n)
+	}
+
+	/// Quotient and remainder of the division by `divisor`, which must not be zero.
+	///
+	/// Large divisions use the power series inverse of the reversed divisor and FFT multiplication.
+	pub fn divmod(&self, divisor: &Self) -> (Self, Self) {
+		let dlen = divisor.coeffs.len();
+		assert!(dlen > 0, "Division by the zero polynomial");
+		if self.coeffs.len() < dlen {
+			return (Self::zero(self.field), self.clone());
+		}
+		if cmp::min(dlen, self.coeffs.len() - dlen + 1) >= FFT_MUL_THRESHOLD {
+			self.divmod_newton(divisor)
+		} else {
+			self.divmod_schoolbook(divisor)
+		}
+	}
+
+	fn divmod_schoolbook(&self, divisor: &Self) -> (Self, Self) {
+		let dlen = divisor.coeffs.len();
+		let field = self.field;
+		let inv_lead = field.inv(divisor.leading());
+		let mut rem = self.coeffs.clone();
+		let mut quot = vec![Element::zero(); rem.len() - dlen + 1];
+		for i in (0..quot.len()).rev() {
+			let q = field.mul(rem[i + dlen - 1], inv_lead);
+			if q == Element::zero() {
+				continue;
+			}
+			quot[i] = q;
+			for (j, &d) in divisor.coeffs.iter().enumerate() {
+				rem[i + j] ^= field.mul(d, q);
+			}
+		}
+		rem.truncate(dlen - 1);
+		(Self::new(field, quot), Self::new(field, rem))
+	}
+
+	fn divmod_newton(&self, divisor: &Self) -> (Self, Self) {
+		let (alen, dlen) = (self.coeffs.len(), divisor.coeffs.len());
+		let qlen = alen - dlen + 1;
+		// rev(q) = rev(a) / rev(d) mod x^qlen
+		let inv = divisor.reverse(dlen).inverse_series(qlen);
+		let quot = self.reverse(alen).truncate(qlen).mul(&inv).truncate(qlen).reverse(qlen);
+		let rem = self.sub(&quot.mul(divisor));
+		(quot, rem)
+	}
+
+	/// Scale to a leading coefficient of one, the zero polynomial stays zero.
+	pub fn monic(&self) -> Self {
+		if self.is_zero() {
+			return self.clone();
+		}
+		self.scale(self.field.inv(self.leading()))
+	}
+
+	/// Formal derivative, in characteristic 2 only the odd powers survive.
+	pub fn derivative(&self) -> Self {
+		let coeffs = self
+			.coeffs
+			.iter()
+			.enumerate()
+			.skip(1)
+			.map(|(i, &c)| if i & 0x1 == 1 { c } else { Element::zero() })
+			.collect();
+		Self::new(self.field, coeffs)
+	}
+
+	/// The monic greatest common divisor, zero if both are zero.
+	pub fn gcd(&self, other: &Self) -> Self {
+		let (mut a, mut b) = (self.clone(), other.clone());
+		while !b.is_zero() {
+			let r = &a % &b;
+			a = b;
+			b = r;
+		}
+		a.monic()
+	}
+
+	/// The distinct roots in the field, ascending, the polynomial must not be zero.
+	///
+	/// Berlekamp's trace algorithm: `gcd(self, x^(2^m) - x)` has one linear factor per root,
+	/// the traces `Tr(b x)` of the basis elements `b` split them apart.
+	pub fn roots(&self) -> Vec<Element> {
+		assert!(!self.is_zero(), "Every element is a root of the zero polynomial");
+		let field = self.field;
+		let height = field.height();
+
+		// x^(2^m) modulo self
+		let x = Self::monomial(field, Element::one(), 1);
+		let mut power = &x % self;
+		for _ in 0..height {
+			power = &power.mul(&power) % self;
+		}
+		let mut pending = vec![self.gcd(&power.add(&(&x % self)))];
+
+		let mut roots = vec![];
+		while let Some(factor) = pending.pop() {
+			match factor.degree() {
+				None | Some(0) => {}
+				Some(1) => roots.push(field.div(factor.coeff(0), factor.coeff(1))),
+				Some(degree) => {
+					// distinct roots differ in the trace of some `b x`
+					let split = (0..height)
+						.find_map(|i| {
+							let mut term = &Self::monomial(field, Element::from(1_u32 << i), 1) % &factor;
+							let mut trace = term.clone();
+							for _ in 1..height {
+								term = &term.mul(&term) % &factor;
+								trace = trace.add(&term);
+							}
+							let divisor = factor.gcd(&trace);
+							divisor.degree().filter(|&d| d > 0 && d < degree).map(|_| divisor)
+						})
+						.expect("The trace form is non degenerate. qed");
+					pending.push(&factor / &split);
+					pending.push(split);
+				}
+			}
+		}
+		roots.sort_unstable();
+		roots
+	}
+
+	/// `self(inner(x))`
+	pub fn compose(&self, inner: &Self) -> Self {
+		self.coeffs
+			.iter()
+			.rev()
+			.fold(Self::zero(self.field), |acc, &c| acc.mul(inner).add(&Self::constant(self.field, c)))
+	}
+}
+
+impl<'a> ops::Add for &Poly<'a> {
+	type Output = Poly<'a>;
+	fn add(self, rhs: Self) -> Self::Output {
+		Poly::add(self, rhs)
+	}
+}
+
+impl<'a> ops::Sub for &Poly<'a> {
+	type Output = Poly<'a>;
+	fn sub(self, rhs: Self) -> Self::Output {
+		Poly::sub(self, rhs)
+	}
+}
+
+impl<'a> ops::Mul for &Poly<'a> {
+	type Output = Poly<'a>;
+	fn mul(self, rhs: Self) -> Self::Output {
+		Poly::mul(self, rhs)
+	}
+}
+
+impl<'a> ops::Div for &Poly<'a> {
+	type Output = Poly<'a>;
+	fn div(self, rhs: Self) -> Self::Output {
+		self.divmod(rhs).0
+	}
+}
+
+impl<'a> ops::Rem for &Poly<'a> {
+	type Output = Poly<'a>;
+	fn rem(self, rhs: Self) -> Self::Output {
+		self.divmod(rhs).1
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn poly<'a>(field: &'a BinaryField, coeffs: &[u32]) -> Poly<'a> {
+		Poly::new(field, coeffs.iter().map(Element::from).collect())
+	}
+
+	fn pseudo_random<'a>(field: &'a BinaryField, len: usize, seed: u32) -> Poly<'a> {
+		let mask = field.order() as u32;
+		let coeffs = (0..len as u32).map(|i| Element::from((i ^ seed).wrapping_mul(0x9E37_79B9) >> 16 & mask)).collect();
+		Poly::new(field, coeffs)
+	}
+
+	#[test]
+	fn normalized() {
+		let field = BinaryField::new(moduli::GF_2_16).unwrap();
+		let p = poly(&field, &[1, 2, 0, 0]);
+		assert_eq!(p.degree(), Some(1));
+		assert_eq!(p.coeffs(), &[Element::from(1_u32), Element::from(2_u32)]);
+		assert_eq!(&p - &p, Poly::zero(&field));
+		assert_eq!(Poly::zero(&field).degree(), None);
+		assert_eq!(Poly::monomial(&field, 3_u32.into(), 4).coeff(4), Element::from(3_u32));
+	}
+
+	#[test]
+	fn fft_mul_matches_schoolbook() {
+		let field = BinaryField::new(moduli::GF_2_16).unwrap();
+		for (la, lb) in [(1, 1), (3, 70), (64, 64), (100, 200), (257, 3)] {
+			let a = pseudo_random(&field, la, 1);
+			let b = pseudo_random(&field, lb, 2);
+			let expected = a.mul_schoolbook(&b);
+			assert_eq!(a.mul_fft(&b), expected);
+			assert_eq!(&a * &b, expected);
+			assert_eq!(expected.degree(), Some(a.degree().unwrap() + b.degree().unwrap()));
+		}
+	}
+
+	#[test]
+	fn divmod() {
+		let field = BinaryField::new(1033).unwrap();
+		let a = pseudo_random(&field, 50, 3);
+		let b = pseudo_random(&field, 17, 4);
+		let (q, r) = a.divmod(&b);
+		assert!(r.degree() < b.degree());
+		assert_eq!(&(&q * &b) + &r, a);
+		assert_eq!(&(&a * &b) / &b, a);
+		assert!((&(&a * &b) % &b).is_zero());
+
+		let (q, r) = b.divmod(&a);
+		assert!(q.is_zero());
+		assert_eq!(r, b);
+	}
+
+	#[test]
+	fn eval_and_roots() {
+		let field = BinaryField::new(1033).unwrap();
+		let roots = (0..100_u32).map(|x| Element::from(x * 7 + 1)).collect::<Vec<_>>();
+		let z = Poly::from_roots(&field, &roots[..]);
+		assert_eq!(z.degree(), Some(roots.len()));
+		assert_eq!(z.leading(), Element::one());
+		assert!(roots.iter().all(|&x| z.eval(x) == Element::zero()));
+		assert_ne!(z.eval(Element::from(2_u32)), Element::zero());
+
+		let p = pseudo_random(&field, 20, 5);
+		for x in (0..1024_u32).step_by(37).map(Element::from) {
+			assert_eq!(p.eval(x), field.eval_poly_at(p.coeffs(), x));
+		}
+	}
+
+	#[test]
+	fn newton_division() {
+		let field = BinaryField::new(moduli::GF_2_16).unwrap();
+		let a = pseudo_random(&field, 700, 10);
+		let b = pseudo_random(&field, 300, 11);
+		assert_eq!(a.divmod_newton(&b), a.divmod_schoolbook(&b));
+
+		let inv = b.inverse_series(200);
+		assert_eq!(b.mul(&inv).truncate(200), Poly::one(&field));
+	}
+
+	#[test]
+	fn derivative() {
+		let field = BinaryField::new(1033).unwrap();
+		// (x^3 + 5x^2 + 7x + 9)' = 3x^2 + 10x + 7 = x^2 + 7
+		assert_eq!(poly(&field, &[9, 7, 5, 1]).derivative(), poly(&field, &[7, 0, 1]));
+
+		// product rule
+		let a = pseudo_random(&field, 9, 6);
+		let b = pseudo_random(&field, 12, 7);
+		assert_eq!((&a * &b).derivative(), &(&a.derivative() * &b) + &(&a * &b.derivative()));
+	}
+
+	#[test]
+	fn gcd() {
+		let field = BinaryField::new(1033).unwrap();
+		let common = Poly::from_roots(&field, &[3_u32.into(), 17_u32.into(), 900_u32.into()]);
+		let a = &common * &Poly::from_roots(&field, &[1_u32.into(), 2_u32.into()]);
+		let b = &common * &Poly::from_roots(&field, &[5_u32.into()]).scale(77_u32.into());
+		assert_eq!(a.gcd(&b), common);
+		assert_eq!(a.gcd(&Poly::zero(&field)), a.monic());
+		let (one, two) = (Poly::from_roots(&field, &[1_u32.into()]), Poly::from_roots(&field, &[2_u32.into()]));
+		assert_eq!(one.gcd(&two), Poly::one(&field));
+	}
+
+	#[test]
+	fn roots() {
+		let field = BinaryField::new(1033).unwrap();
+		let roots = Poly::from_roots(&field, &[900_u32.into(), 17_u32.into(), 3_u32.into(), 17_u32.into()]);
+		let p = &roots * &pseudo_random(&field, 7, 12);
+		let expected = (0..1024_u32).map(Element::from).filter(|&x| p.eval(x) == Element::zero()).collect::<Vec<_>>();
+		assert!(expected.len() >= 3);
+		assert_eq!(p.roots(), expected);
+
+		assert_eq!(Poly::one(&field).roots(), vec![]);
+		assert_eq!(Poly::monomial(&field, 5_u32.into(), 3).roots(), vec![Element::zero()]);
+	}
+
+	#[test]
+	fn compose() {
+		let field = BinaryField::new(1033).unwrap();
+		let outer = pseudo_random(&field, 5, 8);
+		let inner = pseudo_random(&field, 4, 9);
+		let composed = outer.compose(&inner);
+		assert_eq!(composed.degree(), Some(12));
+		for x in (1..1024_u32).step_by(101).map(Element::from) {
+			assert_eq!(composed.eval(x), outer.eval(inner.eval(x)));
+		}
+	}
+}
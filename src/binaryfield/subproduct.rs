@@ -0,0 +1,194 @@
+// Multipoint evaluation and interpolation at arbitrary distinct points via a subproduct tree.
+//
+// Level 0 holds the linear factors `x - x_i`, every node of the next level the product of two
+// neighbours, so the root vanishes on all points. Evaluation reduces a polynomial down the tree,
+// interpolation combines the weighted leaves up the tree, both in O(n log^2 n) with FFT products.
+
+use super::*;
+
+/// Evaluate subtrees with at most this many points directly.
+const DIRECT_EVAL_THRESHOLD: usize = 16;
+
+/// The subproduct tree of a set of distinct points.
+pub struct SubproductTree<'a> {
+	field: &'a BinaryField,
+	points: Vec<Element>,
+	// `levels[l][j]` vanishes on the points `j * 2^l .. (j + 1) * 2^l`
+	levels: Vec<Vec<Poly<'a>>>,
+}
+
+impl<'a> SubproductTree<'a> {
+	/// Build the tree for `points`, which must not be empty.
+	pub fn new(field: &'a BinaryField, points: &[Element]) -> Self {
+		assert!(!points.is_empty(), "Subproduct tree requires at least one point");
+
+		let leaves = points.iter().map(|&x| Poly::new(field, vec![x, Element::one()])).collect::<Vec<_>>();
+		let mut levels = vec![leaves];
+		while levels.last().unwrap().len() > 1 {
+			let next = levels
+				.last()
+				.unwrap()
+				.chunks(2)
+				.map(|pair| match pair {
+					[left, right] => left.mul(right),
+					[single] => single.clone(),
+					_ => unreachable!("chunks of at most two. qed"),
+				})
+				.collect();
+			levels.push(next);
+		}
+
+		Self { field, points: points.to_vec(), levels }
+	}
+
+	pub fn points(&self) -> &[Element] {
+		&self.points[..]
+	}
+
+	/// The monic polynomial vanishing on all points.
+	pub fn root(&self) -> &Poly<'a> {
+		&self.levels.last().unwrap()[0]
+	}
+
+	fn range(&self, level: usize, idx: usize) -> std::ops::Range<usize> {
+		let start = idx << level;
+		start..cmp::min((idx + 1) << level, self.points.len())
+	}
+
+	/// Evaluate `poly` at all points.
+	pub fn evaluate(&self, poly: &Poly<'a>) -> Vec<Element> {
+		let mut values = vec![Element::zero(); self.points.len()];
+		let top = self.levels.len() - 1;
+		self.evaluate_node(&(poly % self.root()), top, 0, &mut values[..]);
+		values
+	}
+
+	fn evaluate_node(&self, rem: &Poly<'a>, level: usize, idx: usize, values: &mut [Element]) {
+		let range = self.range(level, idx);
+		if range.len() <= DIRECT_EVAL_THRESHOLD || level == 0 {
+			for i in range {
+				values[i] = rem.eval(self.points[i]);
+			}
+			return;
+		}
+		for child in [2 * idx, 2 * idx + 1] {
+			if let Some(node) = self.levels[level - 1].get(child) {
+				self.evaluate_node(&(rem % node), level - 1, child, values);
+			}
+		}
+	}
+
+	/// The polynomial of degree below the number of points that takes `values` at the points.
+	///
+	/// # Panics
+	///
+	/// If the points are not distinct or there is not one value per point.
+	pub fn interpolate(&self, values: &[Element]) -> Poly<'a> {
+		assert_eq!(values.len(), self.points.len());
+
+		// Lagrange weights `y_i / M'(x_i)`, with `M` the root
+		let denominators = self.evaluate(&self.root().derivative());
+		assert!(denominators.iter().all(|&d| d != Element::zero()), "Interpolation points must be distinct");
+		let inverses = self.field.multi_inv(&denominators[..]);
+		let mut level = values
+			.iter()
+			.zip(inverses)
+			.map(|(&y, inv)| Poly::constant(self.field, self.field.mul(y, inv)))
+			.collect::<Vec<_>>();
+
+		// combine `left * M_right + right * M_left` up the tree
+		for nodes in self.levels[..(self.levels.len() - 1)].iter() {
+			level = level
+				.chunks(2)
+				.zip(nodes.chunks(2))
+				.map(|(polys, nodes)| match (polys, nodes) {
+					([left, right], [m_left, m_right]) => &left.mul(m_right) + &right.mul(m_left),
+					([single], _) => single.clone(),
+					_ => unreachable!("tree levels are paired alike. qed"),
+				})
+				.collect();
+		}
+		level.pop().unwrap()
+	}
+}
+
+impl<'a> Poly<'a> {
+	/// Evaluate at arbitrary `points`, see [`SubproductTree::evaluate`].
+	pub fn evaluate_many(&self, points: &[Element]) -> Vec<Element> {
+		if points.is_empty() {
+			return vec![];
+		}
+		SubproductTree::new(self.field(), points).evaluate(self)
+	}
+
+	/// Interpolate through `(xs[i], ys[i])` for arbitrary distinct `xs`, see [`SubproductTree::interpolate`].
+	pub fn interpolate(field: &'a BinaryField, xs: &[Element], ys: &[Element]) -> Self {
+		if xs.is_empty() {
+			return Self::zero(field);
+		}
+		SubproductTree::new(field, xs).interpolate(ys)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn points(n: usize, mask: u32) -> Vec<Element> {
+		// odd multipliers are bijective modulo powers of two, so the points are distinct
+		(0..n as u32).map(|i| Element::from(i.wrapping_mul(0x2F6B_5A3D).wrapping_add(0x1234) & mask)).collect()
+	}
+
+	#[test]
+	fn evaluate_matches_horner() {
+		let field = BinaryField::new(moduli::GF_2_16).unwrap();
+		let xs = points(333, 0xFFFF);
+		let poly = Poly::new(&field, points(500, 0xFFFF).into_iter().rev().collect());
+
+		let tree = SubproductTree::new(&field, &xs[..]);
+		assert_eq!(tree.root().degree(), Some(xs.len()));
+		assert!(xs.iter().all(|&x| tree.root().eval(x) == Element::zero()));
+
+		let expected = xs.iter().map(|&x| poly.eval(x)).collect::<Vec<_>>();
+		assert_eq!(tree.evaluate(&poly), expected);
+		assert_eq!(poly.evaluate_many(&xs[..]), expected);
+	}
+
+	#[test]
+	fn interpolate_roundtrip() {
+		let field = BinaryField::new(moduli::GF_2_16).unwrap();
+		for n in [1, 2, 3, 17, 100, 257] {
+			let xs = points(n, 0xFFFF);
+			let ys = points(n, 0xFF00).into_iter().rev().collect::<Vec<_>>();
+			let poly = Poly::interpolate(&field, &xs[..], &ys[..]);
+			assert!(poly.degree().is_none_or(|degree| degree < n));
+			assert_eq!(poly.evaluate_many(&xs[..]), ys);
+		}
+	}
+
+	#[test]
+	fn matches_lagrange() {
+		let field = BinaryField::new(1033).unwrap();
+		let xs = points(20, 0x3FF);
+		let ys = points(20, 0x155);
+		let expected = field.lagrange_interp(xs.clone(), ys.clone());
+		assert_eq!(Poly::interpolate(&field, &xs[..], &ys[..]), Poly::new(&field, expected));
+	}
+
+	#[test]
+	fn large_field() {
+		let field = BinaryField::new(moduli::GF_2_32).unwrap();
+		let xs = points(40, u32::MAX);
+		let ys = points(40, 0xFFFF_0000);
+		let poly = Poly::interpolate(&field, &xs[..], &ys[..]);
+		assert_eq!(poly.evaluate_many(&xs[..]), ys);
+	}
+
+	#[test]
+	#[should_panic(expected = "distinct")]
+	fn duplicate_points() {
+		let field = BinaryField::new(1033).unwrap();
+		let xs = [1_u32, 2, 1].map(Element::from);
+		Poly::interpolate(&field, &xs[..], &[Element::one(); 3]);
+	}
+}
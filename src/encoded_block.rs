@@ -0,0 +1,172 @@
+// All shards of one encoding in a single allocation, with borrowed views per shard.
+//
+// Each shard starts at a multiple of `SHARD_ALIGNMENT`, so the shards are
+// cache line aligned and can be processed with aligned SIMD loads.
+
+use super::*;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Alignment in bytes of every shard within an [`EncodedBlock`].
+pub const SHARD_ALIGNMENT: usize = 64;
+
+#[derive(Clone, Copy)]
+#[repr(C, align(64))]
+struct Line([u8; SHARD_ALIGNMENT]);
+
+/// `n` shards of `shard_len` bytes each, backed by one aligned buffer.
+#[derive(Clone)]
+pub struct EncodedBlock {
+	lines: Vec<Line>,
+	n: usize,
+	shard_len: usize,
+	// distance in bytes between the starts of two shards
+	stride: usize,
+}
+
+impl EncodedBlock {
+	/// Allocate `n` zeroed shards of `shard_len` bytes, which must be even.
+	pub fn new(n: usize, shard_len: usize) -> Self {
+		assert_eq!(shard_len & 0x01, 0, "Shards consist of 2 byte symbols");
+		let lines_per_shard = core::cmp::max(shard_len.div_ceil(SHARD_ALIGNMENT), 1);
+		Self {
+			lines: vec![Line([0u8; SHARD_ALIGNMENT]); n * lines_per_shard],
+			n,
+			shard_len,
+			stride: lines_per_shard * SHARD_ALIGNMENT,
+		}
+	}
+
+	/// Number of shards.
+	pub fn len(&self) -> usize {
+		self.n
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.n == 0
+	}
+
+	/// Length of each shard in bytes.
+	pub fn shard_len(&self) -> usize {
+		self.shard_len
+	}
+
+	fn bytes(&self) -> &[u8] {
+		unsafe { ::core::slice::from_raw_parts(self.lines.as_ptr() as *const u8, self.lines.len() * SHARD_ALIGNMENT) }
+	}
+
+	fn bytes_mut(&mut self) -> &mut [u8] {
+		unsafe {
+			::core::slice::from_raw_parts_mut(self.lines.as_mut_ptr() as *mut u8, self.lines.len() * SHARD_ALIGNMENT)
+		}
+	}
+
+	/// The shard at `index`.
+	pub fn shard(&self, index: usize) -> &[u8] {
+		assert!(index < self.n, "Shard index {} is out of bounds for {} shards", index, self.n);
+		let offset = index * self.stride;
+		&self.bytes()[offset..(offset + self.shard_len)]
+	}
+
+	/// The shard at `index`, mutable.
+	pub fn shard_mut(&mut self, index: usize) -> &mut [u8] {
+		assert!(index < self.n, "Shard index {} is out of bounds for {} shards", index, self.n);
+		let offset = index * self.stride;
+		let shard_len = self.shard_len;
+		&mut self.bytes_mut()[offset..(offset + shard_len)]
+	}
+
+	/// The shard at `index` as 2 byte symbols.
+	pub fn symbols(&self, index: usize) -> &[[u8; 2]] {
+		as_symbols(self.shard(index))
+	}
+
+	/// The shard at `index` as mutable 2 byte symbols.
+	pub fn symbols_mut(&mut self, index: usize) -> &mut [[u8; 2]] {
+		as_symbols_mut(self.shard_mut(index))
+	}
+
+	/// Iterate over all shards in order.
+	pub fn shards(&self) -> impl Iterator<Item = &[u8]> {
+		let shard_len = self.shard_len;
+		self.bytes().chunks(self.stride).take(self.n).map(move |shard| &shard[..shard_len])
+	}
+
+	/// Mutable views of all shards, as taken by `encode_into`.
+	pub fn shards_mut(&mut self) -> Vec<&mut [u8]> {
+		let (n, shard_len, stride) = (self.n, self.shard_len, self.stride);
+		self.bytes_mut().chunks_mut(stride).take(n).map(|shard| &mut shard[..shard_len]).collect()
+	}
+
+	/// Copy every shard into an owned [`WrappedShard`].
+	pub fn to_shards(&self) -> Vec<WrappedShard> {
+		self.shards().map(|shard| WrappedShard::new(shard.to_vec())).collect()
+	}
+}
+
+impl From<EncodedBlock> for Vec<WrappedShard> {
+	fn from(block: EncodedBlock) -> Self {
+		block.to_shards()
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+
+	#[test]
+	fn views_are_aligned_and_disjoint() {
+		let mut block = EncodedBlock::new(5, 70);
+		assert_eq!(block.len(), 5);
+		assert_eq!(block.shard_len(), 70);
+
+		for (i, shard) in block.shards_mut().into_iter().enumerate() {
+			assert_eq!(shard.as_ptr() as usize % SHARD_ALIGNMENT, 0);
+			shard.iter_mut().for_each(|byte| *byte = i as u8);
+		}
+		for i in 0..block.len() {
+			assert!(block.shard(i).iter().all(|&byte| byte == i as u8));
+			assert_eq!(block.symbols(i).len(), 35);
+		}
+
+		block.symbols_mut(2)[0] = [0xAB, 0xCD];
+		assert_eq!(&block.shard(2)[..3], &[0xAB, 0xCD, 2]);
+	}
+
+	#[test]
+	fn empty_shards() {
+		let block = EncodedBlock::new(3, 0);
+		assert_eq!(block.shards().count(), 3);
+		assert!(block.shards().all(|shard| shard.is_empty()));
+	}
+
+	fn block_matches_encode<E, B>(encode: E, encode_block: B)
+	where
+		E: Fn(&[u8]) -> Vec<WrappedShard>,
+		B: Fn(&[u8]) -> EncodedBlock,
+	{
+		let payload = &BYTES[..333];
+		let expected = encode(payload);
+		let block = encode_block(payload);
+
+		assert_eq!(block.len(), N_VALIDATORS);
+		for (i, shard) in expected.iter().enumerate() {
+			assert_eq!(block.shard(i), AsRef::<[u8]>::as_ref(shard));
+		}
+
+		let shards: Vec<WrappedShard> = block.into();
+		for (shard, expected) in shards.iter().zip(expected.iter()) {
+			assert_eq!(AsRef::<[u8]>::as_ref(shard), AsRef::<[u8]>::as_ref(expected));
+		}
+	}
+
+	#[test]
+	fn status_quo_block() {
+		block_matches_encode(status_quo::encode, status_quo::encode_block);
+	}
+
+	#[test]
+	fn novel_poly_basis_block() {
+		block_matches_encode(novel_poly_basis::encode, novel_poly_basis::encode_block);
+	}
+}
@@ -0,0 +1,155 @@
+// A common interface to the binary extension fields of the backends.
+//
+// The implementations represent elements differently: `binaryfield` in the polynomial basis,
+// `novel_poly_basis` in the Cantor basis, `status_quo` as GF((2^8)^2). Discrete logarithms with
+// respect to a primitive element do not depend on the representation, so generic code and tests
+// compare fields through `exp` and `log` rather than through the raw element bits.
+
+use std::fmt;
+
+/// A finite field GF(2^bits).
+pub trait GaloisField {
+	type Element: Copy + Eq + fmt::Debug;
+
+	/// Number of bits of an element, the field has `2^bits` elements.
+	fn bits(&self) -> u32;
+
+	/// Number of non-zero elements, `2^bits - 1`.
+	fn order(&self) -> u64 {
+		(1_u64 << self.bits()) - 1
+	}
+
+	/// The element with the bit representation `value`, which must fit into `bits` bits.
+	fn element(&self, value: u32) -> Self::Element;
+
+	/// The bit representation of `a`.
+	fn value(&self, a: Self::Element) -> u32;
+
+	fn zero(&self) -> Self::Element {
+		self.element(0)
+	}
+
+	fn one(&self) -> Self::Element;
+
+	fn add(&self, a: Self::Element, b: Self::Element) -> Self::Element;
+
+	fn mul(&self, a: Self::Element, b: Self::Element) -> Self::Element;
+
+	/// Multiplicative inverse, `a` must not be zero.
+	fn inv(&self, a: Self::Element) -> Self::Element;
+
+	/// `a / b`, `b` must not be zero.
+	fn div(&self, a: Self::Element, b: Self::Element) -> Self::Element {
+		self.mul(a, self.inv(b))
+	}
+
+	/// The primitive element `exp` and `log` refer to.
+	fn generator(&self) -> Self::Element {
+		self.exp(1)
+	}
+
+	/// `generator^n`.
+	fn exp(&self, n: u64) -> Self::Element;
+
+	/// Discrete logarithm to the base `generator`, in `0..order`. `a` must not be zero.
+	fn log(&self, a: Self::Element) -> u64;
+
+	/// `a^n`.
+	fn pow(&self, a: Self::Element, n: u64) -> Self::Element {
+		if n == 0 {
+			self.one()
+		} else if a == self.zero() {
+			self.zero()
+		} else {
+			let order = self.order() as u128;
+			self.exp((self.log(a) as u128 * n as u128 % order) as u64)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::binaryfield::{moduli, BinaryField};
+	use crate::novel_poly_basis::CantorField;
+	use crate::status_quo::Galois16;
+
+	fn samples<F: GaloisField>(field: &F, count: u32) -> impl Iterator<Item = F::Element> + '_ {
+		let mask = field.order() as u32;
+		(1..=count).map(move |i| field.element(i.wrapping_mul(0x9E37_79B9) >> 7 & mask))
+	}
+
+	fn check_field<F: GaloisField>(field: &F, count: u32) {
+		let (zero, one) = (field.zero(), field.one());
+		assert_eq!(field.value(one), 1);
+		assert_eq!(field.exp(0), one);
+		assert_eq!(field.exp(field.order()), one);
+		assert_eq!(field.generator(), field.exp(1));
+
+		for a in samples(field, count) {
+			assert_eq!(field.element(field.value(a)), a);
+			assert_eq!(field.add(a, a), zero);
+			assert_eq!(field.mul(a, zero), zero);
+			assert_eq!(field.mul(a, one), a);
+			assert_eq!(field.pow(a, 0), one);
+			assert_eq!(field.pow(a, 3), field.mul(a, field.mul(a, a)));
+			if a == zero {
+				continue;
+			}
+			assert_eq!(field.exp(field.log(a)), a);
+			assert_eq!(field.mul(a, field.inv(a)), one);
+
+			let b = field.add(a, field.generator());
+			assert_eq!(field.div(field.mul(a, b), a), b);
+			if b != zero {
+				let log = (field.log(a) + field.log(b)) % field.order();
+				assert_eq!(field.log(field.mul(a, b)), log);
+			}
+		}
+	}
+
+	#[test]
+	fn binaryfield() {
+		for pd in [moduli::GF_2_8, moduli::GF_2_10, moduli::GF_2_16] {
+			let field = BinaryField::new(pd).unwrap();
+			assert_eq!(GaloisField::bits(&field), field.height());
+			check_field(&field, 200);
+		}
+		// every logarithm is a baby-step giant-step search without tables
+		check_field(&BinaryField::new(moduli::GF_2_32).unwrap(), 8);
+	}
+
+	#[test]
+	fn novel_poly_basis() {
+		check_field(&CantorField::new(), 200);
+	}
+
+	#[test]
+	fn status_quo() {
+		check_field(&Galois16::new(), 200);
+	}
+
+	/// `log(1 + g^n)`, the Zech logarithm, which only depends on the minimal polynomial of `g`.
+	fn zech_logs<F: GaloisField>(field: &F) -> Vec<Option<u64>> {
+		(0..field.order())
+			.step_by(37)
+			.map(|n| {
+				let sum = field.add(field.one(), field.exp(n));
+				(sum != field.zero()).then(|| field.log(sum))
+			})
+			.collect()
+	}
+
+	#[test]
+	fn cantor_and_polynomial_basis_agree() {
+		// both use x^16 + x^5 + x^3 + x^2 + 1 with x as generator, so they are isomorphic via g -> g
+		let field = BinaryField::new_primitive(moduli::GF_2_16).unwrap();
+		let cantor = CantorField::new();
+		assert_eq!(zech_logs(&field), zech_logs(&cantor));
+
+		let (a, b) = (GaloisField::exp(&field, 1234), cantor.exp(1234));
+		for n in [0, 1, 2, 1000, 65534, 65535, 1 << 40] {
+			assert_eq!(field.log(GaloisField::pow(&field, a, n)), cantor.log(cantor.pow(b, n)));
+		}
+	}
+}
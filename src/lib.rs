@@ -1,22 +1,167 @@
+// Without the default `std` feature only the novel polynomial basis codecs remain, on `alloc`.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+/// The functions every backend offers for the `N_VALIDATORS` shards of which `DATA_SHARDS` carry data.
+///
+/// Requires `encode_into_sub` and `encode_parity_sub` in scope, and unless invoked with `in_place`
+/// also `encode` and `reconstruct` over [`WrappedShard`]s.
+macro_rules! backend_wrappers {
+	(in_place) => {
+		/// Encode `data` into caller provided `shards` instead of allocating them.
+		///
+		/// The first `DATA_SHARDS` receive the zero padded payload, the remaining ones the parity.
+		///
+		/// # Panics
+		///
+		/// If there are not `N_VALIDATORS` shards of `shard_len(data.len(), DATA_SHARDS)` bytes each.
+		pub fn encode_into(data: &[u8], shards: &mut [&mut [u8]]) {
+			encode_into_sub(data, shards, $crate::N_VALIDATORS, $crate::DATA_SHARDS)
+		}
+
+		/// Only compute the parity shards, for data shards which may borrow from the payload directly.
+		///
+		/// # Panics
+		///
+		/// If there are not `DATA_SHARDS` data and `PARITY_SHARDS` parity shards of the same length,
+		/// see [`encode_parity_sub`].
+		pub fn encode_parity(data_shards: &[&[u8]], parity_shards: &mut [&mut [u8]]) {
+			encode_parity_sub(data_shards, parity_shards, $crate::N_VALIDATORS, $crate::DATA_SHARDS)
+		}
+	};
+	() => {
+		backend_wrappers!(in_place);
+
+		/// Encode `data` into a single [`EncodedBlock`] holding all shards.
+		pub fn encode_block(data: &[u8]) -> $crate::EncodedBlock {
+			let shard_len = $crate::shard_len(data.len(), $crate::DATA_SHARDS);
+			let mut block = $crate::EncodedBlock::new($crate::N_VALIDATORS, shard_len);
+			encode_into(data, &mut block.shards_mut()[..]);
+			block
+		}
+
+		/// Streaming variant of [`encode`], writing each shard to its own sink.
+		///
+		/// See [`stream::encode`] for details.
+		pub fn encode_stream<R: std::io::Read, W: std::io::Write>(
+			reader: R,
+			sinks: &mut [W],
+			shard_len: usize,
+		) -> std::io::Result<u64> {
+			$crate::stream::encode(encode, reader, sinks, shard_len)
+		}
+
+		/// Streaming variant of [`reconstruct`], writing the payload to `sink` stripe by stripe.
+		///
+		/// See [`stream::decode`] for details.
+		pub fn reconstruct_stream<R: std::io::Read, W: std::io::Write>(
+			sources: Vec<Option<R>>,
+			sink: W,
+			shard_len: usize,
+			payload_len: u64,
+		) -> std::io::Result<()> {
+			$crate::stream::decode(reconstruct_shards, sources, sink, shard_len, payload_len)
+		}
+
+		/// Incremental reconstruction, accepting one shard at a time.
+		pub fn reconstructor() -> $crate::reconstructor::BackendReconstructor {
+			$crate::reconstructor::Reconstructor::new(encode, reconstruct)
+		}
+	};
+}
+
 mod wrapped_shard;
 pub use wrapped_shard::*;
 
+mod encoded_block;
+pub use encoded_block::*;
+
+#[cfg(feature = "std")]
 pub mod status_quo;
 
 pub mod novel_poly_basis;
 
+#[cfg(feature = "std")]
+pub mod binaryfield;
+
+#[cfg(feature = "std")]
+pub mod additive_fft;
+
+#[cfg(feature = "std")]
+pub mod galois;
+
+#[cfg(feature = "std")]
+pub mod stream;
+
+#[cfg(feature = "std")]
+pub mod reconstructor;
+
+#[cfg(feature = "merkle")]
+pub mod merkle;
+
+#[cfg(all(feature = "std", feature = "merkle"))]
+pub mod polkadot;
+
 // we want one message per validator, so this is the total number of shards that we should own
 // after
-const N_VALIDATORS: usize = 16; //256;
-const DATA_SHARDS: usize = 4; // N_VALIDATORS / 3;
-const PARITY_SHARDS: usize = N_VALIDATORS - DATA_SHARDS;
+pub const N_VALIDATORS: usize = 16; //256;
+pub const DATA_SHARDS: usize = 4; // N_VALIDATORS / 3;
+pub const PARITY_SHARDS: usize = N_VALIDATORS - DATA_SHARDS;
+
+/// Number of bytes per shard required to spread `payload_len` bytes across `k` data shards.
+///
+/// Rounded up to a multiple of 2, since shards consist of whole GF(2^16) symbols.
+pub fn shard_len(payload_len: usize, k: usize) -> usize {
+	let needed = payload_len.div_ceil(k);
+	needed + (needed & 0x01)
+}
+
+/// Copy consecutive chunks of `data` into `shards`, zero padding the remainder.
+pub(crate) fn fill_data_shards(data: &[u8], shards: &mut [&mut [u8]]) {
+	let mut offset = 0;
+	for shard in shards.iter_mut() {
+		let end = core::cmp::min(offset + shard.len(), data.len());
+		let chunk = &data[core::cmp::min(offset, end)..end];
+		shard[..chunk.len()].copy_from_slice(chunk);
+		shard[chunk.len()..].iter_mut().for_each(|byte| *byte = 0);
+		offset += shard.len();
+	}
+}
+
+/// Zero pad `data` into the first `k` of the `n` `shards`, then let `encode_parity` fill the others.
+///
+/// # Panics
+///
+/// If there are not `n` shards of `shard_len` bytes each.
+pub(crate) fn encode_into_with<P>(
+	data: &[u8],
+	shards: &mut [&mut [u8]],
+	n: usize,
+	k: usize,
+	shard_len: usize,
+	encode_parity: P,
+) where
+	P: FnOnce(&[&[u8]], &mut [&mut [u8]]),
+{
+	assert_eq!(shards.len(), n);
+	assert!(shards.iter().all(|shard| shard.len() == shard_len));
+
+	let (data_shards, parity_shards) = shards.split_at_mut(k);
+	fill_data_shards(data, data_shards);
+
+	let data_shards = data_shards.iter().map(|shard| &shard[..]).collect::<alloc::vec::Vec<&[u8]>>();
+	encode_parity(&data_shards[..], parity_shards);
+}
 
 pub const BYTES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/rand_data.bin"));
 
-pub fn roundtrip<E, R>(encode: E, reconstruct: R, payload: &[u8])
+#[cfg(feature = "std")]
+pub fn roundtrip<S, E, R>(encode: E, reconstruct: R, payload: &[u8])
 where
-	E: Fn(&[u8]) -> Vec<WrappedShard>,
-	R: Fn(Vec<Option<WrappedShard>>) -> Option<Vec<u8>>,
+	S: Clone,
+	E: Fn(&[u8]) -> Vec<S>,
+	R: Fn(Vec<Option<S>>) -> Option<Vec<u8>>,
 {
 	// Construct the shards
 	let encoded = encode(payload);
@@ -37,10 +182,10 @@ where
 	let result = reconstruct(shards).expect("reconstruction must work");
 
 	// the result might have trailing zeros
-	assert_eq!(&payload[..], &result[0..payload.len()]);
+	assert_eq!(payload, &result[0..payload.len()]);
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod test {
 	use super::*;
 
@@ -53,4 +198,79 @@ mod test {
 	fn novel_poly_basis_roundtrip() {
 		roundtrip(novel_poly_basis::encode, novel_poly_basis::reconstruct, &BYTES[0..32])
 	}
+
+	#[test]
+	fn novel_poly_basis_f2e8_roundtrip() {
+		use novel_poly_basis::f2e8;
+		roundtrip(f2e8::encode, f2e8::reconstruct, &BYTES[0..32])
+	}
+
+	#[test]
+	fn novel_poly_basis_bitsliced_roundtrip() {
+		use novel_poly_basis::bitsliced;
+		roundtrip(bitsliced::encode, bitsliced::reconstruct, &BYTES[0..32])
+	}
+
+	#[test]
+	fn additive_fft_roundtrip() {
+		roundtrip(additive_fft::encode, additive_fft::reconstruct, &BYTES[0..32])
+	}
+
+	fn in_place<S, E, I, P>(encode: E, encode_into: I, encode_parity: P)
+	where
+		S: AsRef<[u8]>,
+		E: Fn(&[u8]) -> Vec<S>,
+		I: Fn(&[u8], &mut [&mut [u8]]),
+		P: Fn(&[&[u8]], &mut [&mut [u8]]),
+	{
+		let payload = &BYTES[..1000];
+		let expected = encode(payload);
+		let shard_len = shard_len(payload.len(), DATA_SHARDS);
+
+		// dirty buffers, to make sure the padding is zeroed
+		let mut buffers = vec![vec![0xFFu8; shard_len]; N_VALIDATORS];
+		let mut views = buffers.iter_mut().map(|buf| &mut buf[..]).collect::<Vec<_>>();
+		encode_into(payload, &mut views[..]);
+		for (buf, shard) in buffers.iter().zip(expected.iter()) {
+			assert_eq!(&buf[..], shard.as_ref());
+		}
+
+		// borrow the data shards from the payload
+		let payload = &BYTES[..(DATA_SHARDS * shard_len)];
+		let expected = encode(payload);
+		let data_shards = payload.chunks(shard_len).collect::<Vec<_>>();
+		let mut parity = vec![vec![0u8; shard_len]; PARITY_SHARDS];
+		let mut views = parity.iter_mut().map(|buf| &mut buf[..]).collect::<Vec<_>>();
+		encode_parity(&data_shards[..], &mut views[..]);
+		for (buf, shard) in parity.iter().zip(expected[DATA_SHARDS..].iter()) {
+			assert_eq!(&buf[..], shard.as_ref());
+		}
+	}
+
+	#[test]
+	fn status_quo_in_place() {
+		in_place(status_quo::encode, status_quo::encode_into, status_quo::encode_parity)
+	}
+
+	#[test]
+	fn novel_poly_basis_in_place() {
+		in_place(novel_poly_basis::encode, novel_poly_basis::encode_into, novel_poly_basis::encode_parity)
+	}
+
+	#[test]
+	fn novel_poly_basis_f2e8_in_place() {
+		use novel_poly_basis::f2e8;
+		in_place(f2e8::encode, f2e8::encode_into, f2e8::encode_parity)
+	}
+
+	#[test]
+	fn novel_poly_basis_bitsliced_in_place() {
+		use novel_poly_basis::bitsliced;
+		in_place(bitsliced::encode, bitsliced::encode_into, bitsliced::encode_parity)
+	}
+
+	#[test]
+	fn additive_fft_in_place() {
+		in_place(additive_fft::encode, additive_fft::encode_into, additive_fft::encode_parity)
+	}
 }
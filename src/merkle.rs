@@ -0,0 +1,204 @@
+// Merkle commitment over the encoded shards, to authenticate shards against a block.
+//
+// The leaves are the SHA-256 hashes of the shards, padded with zero hashes to the next power
+// of two, so a proof is one sibling hash per level and verification needs no shard count.
+// Leaves and inner nodes are hashed with distinct prefixes, so no shard collides with a node.
+
+use super::*;
+use alloc::vec;
+use alloc::vec::Vec;
+use sha2::{Digest, Sha256};
+
+pub type Hash = [u8; 32];
+
+const LEAF: u8 = 0x00;
+const NODE: u8 = 0x01;
+
+/// The leaf of `shard`.
+pub fn hash_leaf(shard: &[u8]) -> Hash {
+	Sha256::new().chain([LEAF]).chain(shard).finalize().into()
+}
+
+fn hash_node(left: &Hash, right: &Hash) -> Hash {
+	Sha256::new().chain([NODE]).chain(left).chain(right).finalize().into()
+}
+
+/// The path from a leaf to the root, sibling hashes ordered from the leaves up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proof {
+	siblings: Vec<Hash>,
+}
+
+impl Proof {
+	pub fn siblings(&self) -> &[Hash] {
+		&self.siblings[..]
+	}
+
+	/// Size of the proof in bytes.
+	pub fn encoded_len(&self) -> usize {
+		self.siblings.len() * 32
+	}
+}
+
+/// All levels of the tree over a set of shards.
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+	// leaves first, the last level holds the root only
+	levels: Vec<Vec<Hash>>,
+	n: usize,
+}
+
+impl MerkleTree {
+	/// Build the tree over `shards`, of which there must be at least one.
+	pub fn new<S: AsRef<[u8]>>(shards: &[S]) -> Self {
+		assert!(!shards.is_empty(), "Cannot commit to zero shards");
+		let n = shards.len();
+		let mut leaves = shards.iter().map(|shard| hash_leaf(shard.as_ref())).collect::<Vec<_>>();
+		leaves.resize(n.next_power_of_two(), [0u8; 32]);
+
+		let mut levels = vec![leaves];
+		while levels.last().expect("Starts with the leaves. qed").len() > 1 {
+			let level = levels.last().expect("Starts with the leaves. qed");
+			let next = level.chunks(2).map(|pair| hash_node(&pair[0], &pair[1])).collect();
+			levels.push(next);
+		}
+		Self { levels, n }
+	}
+
+	/// Number of shards committed to.
+	pub fn len(&self) -> usize {
+		self.n
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.n == 0
+	}
+
+	pub fn root(&self) -> Hash {
+		self.levels.last().expect("Starts with the leaves. qed")[0]
+	}
+
+	/// The inclusion proof of the shard with `index`.
+	pub fn proof(&self, index: usize) -> Proof {
+		assert!(index < self.n, "Shard index out of bounds");
+		let levels = &self.levels[..(self.levels.len() - 1)];
+		let siblings = levels.iter().enumerate().map(|(depth, level)| level[(index >> depth) ^ 1]).collect();
+		Proof { siblings }
+	}
+}
+
+/// Commit to `shards`, yielding the root and one proof per shard.
+pub fn commit<S: AsRef<[u8]>>(shards: &[S]) -> (Hash, Vec<Proof>) {
+	let tree = MerkleTree::new(shards);
+	let proofs = (0..tree.len()).map(|index| tree.proof(index)).collect();
+	(tree.root(), proofs)
+}
+
+/// The root of the tree with `leaf` at `index`, `None` if the index exceeds the depth of `proof`.
+pub fn root_from_leaf(index: usize, leaf: Hash, proof: &Proof) -> Option<Hash> {
+	if index >> proof.siblings.len() != 0 {
+		return None;
+	}
+	let root = proof.siblings.iter().enumerate().fold(leaf, |hash, (depth, sibling)| {
+		if index >> depth & 1 == 0 {
+			hash_node(&hash, sibling)
+		} else {
+			hash_node(sibling, &hash)
+		}
+	});
+	Some(root)
+}
+
+/// Whether `shard` is the one at `index` of the shards committed to by `root`.
+pub fn verify(root: &Hash, index: usize, shard: &[u8], proof: &Proof) -> bool {
+	root_from_leaf(index, hash_leaf(shard), proof).as_ref() == Some(root)
+}
+
+/// Replace every shard failing verification against `root` by an erasure.
+///
+/// Yields the shards for `reconstruct` and the indices of the rejected ones.
+pub fn verify_shards(
+	root: &Hash,
+	received_shards: Vec<Option<(WrappedShard, Proof)>>,
+) -> (Vec<Option<WrappedShard>>, Vec<usize>) {
+	let mut rejected = vec![];
+	let shards = received_shards
+		.into_iter()
+		.enumerate()
+		.map(|(index, received)| {
+			let (shard, proof) = received?;
+			if verify(root, index, AsRef::<[u8]>::as_ref(&shard), &proof) {
+				Some(shard)
+			} else {
+				rejected.push(index);
+				None
+			}
+		})
+		.collect();
+	(shards, rejected)
+}
+
+/// Drop the shards failing verification against `root`, then `reconstruct` from the remaining ones.
+pub fn reconstruct_verified<R>(
+	root: &Hash,
+	received_shards: Vec<Option<(WrappedShard, Proof)>>,
+	reconstruct: R,
+) -> Option<Vec<u8>>
+where
+	R: FnOnce(Vec<Option<WrappedShard>>) -> Option<Vec<u8>>,
+{
+	let (shards, _) = verify_shards(root, received_shards);
+	reconstruct(shards)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn proofs_verify() {
+		for n in [1, 2, 5, 16] {
+			let shards = (0..n).map(|i| WrappedShard::new(BYTES[(i * 10)..(i * 10 + 10)].to_vec())).collect::<Vec<_>>();
+			let (root, proofs) = commit(&shards[..]);
+			assert_eq!(proofs.len(), n);
+			for (index, (shard, proof)) in shards.iter().zip(&proofs).enumerate() {
+				assert_eq!(proof.siblings().len(), n.next_power_of_two().trailing_zeros() as usize);
+				assert!(verify(&root, index, shard.as_ref(), proof));
+				// neither another position nor altered contents
+				assert!(!verify(&root, index ^ 1, shard.as_ref(), proof));
+				assert!(!verify(&root, index + n.next_power_of_two(), shard.as_ref(), proof));
+				assert!(!verify(&root, index, &BYTES[1..11], proof));
+			}
+		}
+	}
+
+	#[test]
+	fn rejects_tampered_shards() {
+		let payload = &BYTES[..1000];
+		let encoded = novel_poly_basis::encode(payload);
+		let (root, proofs) = commit(&encoded[..]);
+
+		let mut received = encoded.into_iter().zip(proofs).map(Some).collect::<Vec<_>>();
+		// erase all but 5 shards, tamper with two of the remaining
+		for index in [0, 1, 2, 4, 6, 7, 9, 10, 11, 14, 15] {
+			received[index] = None;
+		}
+		AsMut::<[u8]>::as_mut(&mut received[3].as_mut().unwrap().0)[0] ^= 1;
+		let swapped = received[12].as_ref().unwrap().1.clone();
+		received[8].as_mut().unwrap().1 = swapped;
+
+		let (shards, rejected) = verify_shards(&root, received.clone());
+		assert_eq!(rejected, vec![3, 8]);
+		assert_eq!(shards.iter().flatten().count(), 3);
+
+		// replacing the tampered shards by valid ones suffices
+		let encoded = novel_poly_basis::encode(payload);
+		let (_, proofs) = commit(&encoded[..]);
+		received[3] = Some((encoded[3].clone(), proofs[3].clone()));
+		received[8] = Some((encoded[8].clone(), proofs[8].clone()));
+		received[0] = Some((encoded[0].clone(), proofs[0].clone()));
+		assert!(reconstruct_verified(&[0u8; 32], received.clone(), novel_poly_basis::reconstruct).is_none());
+		let result = reconstruct_verified(&root, received, novel_poly_basis::reconstruct).unwrap();
+		assert_eq!(&result[..payload.len()], payload);
+	}
+}
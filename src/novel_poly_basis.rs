@@ -4,12 +4,33 @@
 //
 // Lin, Han and Chung, "Novel Polynomial Basis and Its Application to Reed-Solomon Erasure Codes," FOCS14.
 // (http://arxiv.org/abs/1404.3458)
+//
+// The tables live in memory of the caller, see `Tables::new_in`, so the codec itself only needs
+// `alloc`. With `std`, the free functions share one set of tables computed on first use.
 
-#![allow(dead_code)]
+// keep the index heavy loops close to the C reference
+#![allow(clippy::precedence, clippy::needless_range_loop)]
 
 use super::*;
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+pub mod cantor;
+
+pub mod f2e8;
+
+#[cfg(feature = "std")]
+pub mod bitsliced;
 
-use std::slice::from_raw_parts;
+#[cfg(feature = "std")]
+pub mod transform;
+
+#[cfg(feature = "std")]
+pub mod gao;
+
+#[cfg(feature = "std")]
+pub mod guruswami_sudan;
 
 type GFSymbol = u16;
 
@@ -25,43 +46,253 @@ const FIELD_SIZE: usize = 1_usize << FIELD_BITS;
 
 const MODULO: GFSymbol = (FIELD_SIZE - 1) as GFSymbol;
 
-static mut LOG_TABLE: [GFSymbol; FIELD_SIZE] = [0_u16; FIELD_SIZE];
-static mut EXP_TABLE: [GFSymbol; FIELD_SIZE] = [0_u16; FIELD_SIZE];
+/// Which tables to keep, trading memory for work per reconstruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+	/// All tables.
+	Full,
+	/// Without the transformed logarithms, saving 128 KiB. Reconstruction then evaluates the error
+	/// locator polynomial directly in O(n^2) for `n` shards, without further allocation, instead of
+	/// two Walsh transforms over all 2^16 symbols. That is cheaper for codes of up to a few hundred
+	/// shards and slower beyond. The log/exp tables, 256 KiB, stay in both layouts.
+	Compact,
+}
+
+/// The log/exp tables and decoding factors of the codec, borrowed from a buffer.
+///
+/// For all codes of up to 2^16 shards the tables take 576 KiB. Codes of up to `max_n` shards
+/// only need `1.5 * max_n` factors, so with [`Layout::Compact`] about 256 KiB remain.
+#[derive(Clone, Copy)]
+pub struct Tables<'a> {
+	log: &'a [GFSymbol],
+	exp: &'a [GFSymbol],
 
-//-----Used in decoding procedure-------
-//twisted factors used in FFT
-static mut SKEW_FACTOR: [GFSymbol; MODULO as usize] = [0_u16; MODULO as usize];
+	//-----Used in decoding procedure-------
+	//twisted factors used in FFT
+	skew: &'a [GFSymbol],
 
-//factors used in formal derivative
-static mut B: [GFSymbol; FIELD_SIZE >> 1] = [0_u16; FIELD_SIZE >> 1];
+	//factors used in formal derivative
+	b: &'a [GFSymbol],
 
-//factors used in the evaluation of the error locator polynomial
-static mut LOG_WALSH: [GFSymbol; FIELD_SIZE] = [0_u16; FIELD_SIZE];
+	//factors used in the evaluation of the error locator polynomial, unless compact
+	log_walsh: Option<&'a [GFSymbol]>,
 
-//return a*EXP_TABLE[b] over GF(2^r)
-fn mul_table(a: GFSymbol, b: GFSymbol) -> GFSymbol {
-	if a != 0_u16 {
-		unsafe {
-			let offset = (LOG_TABLE[a as usize] as u32 + b as u32 & MODULO as u32)
-				+ (LOG_TABLE[a as usize] as u32 + b as u32 >> FIELD_BITS);
-			EXP_TABLE[offset as usize]
+	//table free multiplication in the butterflies, if selected
+	#[cfg(feature = "std")]
+	clmul: Option<&'static Clmul>,
+}
+
+impl core::fmt::Debug for Tables<'_> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Tables")
+			.field("max_n", &self.max_n())
+			.field("compact", &self.log_walsh.is_none())
+			.field("multiplier", &self.multiplier())
+			.finish()
+	}
+}
+
+impl<'a> Tables<'a> {
+	/// Number of symbols [`Tables::new_in`] requires for codes of up to `max_n` shards.
+	pub const fn buffer_len(max_n: usize, layout: Layout) -> usize {
+		let log_walsh = match layout {
+			Layout::Full => FIELD_SIZE,
+			Layout::Compact => 0,
+		};
+		2 * FIELD_SIZE + (max_n - 1) + (max_n >> 1) + log_walsh
+	}
+
+	/// Compute the tables for codes of up to `max_n` shards into `buffer`, without allocating.
+	///
+	/// # Panics
+	///
+	/// If `max_n` is not a power of two between 2 and 2^16, or the buffer is shorter
+	/// than [`Tables::buffer_len`].
+	pub fn new_in(buffer: &'a mut [u16], max_n: usize, layout: Layout) -> Self {
+		assert!(is_power_of_2(max_n) && (2..=FIELD_SIZE).contains(&max_n), "Codes have 2^m shards, at most 2^16");
+		assert!(buffer.len() >= Self::buffer_len(max_n, layout), "Buffer must hold all tables");
+
+		let (log, buffer) = buffer.split_at_mut(FIELD_SIZE);
+		let (exp, buffer) = buffer.split_at_mut(FIELD_SIZE);
+		let (skew, buffer) = buffer.split_at_mut(max_n - 1);
+		let (b, buffer) = buffer.split_at_mut(max_n >> 1);
+		init(log, exp);
+		init_dec(log, exp, skew, b);
+
+		let log_walsh = match layout {
+			Layout::Full => {
+				let log_walsh = &mut buffer[..FIELD_SIZE];
+				transform_log(log, log_walsh);
+				Some(&*log_walsh)
+			}
+			Layout::Compact => None,
+		};
+		Self {
+			log,
+			exp,
+			skew,
+			b,
+			log_walsh,
+			#[cfg(feature = "std")]
+			clmul: None,
+		}
+	}
+
+	/// Multiply with `multiplier` in the FFT butterflies, which are most of the work.
+	///
+	/// Both [`Tables::new_in`] and the shared [`tables`] start with [`Multiplier::Table`].
+	pub fn with_multiplier(self, multiplier: Multiplier) -> Self {
+		match multiplier {
+			Multiplier::Table => Self {
+				#[cfg(feature = "std")]
+				clmul: None,
+				..self
+			},
+			#[cfg(feature = "std")]
+			Multiplier::Clmul => Self { clmul: Some(Clmul::get()), ..self },
+		}
+	}
+
+	pub fn multiplier(&self) -> Multiplier {
+		#[cfg(feature = "std")]
+		if self.clmul.is_some() {
+			return Multiplier::Clmul;
 		}
-	} else {
-		0_u16
+		Multiplier::Table
+	}
+
+	/// The largest number of shards of the supported codes.
+	pub fn max_n(&self) -> usize {
+		self.b.len() << 1
 	}
+
+	//return a*EXP_TABLE[b] over GF(2^r)
+	fn mul_table(&self, a: GFSymbol, b: GFSymbol) -> GFSymbol {
+		if a != 0_u16 {
+			let offset = (self.log[a as usize] as u32 + b as u32 & MODULO as u32)
+				+ (self.log[a as usize] as u32 + b as u32 >> FIELD_BITS);
+			self.exp[offset as usize]
+		} else {
+			0_u16
+		}
+	}
+}
+
+/// The tables for all codes, computed on first use and shared by the free functions.
+#[cfg(feature = "std")]
+pub fn tables() -> Tables<'static> {
+	static TABLES: std::sync::OnceLock<Tables<'static>> = std::sync::OnceLock::new();
+	*TABLES.get_or_init(|| {
+		let buffer = vec![0 as GFSymbol; Tables::buffer_len(FIELD_SIZE, Layout::Full)];
+		Tables::new_in(Box::leak(buffer.into_boxed_slice()), FIELD_SIZE, Layout::Full)
+	})
+}
+
+/// Change between the Cantor basis of the tables and the polynomial basis of [`binaryfield`].
+#[cfg(feature = "std")]
+struct BasisChange {
+	// the Cantor basis in the polynomial basis and vice versa
+	from_cantor: Vec<u32>,
+	to_cantor: Vec<u32>,
+}
+
+#[cfg(feature = "std")]
+fn basis_change() -> &'static BasisChange {
+	static BASIS_CHANGE: std::sync::OnceLock<BasisChange> = std::sync::OnceLock::new();
+	BASIS_CHANGE.get_or_init(|| {
+		let from_cantor = BASE.map(u32::from).to_vec();
+		let to_cantor = cantor::coordinates(&from_cantor[..]).expect("A Cantor basis is a basis. qed");
+		BasisChange { from_cantor, to_cantor }
+	})
 }
 
-const fn log2(mut x: usize) -> usize {
-	let mut o: usize = 0;
-	while x > 1 {
-		x >>= 1;
-		o += 1;
+/// How the FFT butterflies multiply, see [`Tables::with_multiplier`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Multiplier {
+	/// Log/exp table lookups.
+	Table,
+	/// Carry-less products with Barrett reduction, without touching the 256 KiB log/exp tables.
+	#[cfg(feature = "std")]
+	Clmul,
+}
+
+impl Multiplier {
+	/// [`Multiplier::Clmul`] if the CPU has PCLMULQDQ, the portable carry-less product loses to the tables.
+	///
+	/// Even for 2^16 shards the log/exp tables keep up with PCLMULQDQ while they stay in cache, see the
+	/// `multiplier` benchmark, so the shared [`tables`] stick to [`Multiplier::Table`]. Select this where
+	/// other work competes for the cache.
+	pub fn detect() -> Self {
+		#[cfg(feature = "std")]
+		if Clmul::get().barrett.uses_pclmul() {
+			return Self::Clmul;
+		}
+		Self::Table
+	}
+}
+
+/// Table free multiplication by `x^b` for the butterflies.
+///
+/// The transforms run in the polynomial basis, so only their input and output change basis,
+/// by byte wise lookups in 2 KiB of tables. The constant factor changes once per butterfly run.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct Clmul {
+	barrett: binaryfield::Barrett,
+	// images of the low and high byte of a symbol in the other basis
+	from_cantor: [[GFSymbol; 256]; 2],
+	to_cantor: [[GFSymbol; 256]; 2],
+}
+
+#[cfg(feature = "std")]
+impl Clmul {
+	fn new(barrett: binaryfield::Barrett) -> Self {
+		let basis_change = basis_change();
+		let byte_tables = |images: &[u32]| {
+			let mut tables = [[0; 256]; 2];
+			for (half, table) in tables.iter_mut().enumerate() {
+				for (byte, image) in table.iter_mut().enumerate() {
+					*image = cantor::change_basis((byte << (8 * half)) as u32, images) as GFSymbol;
+				}
+			}
+			tables
+		};
+		Self {
+			barrett,
+			from_cantor: byte_tables(&basis_change.from_cantor[..]),
+			to_cantor: byte_tables(&basis_change.to_cantor[..]),
+		}
+	}
+
+	fn get() -> &'static Self {
+		static CLMUL: std::sync::OnceLock<Clmul> = std::sync::OnceLock::new();
+		CLMUL.get_or_init(|| {
+			let modulus = 1 << FIELD_BITS | GENERATOR as u64;
+			Self::new(binaryfield::Barrett::new(modulus).expect("The modulus has degree FIELD_BITS. qed"))
+		})
+	}
+
+	#[inline(always)]
+	fn change(tables: &[[GFSymbol; 256]; 2], a: GFSymbol) -> GFSymbol {
+		tables[0][(a & 0xFF) as usize] ^ tables[1][(a >> 8) as usize]
+	}
+
+	fn change_from_cantor(&self, data: &mut [GFSymbol]) {
+		data.iter_mut().for_each(|a| *a = Self::change(&self.from_cantor, *a));
+	}
+
+	fn change_to_cantor(&self, data: &mut [GFSymbol]) {
+		data.iter_mut().for_each(|a| *a = Self::change(&self.to_cantor, *a));
+	}
+
+	/// The factor `x^b` of `mul_table(_, b)`, in the polynomial basis.
+	fn factor(&self, tables: &Tables<'_>, b: GFSymbol) -> u32 {
+		Self::change(&self.from_cantor, tables.exp[b as usize]) as u32
 	}
-	o
 }
 
 const fn is_power_of_2(x: usize) -> bool {
-	return x > 0_usize && x & (x - 1) == 0;
+	x > 0_usize && x & (x - 1) == 0
 }
 
 //fast Walsh–Hadamard transform over modulo mod
@@ -100,56 +331,222 @@ fn formal_derivative(cos: &mut [GFSymbol], size: usize) {
 	}
 }
 
-//IFFT in the proposed basis
-fn inverse_fft_in_novel_poly_basis(data: &mut [GFSymbol], size: usize, index: usize) {
-	let mut depart_no = 1_usize;
-	while depart_no < size {
-		let mut j = depart_no;
-		while j < size {
-			for i in (j - depart_no)..j {
-				data[i + depart_no] ^= data[i];
+impl Tables<'_> {
+	//dst[i] ^= src[i]*EXP_TABLE[skew], the butterfly of both transforms
+	fn mul_add(&self, dst: &mut [GFSymbol], src: &[GFSymbol], skew: GFSymbol) {
+		#[cfg(feature = "std")]
+		if let Some(clmul) = self.clmul {
+			clmul.barrett.mul_add_slice(dst, src, clmul.factor(self, skew));
+			return;
+		}
+		dst.iter_mut().zip(src).for_each(|(dst, &src)| *dst ^= self.mul_table(src, skew));
+	}
+
+	// apply `transform` to the symbols in the basis `mul_add` expects, the additions do not care
+	fn in_mul_basis(&self, data: &mut [GFSymbol], transform: impl FnOnce(&mut [GFSymbol])) {
+		#[cfg(feature = "std")]
+		if let Some(clmul) = self.clmul {
+			clmul.change_from_cantor(data);
+			transform(data);
+			clmul.change_to_cantor(data);
+			return;
+		}
+		transform(data)
+	}
+
+	//IFFT in the proposed basis
+	fn inverse_fft_in_novel_poly_basis(&self, data: &mut [GFSymbol], size: usize, index: usize) {
+		self.in_mul_basis(&mut data[..size], |data| {
+			let mut depart_no = 1_usize;
+			while depart_no < size {
+				let mut j = depart_no;
+				while j < size {
+					for i in (j - depart_no)..j {
+						data[i + depart_no] ^= data[i];
+					}
+
+					let skew = self.skew[j + index - 1];
+					if skew != MODULO {
+						let (low, high) = data[(j - depart_no)..(j + depart_no)].split_at_mut(depart_no);
+						self.mul_add(low, high, skew);
+					}
+
+					j += depart_no << 1;
+				}
+				depart_no <<= 1;
 			}
+		})
+	}
 
-			let skew = unsafe { SKEW_FACTOR[j + index - 1] };
-			if skew != MODULO {
-				for i in (j - depart_no)..j {
-					data[i] ^= mul_table(data[i + depart_no], skew);
+	//FFT in the proposed basis
+	fn fft_in_novel_poly_basis(&self, data: &mut [GFSymbol], size: usize, index: usize) {
+		self.in_mul_basis(&mut data[..size], |data| {
+			let mut depart_no = size >> 1_usize;
+			while depart_no > 0 {
+				let mut j = depart_no;
+				while j < size {
+					let skew = self.skew[j + index - 1];
+					if skew != MODULO {
+						let (low, high) = data[(j - depart_no)..(j + depart_no)].split_at_mut(depart_no);
+						self.mul_add(low, high, skew);
+					}
+					for i in (j - depart_no)..j {
+						data[i + depart_no] ^= data[i];
+					}
+					j += depart_no << 1;
 				}
+				depart_no >>= 1;
 			}
+		})
+	}
+
+	// Encoding alg for k/n < 0.5: message is a power of two
+	fn encode_low(&self, data: &[GFSymbol], k: usize, codeword: &mut [GFSymbol], n: usize) {
+		assert!(k + k <= n);
+		assert_eq!(codeword.len(), n);
+		assert_eq!(data.len(), n);
+
+		assert!(is_power_of_2(n));
+		assert!(is_power_of_2(k));
+
+		// k | n is guaranteed
+		assert_eq!((n / k) * k, n);
+
+		// move the data to the codeword
+		mem_cpy(&mut codeword[0..], &data[0..]);
+
+		// split after the first k
+		let (codeword_first_k, codeword_skip_first_k) = codeword.split_at_mut(k);
 
-			j += depart_no << 1;
+		self.inverse_fft_in_novel_poly_basis(codeword_first_k, k, 0);
+
+		// the first codeword is now the basis for the remaining transforms
+		// denoted `M_topdash`
+
+		for shift in (k..n).step_by(k) {
+			let codeword_at_shift = &mut codeword_skip_first_k[(shift - k)..shift];
+			// copy `M_topdash` to the position we are currently at, the n transform
+			mem_cpy(codeword_at_shift, codeword_first_k);
+			self.fft_in_novel_poly_basis(codeword_at_shift, k, shift);
 		}
-		depart_no <<= 1;
+
+		// restore `M` from the derived ones
+		mem_cpy(&mut codeword[0..k], &data[0..k]);
 	}
-}
 
-//FFT in the proposed basis
-fn fft_in_novel_poly_basis(data: &mut [GFSymbol], size: usize, index: usize) {
-	let mut depart_no = size >> 1_usize;
-	while depart_no > 0 {
-		let mut j = depart_no;
-		while j < size {
-			let skew = unsafe { SKEW_FACTOR[j + index - 1] };
-			if skew != MODULO {
-				for i in (j - depart_no)..j {
-					data[i] ^= mul_table(data[i + depart_no], skew);
-				}
+	//data: message array. parity: parity array. mem: buffer(size>= n-k)
+	//Encoding alg for k/n>0.5: parity is a power of two.
+	// kept from the C reference, the shard layout always has k <= n / 2
+	#[allow(dead_code)]
+	fn encode_high(&self, data: &[GFSymbol], k: usize, parity: &mut [GFSymbol], mem: &mut [GFSymbol], n: usize) {
+		let t: usize = n - k;
+
+		mem_zero(&mut parity[0..t]);
+
+		let mut i = t;
+		while i < n {
+			mem_cpy(&mut mem[..t], &data[(i - t)..t]);
+
+			self.inverse_fft_in_novel_poly_basis(mem, t, i);
+			for j in 0..t {
+				parity[j] ^= mem[j];
 			}
-			for i in (j - depart_no)..j {
-				data[i + depart_no] ^= data[i];
+			i += t;
+		}
+		self.fft_in_novel_poly_basis(parity, t, 0);
+	}
+
+	// Compute the evaluations of the error locator polynomial
+	// `fn decode_init`
+	// since this has only to be called once per reconstruction
+	fn eval_error_polynomial(&self, erasure: &[bool], log_walsh2: &mut [GFSymbol], n: usize) {
+		let log_walsh = match self.log_walsh {
+			Some(log_walsh) => log_walsh,
+			None => return self.convolve_error_polynomial(erasure, log_walsh2),
+		};
+
+		let z = core::cmp::min(n, erasure.len());
+		for i in 0..z {
+			log_walsh2[i] = erasure[i] as GFSymbol;
+		}
+		for item in log_walsh2.iter_mut().skip(z) {
+			*item = 0 as GFSymbol;
+		}
+		walsh(log_walsh2, FIELD_SIZE);
+		for i in 0..n {
+			let tmp = log_walsh2[i] as u32 * log_walsh[i] as u32;
+			log_walsh2[i] = (tmp % MODULO as u32) as GFSymbol;
+		}
+		walsh(log_walsh2, FIELD_SIZE);
+		for i in 0..z {
+			if erasure[i] {
+				log_walsh2[i] = MODULO - log_walsh2[i];
 			}
-			j += depart_no << 1;
 		}
-		depart_no >>= 1;
+	}
+
+	// Without LOG_WALSH, evaluate what the two Walsh transforms above yield directly: the XOR
+	// convolution of the erasures with the logarithms, as 2^16 = 1 modulo MODULO.
+	// Only the `erasure.len()` positions `decode_main` reads are written, in O(n^2).
+	fn convolve_error_polynomial(&self, erasure: &[bool], log_walsh2: &mut [GFSymbol]) {
+		let n = erasure.len();
+		for (i, item) in log_walsh2[..n].iter_mut().enumerate() {
+			let sum = erasure
+				.iter()
+				.enumerate()
+				.filter(|(j, erased)| **erased && i != *j)
+				.map(|(j, _)| self.log[i ^ j] as u64)
+				.sum::<u64>();
+			let sum = (sum % MODULO as u64) as GFSymbol;
+			*item = if erasure[i] { MODULO - sum } else { sum };
+		}
+	}
+
+	fn decode_main(&self, codeword: &mut [GFSymbol], k: usize, erasure: &[bool], log_walsh2: &[GFSymbol], n: usize) {
+		assert!(codeword.len() >= k);
+		assert_eq!(codeword.len(), n);
+		assert!(erasure.len() >= k);
+		assert_eq!(erasure.len(), n);
+
+		// technically we only need to recover
+		// the first `k` instead of all `n` which
+		// would include parity chunks.
+		let recover_up_to = n;
+
+		for i in 0..n {
+			codeword[i] = if erasure[i] { 0_u16 } else { self.mul_table(codeword[i], log_walsh2[i]) };
+		}
+		self.inverse_fft_in_novel_poly_basis(codeword, n, 0);
+
+		//formal derivative
+		for i in (0..n).step_by(2) {
+			let b = MODULO - self.b[i >> 1];
+			codeword[i] = self.mul_table(codeword[i], b);
+			codeword[i + 1] = self.mul_table(codeword[i + 1], b);
+		}
+
+		formal_derivative(codeword, n);
+
+		for i in (0..n).step_by(2) {
+			let b = self.b[i >> 1];
+			codeword[i] = self.mul_table(codeword[i], b);
+			codeword[i + 1] = self.mul_table(codeword[i + 1], b);
+		}
+
+		self.fft_in_novel_poly_basis(codeword, n, 0);
+
+		for i in 0..recover_up_to {
+			codeword[i] = if erasure[i] { self.mul_table(codeword[i], log_walsh2[i]) } else { 0_u16 };
+		}
 	}
 }
 
 //initialize LOG_TABLE[], EXP_TABLE[]
-unsafe fn init() {
+fn init(log_table: &mut [GFSymbol], exp_table: &mut [GFSymbol]) {
 	let mas: GFSymbol = (1 << FIELD_BITS - 1) - 1;
 	let mut state: usize = 1;
 	for i in 0_usize..(MODULO as usize) {
-		EXP_TABLE[state] = i as GFSymbol;
+		exp_table[state] = i as GFSymbol;
 		if (state >> FIELD_BITS - 1) != 0 {
 			state &= mas as usize;
 			state = state << 1_usize ^ GENERATOR as usize;
@@ -157,56 +554,69 @@ unsafe fn init() {
 			state <<= 1;
 		}
 	}
-	EXP_TABLE[0] = MODULO;
+	exp_table[0] = MODULO;
 
-	LOG_TABLE[0] = 0;
+	log_table[0] = 0;
 	for i in 0..FIELD_BITS {
 		for j in 0..(1 << i) {
-			LOG_TABLE[j + (1 << i)] = LOG_TABLE[j] ^ BASE[i];
+			log_table[j + (1 << i)] = log_table[j] ^ BASE[i];
 		}
 	}
 	for i in 0..FIELD_SIZE {
-		LOG_TABLE[i] = EXP_TABLE[LOG_TABLE[i] as usize];
+		log_table[i] = exp_table[log_table[i] as usize];
 	}
 
 	for i in 0..FIELD_SIZE {
-		EXP_TABLE[LOG_TABLE[i] as usize] = i as GFSymbol;
+		exp_table[log_table[i] as usize] = i as GFSymbol;
 	}
-	EXP_TABLE[MODULO as usize] = EXP_TABLE[0];
+	exp_table[MODULO as usize] = exp_table[0];
 }
 
-//initialize SKEW_FACTOR[], B[], LOG_WALSH[]
-unsafe fn init_dec() {
+//initialize SKEW_FACTOR[], B[], only the leading entries the slices have room for
+fn init_dec(log_table: &[GFSymbol], exp_table: &[GFSymbol], skew_factor: &mut [GFSymbol], b_factor: &mut [GFSymbol]) {
+	let tables = Tables {
+		log: log_table,
+		exp: exp_table,
+		skew: &[],
+		b: &[],
+		log_walsh: None,
+		#[cfg(feature = "std")]
+		clmul: None,
+	};
 	let mut base: [GFSymbol; FIELD_BITS - 1] = Default::default();
 
 	for i in 1..FIELD_BITS {
 		base[i - 1] = 1 << i;
 	}
 
+	// the caller's buffer is not necessarily zeroed
+	mem_zero(skew_factor);
 	for m in 0..(FIELD_BITS - 1) {
 		let step = 1 << (m + 1);
-		SKEW_FACTOR[(1 << m) - 1] = 0;
+		if let Some(skew) = skew_factor.get_mut((1 << m) - 1) {
+			*skew = 0;
+		}
 		for i in m..(FIELD_BITS - 1) {
 			let s = 1 << (i + 1);
 
 			let mut j = (1 << m) - 1;
-			while j < s {
-				SKEW_FACTOR[j + s] = SKEW_FACTOR[j] ^ base[i];
+			while j < s && j + s < skew_factor.len() {
+				skew_factor[j + s] = skew_factor[j] ^ base[i];
 				j += step;
 			}
 		}
 
-		let idx = mul_table(base[m], LOG_TABLE[(base[m] ^ 1_u16) as usize]);
-		base[m] = MODULO - LOG_TABLE[idx as usize];
+		let idx = tables.mul_table(base[m], log_table[(base[m] ^ 1_u16) as usize]);
+		base[m] = MODULO - log_table[idx as usize];
 
 		for i in (m + 1)..(FIELD_BITS - 1) {
-			let b = LOG_TABLE[(base[i] as u16 ^ 1_u16) as usize] as u32 + base[m] as u32;
+			let b = log_table[(base[i] ^ 1_u16) as usize] as u32 + base[m] as u32;
 			let b = b % MODULO as u32;
-			base[i] = mul_table(base[i], b as u16);
+			base[i] = tables.mul_table(base[i], b as u16);
 		}
 	}
-	for i in 0..(MODULO as usize) {
-		SKEW_FACTOR[i] = LOG_TABLE[SKEW_FACTOR[i] as usize];
+	for skew in skew_factor.iter_mut() {
+		*skew = log_table[*skew as usize];
 	}
 
 	base[0] = MODULO - base[0];
@@ -214,294 +624,271 @@ unsafe fn init_dec() {
 		base[i] = ((MODULO as u32 - base[i] as u32 + base[i - 1] as u32) % MODULO as u32) as GFSymbol;
 	}
 
-	B[0] = 0;
+	b_factor[0] = 0;
 	for i in 0..(FIELD_BITS - 1) {
 		let depart = 1 << i;
-		for j in 0..depart {
-			B[j + depart] = ((B[j] as u32 + base[i] as u32) % MODULO as u32) as GFSymbol;
+		for j in 0..depart.min(b_factor.len().saturating_sub(depart)) {
+			b_factor[j + depart] = ((b_factor[j] as u32 + base[i] as u32) % MODULO as u32) as GFSymbol;
 		}
 	}
-
-	mem_cpy(&mut LOG_WALSH[..], &LOG_TABLE[..]);
-	LOG_WALSH[0] = 0;
-	walsh(&mut LOG_WALSH[..], FIELD_SIZE);
 }
 
-// Encoding alg for k/n < 0.5: message is a power of two
-fn encode_low(data: &[GFSymbol], k: usize, codeword: &mut [GFSymbol], n: usize) {
-	assert!(k + k <= n);
-	assert_eq!(codeword.len(), n);
-	assert_eq!(data.len(), n);
-
-	assert!(is_power_of_2(n));
-	assert!(is_power_of_2(k));
-
-	// k | n is guaranteed
-	assert_eq!((n / k) * k, n);
-
-	// move the data to the codeword
-	mem_cpy(&mut codeword[0..], &data[0..]);
-
-	// split after the first k
-	let (codeword_first_k, codeword_skip_first_k) = codeword.split_at_mut(k);
-
-	inverse_fft_in_novel_poly_basis(codeword_first_k, k, 0);
-
-	// the first codeword is now the basis for the remaining transforms
-	// denoted `M_topdash`
-
-	for shift in (k..n).into_iter().step_by(k) {
-		let codeword_at_shift = &mut codeword_skip_first_k[(shift - k)..shift];
-		// copy `M_topdash` to the position we are currently at, the n transform
-		mem_cpy(codeword_at_shift, codeword_first_k);
-		fft_in_novel_poly_basis(codeword_at_shift, k, shift);
-	}
-
-	// restore `M` from the derived ones
-	mem_cpy(&mut codeword[0..k], &data[0..k]);
+//initialize LOG_WALSH[]
+fn transform_log(log_table: &[GFSymbol], log_walsh: &mut [GFSymbol]) {
+	mem_cpy(log_walsh, log_table);
+	log_walsh[0] = 0;
+	walsh(log_walsh, FIELD_SIZE);
 }
 
 fn mem_zero(zerome: &mut [GFSymbol]) {
-	for i in 0..zerome.len() {
-		zerome[i] = 0_u16;
+	for item in zerome.iter_mut() {
+		*item = 0_u16;
 	}
 }
 
 fn mem_cpy(dest: &mut [GFSymbol], src: &[GFSymbol]) {
-	let sl = src.len();
-	debug_assert_eq!(dest.len(), sl);
-	for i in 0..sl {
-		dest[i] = src[i];
+	debug_assert_eq!(dest.len(), src.len());
+	dest.copy_from_slice(src);
+}
+
+/// The field GF(2^16) of this codec, elements in the Cantor basis representation.
+///
+/// Arithmetic goes through the shared log/exp tables, the generator is `x`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct CantorField(Tables<'static>);
+
+#[cfg(feature = "std")]
+impl CantorField {
+	pub fn new() -> Self {
+		Self(tables())
 	}
 }
 
-//data: message array. parity: parity array. mem: buffer(size>= n-k)
-//Encoding alg for k/n>0.5: parity is a power of two.
-fn encode_high(data: &[GFSymbol], k: usize, parity: &mut [GFSymbol], mem: &mut [GFSymbol], n: usize) {
-	let t: usize = n - k;
+#[cfg(feature = "std")]
+impl Default for CantorField {
+	fn default() -> Self {
+		Self::new()
+	}
+}
 
-	mem_zero(&mut parity[0..t]);
+#[cfg(feature = "std")]
+impl galois::GaloisField for CantorField {
+	type Element = GFSymbol;
 
-	let mut i = t;
-	while i < n {
-		mem_cpy(&mut mem[..t], &data[(i - t)..t]);
+	fn bits(&self) -> u32 {
+		FIELD_BITS as u32
+	}
 
-		inverse_fft_in_novel_poly_basis(mem, t, i);
-		for j in 0..t {
-			parity[j] ^= mem[j];
-		}
-		i += t;
+	fn element(&self, value: u32) -> GFSymbol {
+		debug_assert!(value < FIELD_SIZE as u32);
+		value as GFSymbol
 	}
-	fft_in_novel_poly_basis(parity, t, 0);
-}
 
-// Compute the evaluations of the error locator polynomial
-// `fn decode_init`
-// since this has only to be called once per reconstruction
-fn eval_error_polynomial(erasure: &[bool], log_walsh2: &mut [GFSymbol], n: usize) {
-	let z = std::cmp::min(n,erasure.len());
-	for i in 0..z {
-		log_walsh2[i] = erasure[i] as GFSymbol;
+	fn value(&self, a: GFSymbol) -> u32 {
+		a as u32
 	}
-	for i in z..N {
-		log_walsh2[i] = 0 as GFSymbol;
+
+	fn one(&self) -> GFSymbol {
+		self.exp(0)
 	}
-	walsh(log_walsh2, FIELD_SIZE);
-	for i in 0..n {
-		let tmp = log_walsh2[i] as u32 * unsafe { LOG_WALSH[i] } as u32;
-		log_walsh2[i] = (tmp % MODULO as u32) as GFSymbol;
+
+	fn add(&self, a: GFSymbol, b: GFSymbol) -> GFSymbol {
+		a ^ b
 	}
-	walsh(log_walsh2, FIELD_SIZE);
-	for i in 0..z {
-		if erasure[i] {
-			log_walsh2[i] = MODULO - log_walsh2[i];
+
+	fn mul(&self, a: GFSymbol, b: GFSymbol) -> GFSymbol {
+		if b == 0 {
+			0
+		} else {
+			self.0.mul_table(a, self.0.log[b as usize])
 		}
 	}
-}
 
-fn decode_main(codeword: &mut [GFSymbol], k: usize, erasure: &[bool], log_walsh2: &[GFSymbol], n: usize) {
-	assert!(codeword.len() >= k);
-	assert_eq!(codeword.len(), n);
-	assert!(erasure.len() >= k);
-	assert_eq!(erasure.len(), n);
+	fn inv(&self, a: GFSymbol) -> GFSymbol {
+		assert_ne!(a, 0);
+		self.0.exp[(MODULO - self.0.log[a as usize]) as usize]
+	}
 
-	// technically we only need to recover
-	// the first `k` instead of all `n` which
-	// would include parity chunks.
-	let recover_up_to = n;
+	fn exp(&self, n: u64) -> GFSymbol {
+		self.0.exp[(n % MODULO as u64) as usize]
+	}
 
-	for i in 0..n {
-		codeword[i] = if erasure[i] { 0_u16 } else { mul_table(codeword[i], log_walsh2[i]) };
+	fn log(&self, a: GFSymbol) -> u64 {
+		assert_ne!(a, 0);
+		self.0.log[a as usize] as u64
 	}
-	inverse_fft_in_novel_poly_basis(codeword, n, 0);
+}
 
-	//formal derivative
-	for i in (0..n).into_iter().step_by(2) {
-		let b = MODULO - unsafe { B[i >> 1] };
-		codeword[i] = mul_table(codeword[i], b);
-		codeword[i + 1] = mul_table(codeword[i + 1], b);
+impl Tables<'_> {
+	/// [`encode_into`] for arbitrary `n` and `k`, with these tables.
+	pub fn encode_into(&self, data: &[u8], shards: &mut [&mut [u8]], n: usize, k: usize) {
+		let shard_len = shard_len(data.len(), k);
+		encode_into_with(data, shards, n, k, shard_len, |data_shards, parity_shards| {
+			self.encode_parity(data_shards, parity_shards, n, k)
+		});
 	}
 
-	formal_derivative(codeword, n);
+	/// [`encode_parity`] for arbitrary `n` and `k`, with these tables.
+	pub fn encode_parity(&self, data_shards: &[&[u8]], parity_shards: &mut [&mut [u8]], n: usize, k: usize) {
+		assert!(is_power_of_2(n), "Algorithm only works for 2^m sizes for N");
+		assert!(is_power_of_2(k), "Algorithm only works for 2^m sizes for K");
+		assert!(k + k <= n, "Only k/n <= 0.5 is supported for now");
+		assert!(n <= self.max_n(), "Tables must support n shards");
+
+		assert_eq!(data_shards.len(), k);
+		assert_eq!(parity_shards.len(), n - k);
+		let shard_len = data_shards[0].len();
+		assert!(data_shards.iter().all(|shard| shard.len() == shard_len));
+		assert!(parity_shards.iter().all(|shard| shard.len() == shard_len));
+
+		let data_shards = data_shards.iter().map(|shard| as_symbols(shard)).collect::<Vec<_>>();
+		let mut parity_shards = parity_shards.iter_mut().map(|shard| as_symbols_mut(shard)).collect::<Vec<_>>();
+
+		let mut data = vec![0 as GFSymbol; n];
+		let mut codeword = vec![0 as GFSymbol; n];
+		for sym_idx in 0..(shard_len / 2) {
+			for (symbol, shard) in data.iter_mut().zip(&data_shards) {
+				*symbol = GFSymbol::from_le_bytes(shard[sym_idx]);
+			}
+
+			self.encode_low(&data[..], k, &mut codeword[..], n);
 
-	for i in (0..n).into_iter().step_by(2) {
-		let b = unsafe { B[i >> 1] };
-		codeword[i] = mul_table(codeword[i], b);
-		codeword[i + 1] = mul_table(codeword[i + 1], b);
+			// the first `k` are the data shards, which are already in place
+			for (symbol, shard) in codeword[k..].iter().zip(&mut parity_shards) {
+				shard[sym_idx] = symbol.to_le_bytes();
+			}
+		}
+	}
+
+	/// [`reconstruct_sub`] with these tables.
+	pub fn reconstruct(&self, received_shards: Vec<Option<WrappedShard>>, n: usize, k: usize) -> Option<Vec<u8>> {
+		self.reconstruct_shards(&received_shards[..], n, k)
 	}
 
-	fft_in_novel_poly_basis(codeword, n, 0);
+	/// [`Tables::reconstruct`] of borrowed shards.
+	pub(crate) fn reconstruct_shards(
+		&self,
+		received_shards: &[Option<WrappedShard>],
+		n: usize,
+		k: usize,
+	) -> Option<Vec<u8>> {
+		assert!(n <= self.max_n(), "Tables must support n shards");
+		if received_shards.len() != n {
+			return None;
+		}
+
+		let shard_len = received_shards.iter().flatten().map(|shard| AsRef::<[u8]>::as_ref(shard).len()).next()?;
+		if received_shards.iter().flatten().any(|shard| AsRef::<[u8]>::as_ref(shard).len() != shard_len) {
+			return None;
+		}
+
+		// collect all `None` values
+		let erasures = received_shards.iter().map(|x| x.is_none()).collect::<Vec<bool>>();
+		if erasures.iter().filter(|erased| !**erased).count() < k {
+			return None;
+		}
+
+		//---------Erasure decoding----------------
+		// compact tables only evaluate the `n` positions decoding reads
+		let mut log_walsh2 = vec![0 as GFSymbol; if self.log_walsh.is_some() { FIELD_SIZE } else { n }];
+
+		// Evaluate error locator polynomial, identical for all codewords
+		self.eval_error_polynomial(&erasures[..], &mut log_walsh2[..], FIELD_SIZE);
+
+		let mut recovered = vec![0u8; k * shard_len];
+		let mut codeword = vec![0 as GFSymbol; n];
+		for sym_idx in 0..(shard_len / 2) {
+			for (symbol, shard) in codeword.iter_mut().zip(received_shards) {
+				*symbol = shard
+					.as_ref()
+					.map(|shard| {
+						let shard: &[[u8; 2]] = shard.as_ref();
+						GFSymbol::from_le_bytes(shard[sym_idx])
+					})
+					.unwrap_or_default();
+			}
+
+			//---------main processing----------
+			let received = codeword.clone();
+			self.decode_main(&mut codeword[..], k, &erasures[..], &log_walsh2[..], n);
+
+			// `decode_main` only yields the erased symbols
+			for i in 0..k {
+				let symbol = if erasures[i] { codeword[i] } else { received[i] };
+				let offset = i * shard_len + sym_idx * 2;
+				recovered[offset..(offset + 2)].copy_from_slice(&symbol.to_le_bytes());
+			}
+		}
 
-	for i in 0..recover_up_to {
-		codeword[i] = if erasure[i] { mul_table(codeword[i], log_walsh2[i]) } else { 0_u16 };
+		Some(recovered)
 	}
 }
 
-const N: usize = 32;
-const K: usize = 4;
+#[cfg(feature = "std")]
+pub fn encode(data: &[u8]) -> Vec<WrappedShard> {
+	encode_sub(data, N_VALIDATORS, DATA_SHARDS)
+}
 
-use itertools::Itertools;
+/// Encode `bytes` into `n` shards, of which the first `k` are the zero padded payload.
+///
+/// The `i`-th symbol of every shard is one codeword, so the transform is applied
+/// once per symbol of a shard. Both `n` and `k` must be powers of two with `k <= n / 2`.
+#[cfg(feature = "std")]
+pub fn encode_sub(bytes: &[u8], n: usize, k: usize) -> Vec<WrappedShard> {
+	let shard_len = shard_len(bytes.len(), k);
 
-pub fn encode(data: &[u8]) -> Vec<WrappedShard> {
-	unsafe { init() };
-
-	// must be power of 2
-	let l = log2(data.len());
-	let l = 1 << l;
-	let l = if l >= data.len() { l } else { l << 1 };
-	assert!(l >= data.len());
-	assert!(is_power_of_2(l));
-	assert!(is_power_of_2(N), "Algorithm only works for 2^m sizes for N");
-	assert!(is_power_of_2(K), "Algorithm only works for 2^m sizes for K");
-
-	// pad the incoming data with trailing 0s
-	let zero_bytes_to_add = dbg!(l) - dbg!(data.len());
-	let data: Vec<GFSymbol> = data
-		.into_iter()
-		.copied()
-		.chain(std::iter::repeat(0u8).take(zero_bytes_to_add))
-		.tuple_windows()
-		.step_by(2)
-		.map(|(a, b)| (b as u16) << 8 | a as u16)
-		.collect::<Vec<GFSymbol>>();
-
-	// assert_eq!(K, data.len());
-	assert_eq!(data.len() * 2, l + zero_bytes_to_add);
-
-	// two bytes make one `l / 2`
-	let l = l / 2;
-	assert_eq!(l, N, "For now we only want to test of variants that don't have to be 0 padded");
-	let mut codeword = data.clone();
-	assert_eq!(codeword.len(), N);
-
-	assert!(K <= N / 2);
-	// if K + K > N {
-	// 	let (data_till_t, data_skip_t) = data.split_at_mut(N - K);
-	// 	encode_high(data_skip_t, K, data_till_t, &mut codeword[..], N);
-	// } else {
-	encode_low(&data[..], K, &mut codeword[..], N);
-	// }
-
-	println!("Codeword:");
-	for i in 0..N {
-		print!("{:04x} ", codeword[i]);
-	}
-	println!("");
-
-	// XXX currently this is only done for one codeword!
-
-	let shards = (0..N)
-		.into_iter()
-		.map(|i| {
-			WrappedShard::new({
-				let arr = codeword[i].to_le_bytes();
-				arr.to_vec()
-			})
-		})
-		.collect::<Vec<WrappedShard>>();
+	let mut shards = vec![WrappedShard::new(vec![0u8; shard_len]); n];
+	let mut views = shards.iter_mut().map(|shard| shard.as_mut()).collect::<Vec<&mut [u8]>>();
+	encode_into_sub(bytes, &mut views[..], n, k);
 
 	shards
 }
 
-pub fn reconstruct(received_shards: Vec<Option<WrappedShard>>) -> Option<Vec<u8>> {
-	unsafe { init_dec() };
-
-	// collect all `None` values
-	let mut erased_count = 0;
-	let erasures = received_shards
-		.iter()
-		.map(|x| x.is_none())
-		.inspect(|v| {
-			if *v {
-				erased_count += 1;
-			}
-		})
-		.collect::<Vec<bool>>();
-
-	// The recovered _data_ chunks AND parity chunks
-	let mut recovered: Vec<GFSymbol> = std::iter::repeat(0u16).take(N).collect();
-
-	// get rid of all `None`s
-	let mut codeword = received_shards
-		.into_iter()
-		.enumerate()
-		.map(|(idx, wrapped)| {
-			// fill the gaps with `0_u16` codewords
-			if let Some(wrapped) = wrapped {
-				let v: &[[u8; 2]] = wrapped.as_ref();
-				(idx, u16::from_le_bytes(v[0]))
-			} else {
-				(idx, 0_u16)
-			}
-		})
-		.map(|(idx, codeword)| {
-			// copy the good messages (here it's just one codeword/u16 right now)
-			if idx < N {
-				recovered[idx] = codeword;
-			}
-			codeword
-		})
-		.collect::<Vec<u16>>();
-
-	// filled up the remaining spots with 0s
-	assert_eq!(codeword.len(), N);
+#[cfg(feature = "std")]
+backend_wrappers!();
 
-	let recover_up_to = N; // the first k would suffice for the original k message codewords
-
-	//---------Erasure decoding----------------
-	let mut log_walsh2: [GFSymbol; FIELD_SIZE] = [0_u16; FIELD_SIZE];
+/// [`encode_into`] for arbitrary `n` and `k`.
+#[cfg(feature = "std")]
+pub fn encode_into_sub(data: &[u8], shards: &mut [&mut [u8]], n: usize, k: usize) {
+	tables().encode_into(data, shards, n, k)
+}
 
-	// Evaluate error locator polynomial
-	eval_error_polynomial(&erasures[..], &mut log_walsh2[..], FIELD_SIZE);
+/// [`encode_parity`] for arbitrary `n` and `k`.
+#[cfg(feature = "std")]
+pub fn encode_parity_sub(data_shards: &[&[u8]], parity_shards: &mut [&mut [u8]], n: usize, k: usize) {
+	tables().encode_parity(data_shards, parity_shards, n, k)
+}
 
-	//---------main processing----------
-	decode_main(&mut codeword[..], recover_up_to, &erasures[..], &log_walsh2[..], N);
+#[cfg(feature = "std")]
+pub fn reconstruct(received_shards: Vec<Option<WrappedShard>>) -> Option<Vec<u8>> {
+	reconstruct_sub(received_shards, N_VALIDATORS, DATA_SHARDS)
+}
 
-	println!("Decoded result:");
-	for idx in 0..N {
-		if erasures[idx] {
-			print!("{:04x} ", codeword[idx]);
-			recovered[idx] = codeword[idx];
-		} else {
-			print!("XXXX ");
-		};
-	}
+/// Recover the payload from at least `k` of the `n` shards.
+///
+/// Yields `None` if too few shards are present or their lengths do not match.
+/// The result contains the zero padding added by [`encode_sub`].
+#[cfg(feature = "std")]
+pub fn reconstruct_sub(received_shards: Vec<Option<WrappedShard>>, n: usize, k: usize) -> Option<Vec<u8>> {
+	tables().reconstruct(received_shards, n, k)
+}
 
-	let recovered = unsafe {
-		// TODO assure this does not leak memory
-		let x = from_raw_parts(recovered.as_ptr() as *const u8, recovered.len() * 2);
-		std::mem::forget(recovered);
-		x
-	};
-	Some(recovered.to_vec())
+/// [`reconstruct`] of borrowed shards.
+#[cfg(feature = "std")]
+pub(crate) fn reconstruct_shards(received_shards: &mut [Option<WrappedShard>]) -> Option<Vec<u8>> {
+	tables().reconstruct_shards(received_shards, N_VALIDATORS, DATA_SHARDS)
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod test {
 	use rand::seq::index::IndexVec;
 
 	use super::*;
 
+	const N: usize = 32;
+	const K: usize = 4;
+
 	fn print_sha256(txt: &'static str, data: &[GFSymbol]) {
 		use sha2::Digest;
 		let data = unsafe { ::std::slice::from_raw_parts(data.as_ptr() as *const u8, data.len() * 2) };
@@ -512,7 +899,7 @@ mod test {
 		for byte in digest.finalize().into_iter() {
 			print!("{:02x}", byte);
 		}
-		println!("")
+		println!()
 	}
 
 	/// Generate a random index
@@ -529,15 +916,15 @@ mod test {
 	fn flt_back_and_forth() {
 		const N: usize = 128;
 		const K: usize = 32;
-		let mut data = (0..N).into_iter().map(|_x| rand_gf_element()).collect::<Vec<GFSymbol>>();
+		let mut data = (0..N).map(|_x| rand_gf_element()).collect::<Vec<GFSymbol>>();
 		let expected = data.clone();
 
-		fft_in_novel_poly_basis(&mut data, N, K);
+		tables().fft_in_novel_poly_basis(&mut data, N, K);
 
 		// make sure something is done
 		assert!(data.iter().zip(expected.iter()).filter(|(a, b)| { a != b }).count() > 0);
 
-		inverse_fft_in_novel_poly_basis(&mut data, N, K);
+		tables().inverse_fft_in_novel_poly_basis(&mut data, N, K);
 
 		itertools::assert_equal(data, expected);
 	}
@@ -547,27 +934,158 @@ mod test {
 		const N: usize = 16;
 		const EXPECTED: [GFSymbol; N] = [1, 2, 3, 5, 8, 13, 21, 44, 65, 0, 0xFFFF, 2, 3, 5, 7, 11];
 
-		let mut data = EXPECTED.clone();
+		let mut data = EXPECTED;
 
-		fft_in_novel_poly_basis(&mut data, N, N / 4);
+		tables().fft_in_novel_poly_basis(&mut data, N, N / 4);
 
 		println!("novel basis(rust):");
 		data.iter().for_each(|sym| {
 			print!(" {:04X}", sym);
 		});
-		println!("");
+		println!();
 
-		inverse_fft_in_novel_poly_basis(&mut data, N, N / 4);
+		tables().inverse_fft_in_novel_poly_basis(&mut data, N, N / 4);
 		itertools::assert_equal(data.iter(), EXPECTED.iter());
 	}
 
+	/// The element with Cantor basis representation `cantor`, in the polynomial basis.
+	fn from_cantor(cantor: GFSymbol) -> binaryfield::Element {
+		let mut poly = 0_u16;
+		for (i, base) in BASE.iter().enumerate() {
+			if cantor & (1 << i) != 0 {
+				poly ^= base;
+			}
+		}
+		poly.into()
+	}
+
 	#[test]
-	fn ported_c_test() {
-		unsafe {
-			init(); //fill log table and exp table
-			init_dec(); //compute factors used in erasure decoder
+	fn tables_match_binaryfield() {
+		let tables = tables();
+		assert_eq!(binaryfield::moduli::GF_2_16, 1 << FIELD_BITS | GENERATOR as u64);
+		let field = binaryfield::BinaryField::new_primitive(binaryfield::moduli::GF_2_16).unwrap();
+		let x = binaryfield::Element::from(2_u16);
+
+		for a in (1..FIELD_SIZE).step_by(13).map(|a| a as GFSymbol) {
+			let log_a = tables.log[a as usize];
+			assert_eq!(field.exp(x, log_a.into()), from_cantor(a));
+			assert_eq!(from_cantor(tables.exp[log_a as usize]), from_cantor(a));
+
+			let b = a.wrapping_mul(7919) | 1;
+			let product = tables.mul_table(a, tables.log[b as usize]);
+			assert_eq!(from_cantor(product), field.mul(from_cantor(a), from_cantor(b)));
+		}
+	}
+
+	#[test]
+	fn clmul_matches_mul_table() {
+		let tables = tables();
+		let modulus = 1 << FIELD_BITS | GENERATOR as u64;
+		for barrett in [binaryfield::Barrett::new(modulus).unwrap(), binaryfield::Barrett::portable(modulus).unwrap()] {
+			let clmul = Clmul::new(barrett);
+			for b in (0..=MODULO).step_by(89).chain([0, MODULO]) {
+				let factor = clmul.factor(&tables, b);
+				let symbols = (0..=MODULO).step_by(97).chain([1, MODULO]).collect::<Vec<_>>();
+				let mut src = symbols.clone();
+				clmul.change_from_cantor(&mut src[..]);
+				let mut products = vec![0; src.len()];
+				clmul.barrett.mul_add_slice(&mut products[..], &src[..], factor);
+				clmul.change_to_cantor(&mut products[..]);
+				for (&a, &product) in symbols.iter().zip(&products) {
+					assert_eq!(product, tables.mul_table(a, b), "{:#x} * x^{}", a, b);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn multipliers_agree() {
+		let table = tables().with_multiplier(Multiplier::Table);
+		let clmul = table.with_multiplier(Multiplier::Clmul);
+		assert_eq!(table.multiplier(), Multiplier::Table);
+		assert_eq!(clmul.multiplier(), Multiplier::Clmul);
+
+		let (n, k) = (256, 32);
+		let payload = &BYTES[..1000];
+		let mut shards = vec![vec![0u8; shard_len(payload.len(), k)]; 2 * n];
+		let (expected, actual) = shards.split_at_mut(n);
+		let mut views = expected.iter_mut().map(|shard| &mut shard[..]).collect::<Vec<_>>();
+		table.encode_into(payload, &mut views[..], n, k);
+		let mut views = actual.iter_mut().map(|shard| &mut shard[..]).collect::<Vec<_>>();
+		clmul.encode_into(payload, &mut views[..], n, k);
+		assert_eq!(expected, actual);
+
+		let received = actual
+			.iter()
+			.enumerate()
+			.map(|(i, shard)| if i % 8 == 5 { Some(WrappedShard::new(shard.clone())) } else { None })
+			.collect();
+		let recovered = clmul.reconstruct(received, n, k).unwrap();
+		assert_eq!(&recovered[..payload.len()], payload);
+	}
+
+	#[test]
+	fn tables_match_generator() {
+		let tables = tables();
+		let modulus = 1 << FIELD_BITS | GENERATOR as u64;
+		let base = cantor::cantor_basis(modulus).unwrap();
+		assert_eq!(base, BASE.map(u32::from));
+
+		let generated = cantor::Tables::new(modulus, &base[..]);
+		let widen = |table: &[GFSymbol]| table.iter().copied().map(u32::from).collect::<Vec<_>>();
+		assert_eq!(generated.log, widen(tables.log));
+		assert_eq!(generated.exp, widen(tables.exp));
+		assert_eq!(generated.skew, widen(tables.skew));
+		assert_eq!(generated.b, widen(tables.b));
+		assert_eq!(generated.log_walsh, widen(tables.log_walsh.unwrap()));
+	}
+
+	#[test]
+	fn compact_tables_in_caller_buffer() {
+		const MAX_N: usize = 256;
+		let full = tables();
+		// dirty, the buffer of the caller is not necessarily zeroed
+		let mut buffer = vec![0xA5A5_u16; Tables::buffer_len(MAX_N, Layout::Compact)];
+		let compact = Tables::new_in(&mut buffer[..], MAX_N, Layout::Compact);
+		assert_eq!(compact.max_n(), MAX_N);
+		assert_eq!(compact.log, full.log);
+		assert_eq!(compact.exp, full.exp);
+		assert_eq!(compact.skew, &full.skew[..(MAX_N - 1)]);
+		assert_eq!(compact.b, &full.b[..(MAX_N / 2)]);
+		assert!(compact.log_walsh.is_none());
+
+		let (n, k) = (MAX_N, 64);
+		let payload = &BYTES[..1000];
+		let expected = encode_sub(payload, n, k);
+		let mut shards = vec![vec![0u8; shard_len(payload.len(), k)]; n];
+		let mut views = shards.iter_mut().map(|shard| &mut shard[..]).collect::<Vec<_>>();
+		compact.encode_into(payload, &mut views[..], n, k);
+		for (shard, expected) in shards.iter().zip(&expected) {
+			assert_eq!(&shard[..], AsRef::<[u8]>::as_ref(expected));
 		}
 
+		// only every fourth shard, and the log walsh transform is recomputed
+		let received =
+			expected.into_iter().enumerate().map(|(i, shard)| if i % 4 == 3 { Some(shard) } else { None }).collect();
+		let recovered = compact.reconstruct(received, n, k).unwrap();
+		assert_eq!(&recovered[..payload.len()], payload);
+	}
+
+	#[test]
+	#[should_panic(expected = "Tables must support n shards")]
+	fn tables_limit_shards() {
+		let mut buffer = vec![0_u16; Tables::buffer_len(16, Layout::Full)];
+		let tables = Tables::new_in(&mut buffer[..], 16, Layout::Full);
+		let mut buffers = vec![vec![0_u8; 2]; 32];
+		let mut views = buffers.iter_mut().map(|shard| &mut shard[..]).collect::<Vec<_>>();
+		tables.encode_into(&[1, 2, 3], &mut views[..], 32, 4);
+	}
+
+	#[test]
+	fn ported_c_test() {
+		// fill log table and exp table, compute factors used in erasure decoder
+		let tables = tables();
+
 		//-----------Generating message----------
 		//message array
 		let mut data: [GFSymbol; N] = [0; N];
@@ -584,24 +1102,24 @@ mod test {
 		for i in 0..K {
 			print!("{:04x} ", data[i]);
 		}
-		println!("");
+		println!();
 		print_sha256("data", &data[..]);
 
 		//---------encoding----------
 		let mut codeword = [0_u16; N];
 
-		if K + K > N && false {
-			let (data_till_t, data_skip_t) = data.split_at_mut(N - K);
-			encode_high(data_skip_t, K, data_till_t, &mut codeword[..], N);
-		} else {
-			encode_low(&data[..], K, &mut codeword[..], N);
-		}
+		// if K + K > N {
+		// 	let (data_till_t, data_skip_t) = data.split_at_mut(N - K);
+		// 	encode_high(data_skip_t, K, data_till_t, &mut codeword[..], N);
+		// } else {
+		tables.encode_low(&data[..], K, &mut codeword[..], N);
+		// }
 
 		// println!("Codeword:");
 		// for i in K..(K+100) {
 		// print!("{:04x} ", codeword[i]);
 		// }
-		// println!("");
+		// println!();
 
 		print_sha256("encoded", &codeword);
 
@@ -617,7 +1135,7 @@ mod test {
 
 			erasures_iv
 		} else {
-			IndexVec::from((0..(N - K)).into_iter().collect::<Vec<usize>>())
+			IndexVec::from((0..(N - K)).collect::<Vec<usize>>())
 		};
 		assert_eq!(erasures_iv.len(), N - K);
 
@@ -632,11 +1150,11 @@ mod test {
 		//---------Erasure decoding----------------
 		let mut log_walsh2: [GFSymbol; FIELD_SIZE] = [0_u16; FIELD_SIZE];
 
-		eval_error_polynomial(&erasure[..], &mut log_walsh2[..], FIELD_SIZE);
+		tables.eval_error_polynomial(&erasure[..], &mut log_walsh2[..], FIELD_SIZE);
 
 		print_sha256("log_walsh2", &log_walsh2);
 
-		decode_main(&mut codeword[..], K, &erasure[..], &log_walsh2[..], N);
+		tables.decode_main(&mut codeword[..], K, &erasure[..], &log_walsh2[..], N);
 
 		print_sha256("decoded", &codeword[0..K]);
 
@@ -645,7 +1163,7 @@ mod test {
 			// the data word plus a few more
 			print!("{:04x} ", codeword[i]);
 		}
-		println!("");
+		println!();
 
 		for i in 0..K {
 			//Check the correctness of the result
@@ -660,3 +1178,55 @@ mod test {
 
 	}
 }
+
+// only `alloc`, so these also run with `--no-default-features`
+#[cfg(test)]
+mod alloc_test {
+	use super::*;
+
+	#[test]
+	fn compact_error_polynomial_matches_full() {
+		const MAX_N: usize = 256;
+		let mut full = vec![0_u16; Tables::buffer_len(MAX_N, Layout::Full)];
+		let full = Tables::new_in(&mut full[..], MAX_N, Layout::Full);
+		let mut compact = vec![0_u16; Tables::buffer_len(MAX_N, Layout::Compact)];
+		let compact = Tables::new_in(&mut compact[..], MAX_N, Layout::Compact);
+
+		for n in [2, 16, MAX_N] {
+			let erasure = (0..n).map(|i| i % 3 == 0 || i % 7 == 5).collect::<Vec<_>>();
+			let mut expected = vec![0 as GFSymbol; FIELD_SIZE];
+			full.eval_error_polynomial(&erasure[..], &mut expected[..], FIELD_SIZE);
+			let mut computed = vec![0 as GFSymbol; n];
+			compact.eval_error_polynomial(&erasure[..], &mut computed[..], FIELD_SIZE);
+
+			// the Walsh transforms may yield MODULO for zero
+			for (expected, computed) in expected.iter().zip(&computed) {
+				assert_eq!(expected % MODULO, computed % MODULO);
+			}
+		}
+	}
+
+	#[test]
+	fn roundtrip_with_tables_in_caller_buffer() {
+		const MAX_N: usize = 64;
+		let (n, k) = (MAX_N, 16);
+		let mut buffer = vec![0_u16; Tables::buffer_len(MAX_N, Layout::Compact)];
+		let tables = Tables::new_in(&mut buffer[..], MAX_N, Layout::Compact);
+
+		let payload = &BYTES[..500];
+		let shard_len = shard_len(payload.len(), k);
+		let mut shards = vec![vec![0u8; shard_len]; n];
+		let mut views = shards.iter_mut().map(|shard| &mut shard[..]).collect::<Vec<_>>();
+		tables.encode_into(payload, &mut views[..], n, k);
+		assert_eq!(&shards[0][..], &payload[..shard_len]);
+
+		// exactly `k` shards, mostly parity
+		let received = shards
+			.into_iter()
+			.enumerate()
+			.map(|(i, shard)| if i % 4 == 1 { Some(WrappedShard::new(shard)) } else { None })
+			.collect();
+		let recovered = tables.reconstruct(received, n, k).unwrap();
+		assert_eq!(&recovered[..payload.len()], payload);
+	}
+}
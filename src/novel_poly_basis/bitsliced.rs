@@ -0,0 +1,551 @@
+// Bitsliced GF(2^16) kernels, the novel polynomial basis codec with XORs only.
+//
+// A `Block` holds 16 bit-planes: bit `t` of plane `b` is bit `b` of the `t`-th symbol, so one
+// block carries 64 symbols with `u64` words or 256 with `U64x4`. Multiplication by a constant is
+// linear over GF(2), so `a * c` becomes a fixed XOR network over the planes, derived once per
+// constant from `mul_table`. The network is stored as a list of input planes per output plane
+// and interpreted at run time, with one XOR per tap and no tests of the matrix bits. The FFT
+// butterflies then run on whole words, without table lookups in the inner loops and with timing
+// independent of the data, on any architecture.
+//
+// The shards are identical to the ones of the table based parent module, the `t`-th symbols
+// of all shards form one codeword and a block bundles consecutive symbols of one shard.
+
+use super::*;
+use std::ops::{BitXor, BitXorAssign};
+
+/// A machine word holding one bit of each of its [`Word::BITS`] symbols.
+pub trait Word: Copy + Default + Eq + std::fmt::Debug + BitXor<Output = Self> + BitXorAssign {
+	/// Number of symbols per word.
+	const BITS: usize;
+
+	/// Bit `t`, as `0` or `1`.
+	fn bit(&self, t: usize) -> u16;
+
+	/// Set bit `t` to `value`, which is `0` or `1`, assuming it is clear.
+	fn insert(&mut self, t: usize, value: u16);
+}
+
+impl Word for u64 {
+	const BITS: usize = 64;
+
+	#[inline(always)]
+	fn bit(&self, t: usize) -> u16 {
+		(self >> t & 1) as u16
+	}
+
+	#[inline(always)]
+	fn insert(&mut self, t: usize, value: u16) {
+		*self |= (value as u64) << t;
+	}
+}
+
+/// A 256 bit word, four `u64` the compiler may keep in one vector register.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct U64x4(pub [u64; 4]);
+
+impl BitXor for U64x4 {
+	type Output = Self;
+
+	#[inline(always)]
+	fn bitxor(mut self, rhs: Self) -> Self {
+		self ^= rhs;
+		self
+	}
+}
+
+impl BitXorAssign for U64x4 {
+	#[inline(always)]
+	fn bitxor_assign(&mut self, rhs: Self) {
+		for (lane, rhs) in self.0.iter_mut().zip(rhs.0) {
+			*lane ^= rhs;
+		}
+	}
+}
+
+impl Word for U64x4 {
+	const BITS: usize = 256;
+
+	#[inline(always)]
+	fn bit(&self, t: usize) -> u16 {
+		self.0[t >> 6].bit(t & 63)
+	}
+
+	#[inline(always)]
+	fn insert(&mut self, t: usize, value: u16) {
+		self.0[t >> 6].insert(t & 63, value)
+	}
+}
+
+/// [`Word::BITS`] symbols of GF(2^16) as 16 bit-planes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Block<W: Word>(pub [W; FIELD_BITS]);
+
+impl<W: Word> Block<W> {
+	/// Transpose up to [`Word::BITS`] symbols into planes, missing symbols are zero.
+	pub fn pack<I: IntoIterator<Item = u16>>(symbols: I) -> Self {
+		let mut planes = [W::default(); FIELD_BITS];
+		for (t, symbol) in symbols.into_iter().take(W::BITS).enumerate() {
+			for (b, plane) in planes.iter_mut().enumerate() {
+				plane.insert(t, symbol >> b & 1);
+			}
+		}
+		Self(planes)
+	}
+
+	/// The `t`-th symbol.
+	pub fn symbol(&self, t: usize) -> u16 {
+		self.0.iter().enumerate().fold(0, |acc, (b, plane)| acc | plane.bit(t) << b)
+	}
+
+	/// Product of all symbols with the constant of `multiplier`.
+	#[inline(always)]
+	pub fn mul(&self, multiplier: &Multiplier) -> Self {
+		let mut planes = [W::default(); FIELD_BITS];
+		let mut start = 0;
+		for (plane, &end) in planes.iter_mut().zip(&multiplier.ends) {
+			// only the taps of the constant, never a branch
+			for &input in &multiplier.taps[start..end as usize] {
+				*plane ^= self.0[input as usize];
+			}
+			start = end as usize;
+		}
+		Self(planes)
+	}
+}
+
+impl<W: Word> BitXorAssign for Block<W> {
+	#[inline(always)]
+	fn bitxor_assign(&mut self, rhs: Self) {
+		for (plane, rhs) in self.0.iter_mut().zip(rhs.0) {
+			*plane ^= rhs;
+		}
+	}
+}
+
+/// The XOR network of the multiplication by a constant of GF(2^16).
+///
+/// Stored as the list of input planes per output plane, so [`Block::mul`] performs exactly one
+/// XOR per set bit of the matrix of the constant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Multiplier {
+	// the input planes of output plane `r` are `taps[ends[r - 1]..ends[r]]`, with `ends[-1] = 0`
+	taps: [u8; FIELD_BITS * FIELD_BITS],
+	ends: [u16; FIELD_BITS],
+}
+
+impl Multiplier {
+	/// Multiplication by `factor`, in the Cantor basis representation of the codec.
+	pub fn new(factor: u16) -> Self {
+		if factor == 0 {
+			return Self::from_rows([0; FIELD_BITS]);
+		}
+		let tables = tables();
+		Self::from_log(&tables, tables.log[factor as usize])
+	}
+
+	/// The network where bit `b` of `rows[r]` is set iff input plane `b` contributes to output plane `r`.
+	fn from_rows(rows: [u16; FIELD_BITS]) -> Self {
+		let mut taps = [0_u8; FIELD_BITS * FIELD_BITS];
+		let mut ends = [0_u16; FIELD_BITS];
+		let mut len = 0;
+		for (row, end) in rows.iter().zip(ends.iter_mut()) {
+			for b in (0..FIELD_BITS).filter(|b| row >> b & 1 != 0) {
+				taps[len] = b as u8;
+				len += 1;
+			}
+			*end = len as u16;
+		}
+		Self { taps, ends }
+	}
+
+	/// Number of XORs per plane word of [`Block::mul`].
+	pub fn weight(&self) -> usize {
+		self.ends[FIELD_BITS - 1] as usize
+	}
+
+	/// Multiplication by `exp(log)`, the factor of `mul_table(_, log)`.
+	fn from_log(tables: &Tables, log: GFSymbol) -> Self {
+		let mut rows = [0_u16; FIELD_BITS];
+		for b in 0..FIELD_BITS {
+			// the image of the `b`-th basis element is column `b`
+			let column = tables.mul_table(1 << b, log);
+			for (r, row) in rows.iter_mut().enumerate() {
+				*row |= (column >> r & 1) << b;
+			}
+		}
+		Self::from_rows(rows)
+	}
+}
+
+/// Networks for `SKEW_FACTOR[offset..(offset + len)]`, `None` for the factor zero.
+fn skew_networks(tables: &Tables, offset: usize, len: usize) -> Vec<Option<Multiplier>> {
+	tables.skew[offset..(offset + len)]
+		.iter()
+		.map(|&skew| if skew == MODULO { None } else { Some(Multiplier::from_log(tables, skew)) })
+		.collect()
+}
+
+fn check_size(len: usize, index: usize) {
+	assert!(is_power_of_2(len), "Transform size must be a power of two");
+	assert_eq!(index % len, 0, "Offset must be a multiple of the transform size");
+	assert!(index + len <= FIELD_SIZE, "Points must lie within the field");
+}
+
+/// Bitsliced [`transform::fft`], for each symbol of the blocks.
+pub fn fft<W: Word>(data: &mut [Block<W>], index: usize) {
+	check_size(data.len(), index);
+	fft_with(data, &skew_networks(&tables(), index, data.len() - 1)[..]);
+}
+
+/// Bitsliced [`transform::ifft`], for each symbol of the blocks.
+pub fn ifft<W: Word>(data: &mut [Block<W>], index: usize) {
+	check_size(data.len(), index);
+	inverse_fft_with(data, &skew_networks(&tables(), index, data.len() - 1)[..]);
+}
+
+// `skews[j - 1]` is the factor of the butterflies at `j`, as `SKEW_FACTOR[j + index - 1]`
+fn inverse_fft_with<W: Word>(data: &mut [Block<W>], skews: &[Option<Multiplier>]) {
+	let size = data.len();
+	let mut depart_no = 1_usize;
+	while depart_no < size {
+		let mut j = depart_no;
+		while j < size {
+			for i in (j - depart_no)..j {
+				let lower = data[i];
+				data[i + depart_no] ^= lower;
+			}
+
+			if let Some(skew) = &skews[j - 1] {
+				for i in (j - depart_no)..j {
+					let product = data[i + depart_no].mul(skew);
+					data[i] ^= product;
+				}
+			}
+
+			j += depart_no << 1;
+		}
+		depart_no <<= 1;
+	}
+}
+
+fn fft_with<W: Word>(data: &mut [Block<W>], skews: &[Option<Multiplier>]) {
+	let size = data.len();
+	let mut depart_no = size >> 1_usize;
+	while depart_no > 0 {
+		let mut j = depart_no;
+		while j < size {
+			if let Some(skew) = &skews[j - 1] {
+				for i in (j - depart_no)..j {
+					let product = data[i + depart_no].mul(skew);
+					data[i] ^= product;
+				}
+			}
+			for i in (j - depart_no)..j {
+				let lower = data[i];
+				data[i + depart_no] ^= lower;
+			}
+			j += depart_no << 1;
+		}
+		depart_no >>= 1;
+	}
+}
+
+// `encode_low` of the parent module, only yields the parity in `codeword[k..]`
+fn encode_low<W: Word>(data: &[Block<W>], codeword: &mut [Block<W>], skews: &[Option<Multiplier>]) {
+	let k = data.len();
+	codeword[..k].copy_from_slice(data);
+
+	let (codeword_first_k, codeword_skip_first_k) = codeword.split_at_mut(k);
+	inverse_fft_with(codeword_first_k, skews);
+
+	for (shift, codeword_at_shift) in (k..).step_by(k).zip(codeword_skip_first_k.chunks_mut(k)) {
+		codeword_at_shift.copy_from_slice(codeword_first_k);
+		fft_with(codeword_at_shift, &skews[shift..]);
+	}
+}
+
+/// The networks of `decode_main`, identical for all codewords of one set of erasures.
+struct Decoder {
+	erasures: Vec<bool>,
+	// multiplication with the evaluations of the error locator polynomial
+	locator: Vec<Multiplier>,
+	// normalization of the formal derivative, `1 / B` and `B` per pair of coefficients
+	unscale: Vec<Multiplier>,
+	scale: Vec<Multiplier>,
+	skews: Vec<Option<Multiplier>>,
+}
+
+impl Decoder {
+	fn new(tables: &Tables, erasures: Vec<bool>) -> Self {
+		let n = erasures.len();
+		let mut log_walsh2 = vec![0 as GFSymbol; FIELD_SIZE];
+		tables.eval_error_polynomial(&erasures[..], &mut log_walsh2[..], FIELD_SIZE);
+
+		let b = &tables.b[..(n >> 1)];
+		Self {
+			locator: log_walsh2[..n].iter().map(|&log| Multiplier::from_log(tables, log)).collect(),
+			unscale: b.iter().map(|&b| Multiplier::from_log(tables, MODULO - b)).collect(),
+			scale: b.iter().map(|&b| Multiplier::from_log(tables, b)).collect(),
+			skews: skew_networks(tables, 0, n - 1),
+			erasures,
+		}
+	}
+
+	// `decode_main` of the parent module, only yields the erased symbols
+	fn decode<W: Word>(&self, codeword: &mut [Block<W>]) {
+		let n = codeword.len();
+		for ((symbol, &erased), locator) in codeword.iter_mut().zip(&self.erasures).zip(&self.locator) {
+			*symbol = if erased { Block::default() } else { symbol.mul(locator) };
+		}
+		inverse_fft_with(codeword, &self.skews[..]);
+
+		for (pair, unscale) in codeword.chunks_mut(2).zip(&self.unscale) {
+			pair.iter_mut().for_each(|symbol| *symbol = symbol.mul(unscale));
+		}
+		for i in 1..n {
+			let length = ((i ^ i - 1) + 1) >> 1;
+			for j in (i - length)..i {
+				let upper = codeword[j + length];
+				codeword[j] ^= upper;
+			}
+		}
+		for (pair, scale) in codeword.chunks_mut(2).zip(&self.scale) {
+			pair.iter_mut().for_each(|symbol| *symbol = symbol.mul(scale));
+		}
+
+		fft_with(codeword, &self.skews[..]);
+		for ((symbol, &erased), locator) in codeword.iter_mut().zip(&self.erasures).zip(&self.locator) {
+			*symbol = if erased { symbol.mul(locator) } else { Block::default() };
+		}
+	}
+}
+
+/// The word of the codec, 256 symbols per block.
+type Lanes = U64x4;
+
+pub fn encode(data: &[u8]) -> Vec<WrappedShard> {
+	encode_sub(data, N_VALIDATORS, DATA_SHARDS)
+}
+
+/// Encode `bytes` into `n` shards, identical to the ones of [`super::encode_sub`].
+pub fn encode_sub(bytes: &[u8], n: usize, k: usize) -> Vec<WrappedShard> {
+	let mut shards = vec![WrappedShard::new(vec![0u8; shard_len(bytes.len(), k)]); n];
+	let mut views = shards.iter_mut().map(|shard| shard.as_mut()).collect::<Vec<&mut [u8]>>();
+	encode_into_sub(bytes, &mut views[..], n, k);
+	shards
+}
+
+backend_wrappers!();
+
+/// [`encode_into`] for arbitrary `n` and `k`.
+pub fn encode_into_sub(data: &[u8], shards: &mut [&mut [u8]], n: usize, k: usize) {
+	let shard_len = shard_len(data.len(), k);
+	encode_into_with(data, shards, n, k, shard_len, |data_shards, parity_shards| {
+		encode_parity_sub(data_shards, parity_shards, n, k)
+	});
+}
+
+/// [`encode_parity`] for arbitrary `n` and `k`.
+pub fn encode_parity_sub(data_shards: &[&[u8]], parity_shards: &mut [&mut [u8]], n: usize, k: usize) {
+	let tables = tables();
+
+	assert!(is_power_of_2(n), "Algorithm only works for 2^m sizes for N");
+	assert!(is_power_of_2(k), "Algorithm only works for 2^m sizes for K");
+	assert!(k + k <= n, "Only k/n <= 0.5 is supported for now");
+	assert!(n <= FIELD_SIZE);
+
+	assert_eq!(data_shards.len(), k);
+	assert_eq!(parity_shards.len(), n - k);
+	let shard_len = data_shards[0].len();
+	assert!(data_shards.iter().all(|shard| shard.len() == shard_len));
+	assert!(parity_shards.iter().all(|shard| shard.len() == shard_len));
+
+	let data_shards = data_shards.iter().map(|shard| as_symbols(shard)).collect::<Vec<_>>();
+	let mut parity_shards = parity_shards.iter_mut().map(|shard| as_symbols_mut(shard)).collect::<Vec<_>>();
+
+	let skews = skew_networks(&tables, 0, n - 1);
+	let mut data = vec![Block::<Lanes>::default(); k];
+	let mut codeword = vec![Block::<Lanes>::default(); n];
+	for start in (0..(shard_len / 2)).step_by(Lanes::BITS) {
+		let end = std::cmp::min(start + Lanes::BITS, shard_len / 2);
+		for (block, shard) in data.iter_mut().zip(&data_shards) {
+			*block = Block::pack(shard[start..end].iter().map(|&symbol| GFSymbol::from_le_bytes(symbol)));
+		}
+
+		encode_low(&data[..], &mut codeword[..], &skews[..]);
+
+		for (block, shard) in codeword[k..].iter().zip(&mut parity_shards) {
+			for (t, symbol) in shard[start..end].iter_mut().enumerate() {
+				*symbol = block.symbol(t).to_le_bytes();
+			}
+		}
+	}
+}
+
+pub fn reconstruct(received_shards: Vec<Option<WrappedShard>>) -> Option<Vec<u8>> {
+	reconstruct_sub(received_shards, N_VALIDATORS, DATA_SHARDS)
+}
+
+/// Recover the payload from at least `k` of the `n` shards, see [`super::reconstruct_sub`].
+pub fn reconstruct_sub(received_shards: Vec<Option<WrappedShard>>, n: usize, k: usize) -> Option<Vec<u8>> {
+	reconstruct_shards_sub(&received_shards[..], n, k)
+}
+
+/// [`reconstruct`] of borrowed shards.
+pub(crate) fn reconstruct_shards(received_shards: &mut [Option<WrappedShard>]) -> Option<Vec<u8>> {
+	reconstruct_shards_sub(received_shards, N_VALIDATORS, DATA_SHARDS)
+}
+
+fn reconstruct_shards_sub(received_shards: &[Option<WrappedShard>], n: usize, k: usize) -> Option<Vec<u8>> {
+	let tables = tables();
+
+	if received_shards.len() != n {
+		return None;
+	}
+
+	let shard_len = received_shards.iter().flatten().map(|shard| AsRef::<[u8]>::as_ref(shard).len()).next()?;
+	if received_shards.iter().flatten().any(|shard| AsRef::<[u8]>::as_ref(shard).len() != shard_len) {
+		return None;
+	}
+
+	let erasures = received_shards.iter().map(|x| x.is_none()).collect::<Vec<bool>>();
+	if erasures.iter().filter(|erased| !**erased).count() < k {
+		return None;
+	}
+
+	let decoder = Decoder::new(&tables, erasures);
+	let received_shards = received_shards
+		.iter()
+		.map(|shard| shard.as_ref().map(AsRef::<[[u8; 2]]>::as_ref))
+		.collect::<Vec<_>>();
+
+	let mut recovered = vec![0u8; k * shard_len];
+	let mut codeword = vec![Block::<Lanes>::default(); n];
+	for start in (0..(shard_len / 2)).step_by(Lanes::BITS) {
+		let end = std::cmp::min(start + Lanes::BITS, shard_len / 2);
+		for (block, shard) in codeword.iter_mut().zip(&received_shards) {
+			*block = shard
+				.map(|shard| Block::pack(shard[start..end].iter().map(|&symbol| GFSymbol::from_le_bytes(symbol))))
+				.unwrap_or_default();
+		}
+
+		decoder.decode(&mut codeword[..]);
+
+		for (i, shard) in received_shards.iter().take(k).enumerate() {
+			let recovered = &mut recovered[(i * shard_len)..((i + 1) * shard_len)];
+			let recovered = &mut as_symbols_mut(recovered)[start..end];
+			match shard {
+				Some(shard) => recovered.copy_from_slice(&shard[start..end]),
+				None => {
+					for (t, symbol) in recovered.iter_mut().enumerate() {
+						*symbol = codeword[i].symbol(t).to_le_bytes();
+					}
+				}
+			}
+		}
+	}
+
+	Some(recovered)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn symbols(len: usize, seed: u32) -> Vec<u16> {
+		(0..len as u32).map(|i| ((i ^ seed).wrapping_mul(0x9E37_79B9) >> 16) as u16).collect()
+	}
+
+	fn multiplier_matches_mul_table<W: Word>() {
+		let tables = tables();
+		let input = symbols(W::BITS, 1);
+		let block = Block::<W>::pack(input.iter().copied());
+		for log in (0..=MODULO).step_by(257) {
+			let product = block.mul(&Multiplier::from_log(&tables, log));
+			for (t, &symbol) in input.iter().enumerate() {
+				assert_eq!(product.symbol(t), tables.mul_table(symbol, log), "{} * exp({})", symbol, log);
+			}
+		}
+		let zero = block.mul(&Multiplier::new(0));
+		assert_eq!(zero, Block::default());
+		assert_eq!(Multiplier::new(0).weight(), 0);
+		assert_eq!(Multiplier::new(1).weight(), FIELD_BITS);
+		assert_eq!(block.mul(&Multiplier::new(1)), block);
+	}
+
+	#[test]
+	fn multiplier_matches_mul_table_64() {
+		multiplier_matches_mul_table::<u64>()
+	}
+
+	#[test]
+	fn multiplier_matches_mul_table_256() {
+		multiplier_matches_mul_table::<U64x4>()
+	}
+
+	#[test]
+	fn pack_roundtrip() {
+		let input = symbols(100, 3);
+		let block = Block::<U64x4>::pack(input.iter().copied());
+		assert_eq!((0..100).map(|t| block.symbol(t)).collect::<Vec<_>>(), input);
+		assert!((100..256).all(|t| block.symbol(t) == 0));
+	}
+
+	fn fft_matches_table_fft<W: Word>() {
+		const SIZE: usize = 32;
+		const INDEX: usize = 64;
+		let codewords = (0..W::BITS).map(|t| symbols(SIZE, t as u32)).collect::<Vec<_>>();
+		let original =
+			(0..SIZE).map(|i| Block::<W>::pack(codewords.iter().map(|codeword| codeword[i]))).collect::<Vec<_>>();
+
+		let mut blocks = original.clone();
+		fft(&mut blocks[..], INDEX);
+		for (t, codeword) in codewords.iter().enumerate() {
+			let mut expected = codeword.clone();
+			tables().fft_in_novel_poly_basis(&mut expected[..], SIZE, INDEX);
+			assert_eq!(blocks.iter().map(|block| block.symbol(t)).collect::<Vec<_>>(), expected);
+		}
+		ifft(&mut blocks[..], INDEX);
+		assert_eq!(blocks, original);
+	}
+
+	#[test]
+	fn fft_matches_table_fft_64() {
+		fft_matches_table_fft::<u64>()
+	}
+
+	#[test]
+	fn fft_matches_table_fft_256() {
+		fft_matches_table_fft::<U64x4>()
+	}
+
+	#[test]
+	fn shards_match_parent() {
+		// more than one block per shard, the last one partial
+		let payload = &BYTES[..(4 * 2 * 300)];
+		for (n, k) in [(16, 4), (64, 32), (256, 2)] {
+			let shards = encode_sub(payload, n, k);
+			let bytes =
+				|shards: Vec<WrappedShard>| shards.into_iter().map(WrappedShard::into_inner).collect::<Vec<_>>();
+			assert_eq!(bytes(shards.clone()), bytes(super::super::encode_sub(payload, n, k)));
+
+			let mut received = shards.into_iter().map(Some).collect::<Vec<_>>();
+			for (idx, shard) in received.iter_mut().enumerate() {
+				if idx % (n / k) != n / k - 1 {
+					*shard = None;
+				}
+			}
+			let recovered = reconstruct_sub(received.clone(), n, k).unwrap();
+			assert_eq!(&recovered[..payload.len()], payload);
+			assert_eq!(recovered, super::super::reconstruct_sub(received, n, k).unwrap());
+		}
+	}
+
+	#[test]
+	fn too_few_shards() {
+		let mut received = encode_sub(&BYTES[..64], 16, 4).into_iter().map(Some).collect::<Vec<_>>();
+		received.iter_mut().skip(3).for_each(|shard| *shard = None);
+		assert_eq!(reconstruct_sub(received, 16, 4), None);
+	}
+}
@@ -0,0 +1,364 @@
+// Cantor bases and the novel polynomial basis tables for arbitrary reduction polynomials.
+//
+// Only depends on `std`, so `build.rs` includes this file to emit tables at compile time,
+// while the codecs use it at run time and to check their built in constants.
+//
+// A Cantor basis of GF(2^m) satisfies `b_0 = 1` and `b_i^2 + b_i = b_(i-1)`. It has full length
+// iff `m` is a power of two. Of the two roots `z` and `z + 1`, the one without constant term is
+// taken, which reproduces the bases of the C reference.
+
+// keep the index heavy loops close to the C reference
+#![allow(clippy::needless_range_loop)]
+
+use std::fmt::Write;
+
+/// Degree of the reduction polynomial `modulus`, if supported.
+fn degree(modulus: u64) -> Option<usize> {
+	match modulus.checked_ilog2()? {
+		bits @ 1..=32 => Some(bits as usize),
+		_ => None,
+	}
+}
+
+/// `a * b` modulo `modulus` of degree `bits`.
+fn mul(a: u32, b: u32, modulus: u64, bits: usize) -> u32 {
+	let (mut a, mut b, mut res) = (a as u64, b, 0_u64);
+	while b != 0 {
+		if b & 1 != 0 {
+			res ^= a;
+		}
+		b >>= 1;
+		a <<= 1;
+		if a >> bits != 0 {
+			a ^= modulus;
+		}
+	}
+	res as u32
+}
+
+/// The root of `z^2 + z = c` without constant term, if there is one.
+fn half_trace_root(c: u32, modulus: u64, bits: usize) -> Option<u32> {
+	// z -> z^2 + z is linear with kernel {0, 1}, so the columns of x^1..x^(bits-1) are independent
+	let mut pivots: Vec<Option<(u32, u32)>> = vec![None; bits];
+	for i in 1..bits {
+		let (mut value, mut combination) = (mul(1 << i, 1 << i, modulus, bits) ^ (1 << i), 1_u32 << i);
+		while value != 0 {
+			let high = 31 - value.leading_zeros() as usize;
+			match pivots[high] {
+				Some((pivot, pivot_combination)) => {
+					value ^= pivot;
+					combination ^= pivot_combination;
+				}
+				None => {
+					pivots[high] = Some((value, combination));
+					break;
+				}
+			}
+		}
+	}
+
+	let (mut value, mut root) = (c, 0_u32);
+	while value != 0 {
+		let (pivot, combination) = pivots[31 - value.leading_zeros() as usize]?;
+		value ^= pivot;
+		root ^= combination;
+	}
+	Some(root)
+}
+
+/// The Cantor basis of GF(2^m) for the irreducible `modulus` of degree `m`, in the polynomial basis.
+///
+/// `None` unless `m` is a power of two of at most 32.
+pub fn cantor_basis(modulus: u64) -> Option<Vec<u32>> {
+	let bits = degree(modulus)?;
+	if !bits.is_power_of_two() {
+		return None;
+	}
+	let mut basis = vec![1_u32];
+	while basis.len() < bits {
+		let next = half_trace_root(*basis.last().unwrap(), modulus, bits)?;
+		basis.push(next);
+	}
+	Some(basis)
+}
+
+/// The element with coordinates `value` relative to `images`, i.e. the XOR of `images[i]`
+/// over the set bits `i`.
+///
+/// With `images` a basis this converts to the polynomial basis, with the output of
+/// [`coordinates`] back from it.
+pub fn change_basis(value: u32, images: &[u32]) -> u32 {
+	images.iter().enumerate().filter(|(i, _)| value >> i & 1 != 0).fold(0, |acc, (_, image)| acc ^ image)
+}
+
+/// Coordinates of the monomials `x^i` relative to `basis`, the inverse change of basis.
+///
+/// `None` if `basis` is linearly dependent.
+pub fn coordinates(basis: &[u32]) -> Option<Vec<u32>> {
+	let bits = basis.len();
+	// pivot rows by highest bit, paired with their coordinates
+	let mut pivots: Vec<Option<(u32, u32)>> = vec![None; bits];
+	for (i, &element) in basis.iter().enumerate() {
+		let (mut value, mut combination) = (element, 1_u32 << i);
+		loop {
+			let high = value.checked_ilog2()? as usize;
+			match *pivots.get(high)? {
+				Some((pivot, pivot_combination)) => {
+					value ^= pivot;
+					combination ^= pivot_combination;
+				}
+				None => {
+					pivots[high] = Some((value, combination));
+					break;
+				}
+			}
+		}
+	}
+
+	let monomials = (0..bits).map(|i| {
+		let (mut value, mut combination) = (1_u32 << i, 0_u32);
+		while value != 0 {
+			let (pivot, pivot_combination) = pivots[value.ilog2() as usize].expect("Independent basis spans. qed");
+			value ^= pivot;
+			combination ^= pivot_combination;
+		}
+		combination
+	});
+	Some(monomials.collect())
+}
+
+/// The tables of the novel polynomial basis codec, see `novel_poly_basis`.
+///
+/// Elements are in the representation relative to the basis, logarithms are to the base `x`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tables {
+	pub bits: usize,
+	pub log: Vec<u32>,
+	pub exp: Vec<u32>,
+	/// twisted factors used in FFT
+	pub skew: Vec<u32>,
+	/// factors used in formal derivative
+	pub b: Vec<u32>,
+	/// factors used in the evaluation of the error locator polynomial
+	pub log_walsh: Vec<u32>,
+}
+
+impl Tables {
+	/// Compute the tables for the primitive `modulus` of degree at most 16 and its `base`,
+	/// i.e. the output of [`cantor_basis`].
+	///
+	/// # Panics
+	///
+	/// If the degree of `modulus` is not the length of `base`, below 2 or above 16.
+	/// In particular an empty `base` and the `[1]` of GF(2) are rejected.
+	pub fn new(modulus: u64, base: &[u32]) -> Self {
+		let bits = base.len();
+		assert!(bits >= 2, "Tables need at least GF(2^2)");
+		assert!(bits <= 16, "Tables are limited to GF(2^16)");
+		assert_eq!(degree(modulus), Some(bits), "Base must have one element per bit");
+
+		let size = 1_usize << bits;
+		let modulo = (size - 1) as u32;
+		let mut tables = Self {
+			bits,
+			log: vec![0; size],
+			exp: vec![0; size],
+			skew: vec![0; size - 1],
+			b: vec![0; size >> 1],
+			log_walsh: vec![0; size],
+		};
+
+		// log and exp, following `init` of the C reference
+		let mut state = 1_usize;
+		for i in 0..modulo {
+			tables.exp[state] = i;
+			state <<= 1;
+			if state >> bits != 0 {
+				state ^= modulus as usize;
+			}
+		}
+		tables.exp[0] = modulo;
+
+		for i in 0..bits {
+			for j in 0..(1 << i) {
+				tables.log[j + (1 << i)] = tables.log[j] ^ base[i];
+			}
+		}
+		for i in 0..size {
+			tables.log[i] = tables.exp[tables.log[i] as usize];
+		}
+		for i in 0..size {
+			tables.exp[tables.log[i] as usize] = i as u32;
+		}
+		tables.exp[modulo as usize] = tables.exp[0];
+
+		// skew, b and log_walsh, following `init_dec`
+		let mut base = (1..bits).map(|i| 1_u32 << i).collect::<Vec<_>>();
+		for m in 0..(bits - 1) {
+			let step = 1 << (m + 1);
+			tables.skew[(1 << m) - 1] = 0;
+			for i in m..(bits - 1) {
+				let s = 1 << (i + 1);
+				let mut j = (1 << m) - 1;
+				while j < s {
+					tables.skew[j + s] = tables.skew[j] ^ base[i];
+					j += step;
+				}
+			}
+
+			let idx = tables.mul_table(base[m], tables.log[(base[m] ^ 1) as usize]);
+			base[m] = modulo - tables.log[idx as usize];
+
+			for i in (m + 1)..(bits - 1) {
+				let b = (tables.log[(base[i] ^ 1) as usize] + base[m]) % modulo;
+				base[i] = tables.mul_table(base[i], b);
+			}
+		}
+		for i in 0..(size - 1) {
+			tables.skew[i] = tables.log[tables.skew[i] as usize];
+		}
+
+		base[0] = modulo - base[0];
+		for i in 1..(bits - 1) {
+			base[i] = (modulo - base[i] + base[i - 1]) % modulo;
+		}
+
+		for i in 0..(bits - 1) {
+			let depart = 1 << i;
+			for j in 0..depart {
+				tables.b[j + depart] = (tables.b[j] + base[i]) % modulo;
+			}
+		}
+
+		tables.log_walsh = tables.log.clone();
+		tables.log_walsh[0] = 0;
+		tables.walsh();
+		tables
+	}
+
+	//return a*exp[b]
+	fn mul_table(&self, a: u32, b: u32) -> u32 {
+		if a == 0 {
+			return 0;
+		}
+		let modulo = (1_u32 << self.bits) - 1;
+		let sum = self.log[a as usize] + b;
+		self.exp[((sum & modulo) + (sum >> self.bits)) as usize]
+	}
+
+	//fast Walsh–Hadamard transform of log_walsh over modulo mod
+	fn walsh(&mut self) {
+		let modulo = (1_u32 << self.bits) - 1;
+		let data = &mut self.log_walsh[..];
+		let mut depart_no = 1_usize;
+		while depart_no < data.len() {
+			for j in (0..data.len()).step_by(depart_no << 1) {
+				for i in j..(depart_no + j) {
+					let sum = data[i] + data[i + depart_no];
+					let diff = data[i] + modulo - data[i + depart_no];
+					data[i] = (sum & modulo) + (sum >> self.bits);
+					data[i + depart_no] = (diff & modulo) + (diff >> self.bits);
+				}
+			}
+			depart_no <<= 1;
+		}
+	}
+
+	/// Rust source declaring the tables as constants `LOG`, `EXP`, `SKEW`, `B` and `LOG_WALSH`
+	/// of element type `ty`, for `include!`.
+	pub fn to_rust(&self, ty: &str) -> String {
+		let mut out = String::new();
+		for (name, values) in [
+			("LOG", &self.log),
+			("EXP", &self.exp),
+			("SKEW", &self.skew),
+			("B", &self.b),
+			("LOG_WALSH", &self.log_walsh),
+		] {
+			write!(out, "pub const {}: [{}; {}] = [", name, ty, values.len()).unwrap();
+			for value in values.iter() {
+				write!(out, "{},", value).unwrap();
+			}
+			out.push_str("];\n");
+		}
+		out
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn basis_relation() {
+		for modulus in [0x13, 0x11D, 0x1002D, 0x1002B, 0x1_0040_0007] {
+			let bits = degree(modulus).unwrap();
+			let basis = cantor_basis(modulus).unwrap();
+			assert_eq!(basis.len(), bits);
+			for pair in basis.windows(2) {
+				assert_eq!(mul(pair[1], pair[1], modulus, bits) ^ pair[1], pair[0]);
+				assert_eq!(pair[1] & 1, 0);
+			}
+			// linearly independent, so every element has a unique representation
+			let mut span = vec![false; 1 << bits.min(16)];
+			if bits <= 16 {
+				for combination in 0..(1_usize << bits) {
+					let element = (0..bits).filter(|i| combination >> i & 1 != 0).fold(0, |acc, i| acc ^ basis[i]);
+					assert!(!span[element as usize]);
+					span[element as usize] = true;
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn change_of_basis_roundtrip() {
+		let basis = cantor_basis(0x1002D).unwrap();
+		let inverse = coordinates(&basis[..]).unwrap();
+		for value in (0..=0xFFFF_u32).step_by(7) {
+			assert_eq!(change_basis(change_basis(value, &basis[..]), &inverse[..]), value);
+		}
+		assert_eq!(coordinates(&[1, 2, 3]), None);
+		assert_eq!(coordinates(&[1, 2, 0]), None);
+	}
+
+	#[test]
+	fn unsupported_degrees() {
+		// x^10 + x^3 + 1, only b_0 and b_1 exist
+		assert_eq!(cantor_basis(0x409), None);
+		assert_eq!(cantor_basis(0), None);
+		assert_eq!(cantor_basis(1), None);
+		assert_eq!(cantor_basis(1 << 33 | 1), None);
+	}
+
+	#[test]
+	#[should_panic(expected = "at least GF(2^2)")]
+	fn rejects_empty_base() {
+		Tables::new(0x3, &[]);
+	}
+
+	#[test]
+	#[should_panic(expected = "at least GF(2^2)")]
+	fn rejects_gf2() {
+		assert_eq!(cantor_basis(0x3), Some(vec![1]));
+		Tables::new(0x3, &[1]);
+	}
+
+	#[test]
+	fn smallest_tables() {
+		let tables = Tables::new(0x7, &cantor_basis(0x7).unwrap()[..]);
+		assert_eq!(tables.log.len(), 4);
+		assert_eq!(tables.skew.len(), 3);
+	}
+
+	#[test]
+	fn rust_source() {
+		let tables = Tables::new(0x13, &cantor_basis(0x13).unwrap()[..]);
+		assert_eq!(tables.log.len(), 16);
+		assert_eq!(tables.skew.len(), 15);
+		let source = tables.to_rust("u8");
+		// zero has no logarithm, the C reference stores the modulo
+		assert!(source.starts_with("pub const LOG: [u8; 16] = [15,"));
+		assert_eq!(source.lines().count(), 5);
+	}
+}
@@ -0,0 +1,425 @@
+// The novel polynomial basis codec over GF(2^8), for up to 256 shards.
+//
+// Same algorithm as the GF(2^16) parent module, with the 8 bit configuration of the C reference:
+// reduction polynomial x^8 + x^4 + x^3 + x^2 + 1 and its Cantor basis. Every byte is one symbol,
+// so shards may have any length and the tables of 256 entries stay in L1. The tables are
+// generated at compile time.
+
+// keep the index heavy loops close to the C reference
+#![allow(clippy::needless_range_loop)]
+
+use super::*;
+use alloc::vec;
+use alloc::vec::Vec;
+
+type GFSymbol = u8;
+
+const FIELD_BITS: usize = 8;
+
+#[cfg(all(test, feature = "std"))]
+const GENERATOR: GFSymbol = 0x1D; //x^8 + x^4 + x^3 + x^2 + 1
+
+// Cantor basis
+#[cfg(all(test, feature = "std"))]
+const BASE: [GFSymbol; FIELD_BITS] = [1, 214, 152, 146, 86, 200, 88, 230];
+
+const FIELD_SIZE: usize = 1_usize << FIELD_BITS;
+
+const MODULO: GFSymbol = (FIELD_SIZE - 1) as GFSymbol;
+
+struct Tables {
+	log: [GFSymbol; FIELD_SIZE],
+	exp: [GFSymbol; FIELD_SIZE],
+	// twisted factors used in FFT
+	skew: [GFSymbol; MODULO as usize],
+	// factors used in formal derivative
+	b: [GFSymbol; FIELD_SIZE >> 1],
+	// factors used in the evaluation of the error locator polynomial
+	log_walsh: [GFSymbol; FIELD_SIZE],
+}
+
+mod generated {
+	include!(concat!(env!("OUT_DIR"), "/novel_poly_basis_f2e8.rs"));
+}
+
+// generated by `build.rs` from `BASE`, see `cantor::Tables`
+static TABLES: Tables = Tables {
+	log: generated::LOG,
+	exp: generated::EXP,
+	skew: generated::SKEW,
+	b: generated::B,
+	log_walsh: generated::LOG_WALSH,
+};
+
+fn tables() -> &'static Tables {
+	&TABLES
+}
+
+impl Tables {
+	//return a*exp[b] over GF(2^r)
+	fn mul_table(&self, a: GFSymbol, b: GFSymbol) -> GFSymbol {
+		if a != 0 {
+			let sum = self.log[a as usize] as u32 + b as u32;
+			self.exp[((sum & MODULO as u32) + (sum >> FIELD_BITS)) as usize]
+		} else {
+			0
+		}
+	}
+
+	//IFFT in the proposed basis
+	fn inverse_fft_in_novel_poly_basis(&self, data: &mut [GFSymbol], size: usize, index: usize) {
+		let mut depart_no = 1_usize;
+		while depart_no < size {
+			let mut j = depart_no;
+			while j < size {
+				for i in (j - depart_no)..j {
+					data[i + depart_no] ^= data[i];
+				}
+
+				let skew = self.skew[j + index - 1];
+				if skew != MODULO {
+					for i in (j - depart_no)..j {
+						data[i] ^= self.mul_table(data[i + depart_no], skew);
+					}
+				}
+
+				j += depart_no << 1;
+			}
+			depart_no <<= 1;
+		}
+	}
+
+	//FFT in the proposed basis
+	fn fft_in_novel_poly_basis(&self, data: &mut [GFSymbol], size: usize, index: usize) {
+		let mut depart_no = size >> 1_usize;
+		while depart_no > 0 {
+			let mut j = depart_no;
+			while j < size {
+				let skew = self.skew[j + index - 1];
+				if skew != MODULO {
+					for i in (j - depart_no)..j {
+						data[i] ^= self.mul_table(data[i + depart_no], skew);
+					}
+				}
+				for i in (j - depart_no)..j {
+					data[i + depart_no] ^= data[i];
+				}
+				j += depart_no << 1;
+			}
+			depart_no >>= 1;
+		}
+	}
+
+	// Encoding alg for k/n <= 0.5: message is a power of two
+	fn encode_low(&self, data: &[GFSymbol], k: usize, codeword: &mut [GFSymbol], n: usize) {
+		assert!(k + k <= n);
+		assert_eq!(codeword.len(), n);
+		assert_eq!(data.len(), n);
+
+		codeword.copy_from_slice(data);
+
+		let (codeword_first_k, codeword_skip_first_k) = codeword.split_at_mut(k);
+
+		self.inverse_fft_in_novel_poly_basis(codeword_first_k, k, 0);
+
+		// the first codeword is now the basis for the remaining transforms
+		for shift in (k..n).step_by(k) {
+			let codeword_at_shift = &mut codeword_skip_first_k[(shift - k)..shift];
+			codeword_at_shift.copy_from_slice(codeword_first_k);
+			self.fft_in_novel_poly_basis(codeword_at_shift, k, shift);
+		}
+
+		// restore `M` from the derived ones
+		codeword[0..k].copy_from_slice(&data[0..k]);
+	}
+
+	// Compute the evaluations of the error locator polynomial,
+	// identical for all codewords of a reconstruction
+	fn eval_error_polynomial(&self, erasure: &[bool], log_walsh2: &mut [GFSymbol], n: usize) {
+		let z = core::cmp::min(n, erasure.len());
+		for i in 0..z {
+			log_walsh2[i] = erasure[i] as GFSymbol;
+		}
+		for item in log_walsh2.iter_mut().skip(z) {
+			*item = 0;
+		}
+		walsh(log_walsh2, FIELD_SIZE);
+		for i in 0..n {
+			let tmp = log_walsh2[i] as u32 * self.log_walsh[i] as u32;
+			log_walsh2[i] = (tmp % MODULO as u32) as GFSymbol;
+		}
+		walsh(log_walsh2, FIELD_SIZE);
+		for i in 0..z {
+			if erasure[i] {
+				log_walsh2[i] = MODULO - log_walsh2[i];
+			}
+		}
+	}
+
+	fn decode_main(&self, codeword: &mut [GFSymbol], erasure: &[bool], log_walsh2: &[GFSymbol], n: usize) {
+		assert_eq!(codeword.len(), n);
+		assert_eq!(erasure.len(), n);
+
+		for i in 0..n {
+			codeword[i] = if erasure[i] { 0 } else { self.mul_table(codeword[i], log_walsh2[i]) };
+		}
+		self.inverse_fft_in_novel_poly_basis(codeword, n, 0);
+
+		//formal derivative
+		for i in (0..n).step_by(2) {
+			let b = MODULO - self.b[i >> 1];
+			codeword[i] = self.mul_table(codeword[i], b);
+			codeword[i + 1] = self.mul_table(codeword[i + 1], b);
+		}
+
+		formal_derivative(codeword, n);
+
+		for i in (0..n).step_by(2) {
+			let b = self.b[i >> 1];
+			codeword[i] = self.mul_table(codeword[i], b);
+			codeword[i + 1] = self.mul_table(codeword[i + 1], b);
+		}
+
+		self.fft_in_novel_poly_basis(codeword, n, 0);
+
+		for i in 0..n {
+			codeword[i] = if erasure[i] { self.mul_table(codeword[i], log_walsh2[i]) } else { 0 };
+		}
+	}
+}
+
+//fast Walsh–Hadamard transform over modulo mod
+fn walsh(data: &mut [GFSymbol], size: usize) {
+	let mut depart_no = 1_usize;
+	while depart_no < size {
+		let mut j = 0;
+		let depart_no_next = depart_no << 1;
+		while j < size {
+			for i in j..(depart_no + j) {
+				let sum = data[i] as u32 + data[i + depart_no] as u32;
+				let diff = data[i] as u32 + MODULO as u32 - data[i + depart_no] as u32;
+				data[i] = ((sum & MODULO as u32) + (sum >> FIELD_BITS)) as GFSymbol;
+				data[i + depart_no] = ((diff & MODULO as u32) + (diff >> FIELD_BITS)) as GFSymbol;
+			}
+			j += depart_no_next;
+		}
+		depart_no = depart_no_next;
+	}
+}
+
+//formal derivative of polynomial in the new basis
+fn formal_derivative(cos: &mut [GFSymbol], size: usize) {
+	for i in 1..size {
+		let length = ((i ^ (i - 1)) + 1) >> 1;
+		for j in (i - length)..i {
+			cos[j] ^= cos.get(j + length).copied().unwrap_or_default();
+		}
+	}
+	let mut i = size;
+	while i < FIELD_SIZE && i < cos.len() {
+		for j in 0..size {
+			cos[j] ^= cos.get(j + i).copied().unwrap_or_default();
+		}
+		i <<= 1;
+	}
+}
+
+/// Number of bytes per shard required to spread `payload_len` bytes across `k` data shards.
+///
+/// Unlike [`crate::shard_len`] not rounded up to an even number, symbols are single bytes.
+pub fn shard_len(payload_len: usize, k: usize) -> usize {
+	payload_len.div_ceil(k)
+}
+
+/// Encode `data` into [`N_VALIDATORS`] shards of [`shard_len`] bytes each.
+///
+/// Plain byte vectors, as a [`WrappedShard`] would pad odd length shards to whole `u16` symbols.
+pub fn encode(data: &[u8]) -> Vec<Vec<u8>> {
+	encode_sub(data, N_VALIDATORS, DATA_SHARDS)
+}
+
+/// Encode `bytes` into `n` shards, of which the first `k` are the zero padded payload.
+///
+/// The `i`-th byte of every shard is one codeword. Both `n` and `k` must be powers of two
+/// with `k <= n / 2` and `n <= 256`.
+pub fn encode_sub(bytes: &[u8], n: usize, k: usize) -> Vec<Vec<u8>> {
+	let mut shards = vec![vec![0u8; shard_len(bytes.len(), k)]; n];
+	let mut views = shards.iter_mut().map(|shard| &mut shard[..]).collect::<Vec<&mut [u8]>>();
+	encode_into_sub(bytes, &mut views[..], n, k);
+	shards
+}
+
+backend_wrappers!(in_place);
+
+/// [`encode_into`] for arbitrary `n` and `k`.
+pub fn encode_into_sub(data: &[u8], shards: &mut [&mut [u8]], n: usize, k: usize) {
+	let shard_len = shard_len(data.len(), k);
+	encode_into_with(data, shards, n, k, shard_len, |data_shards, parity_shards| {
+		encode_parity_sub(data_shards, parity_shards, n, k)
+	});
+}
+
+/// [`encode_parity`] for arbitrary `n` and `k`.
+pub fn encode_parity_sub(data_shards: &[&[u8]], parity_shards: &mut [&mut [u8]], n: usize, k: usize) {
+	assert!(is_power_of_2(n), "Algorithm only works for 2^m sizes for N");
+	assert!(is_power_of_2(k), "Algorithm only works for 2^m sizes for K");
+	assert!(k + k <= n, "Only k/n <= 0.5 is supported for now");
+	assert!(n <= FIELD_SIZE);
+
+	assert_eq!(data_shards.len(), k);
+	assert_eq!(parity_shards.len(), n - k);
+	let shard_len = data_shards[0].len();
+	assert!(data_shards.iter().all(|shard| shard.len() == shard_len));
+	assert!(parity_shards.iter().all(|shard| shard.len() == shard_len));
+
+	let tables = tables();
+	let mut data = vec![0 as GFSymbol; n];
+	let mut codeword = vec![0 as GFSymbol; n];
+	for sym_idx in 0..shard_len {
+		for (symbol, shard) in data.iter_mut().zip(data_shards) {
+			*symbol = shard[sym_idx];
+		}
+
+		tables.encode_low(&data[..], k, &mut codeword[..], n);
+
+		// the first `k` are the data shards, which are already in place
+		for (&symbol, shard) in codeword[k..].iter().zip(parity_shards.iter_mut()) {
+			shard[sym_idx] = symbol;
+		}
+	}
+}
+
+pub fn reconstruct(received_shards: Vec<Option<Vec<u8>>>) -> Option<Vec<u8>> {
+	reconstruct_sub(received_shards, N_VALIDATORS, DATA_SHARDS)
+}
+
+/// Recover the payload from at least `k` of the `n` shards.
+///
+/// Yields `None` if too few shards are present or their lengths do not match.
+/// The result contains the zero padding added by [`encode_sub`].
+pub fn reconstruct_sub(received_shards: Vec<Option<Vec<u8>>>, n: usize, k: usize) -> Option<Vec<u8>> {
+	if received_shards.len() != n {
+		return None;
+	}
+
+	let shard_len = received_shards.iter().flatten().map(|shard| shard.len()).next()?;
+	if received_shards.iter().flatten().any(|shard| shard.len() != shard_len) {
+		return None;
+	}
+
+	let erasures = received_shards.iter().map(|x| x.is_none()).collect::<Vec<bool>>();
+	if erasures.iter().filter(|erased| !**erased).count() < k {
+		return None;
+	}
+
+	let tables = tables();
+	let mut log_walsh2 = vec![0 as GFSymbol; FIELD_SIZE];
+	tables.eval_error_polynomial(&erasures[..], &mut log_walsh2[..], FIELD_SIZE);
+
+	let mut recovered = vec![0u8; k * shard_len];
+	let mut codeword = vec![0 as GFSymbol; n];
+	for sym_idx in 0..shard_len {
+		for (symbol, shard) in codeword.iter_mut().zip(&received_shards) {
+			*symbol = shard.as_ref().map(|shard| shard[sym_idx]).unwrap_or_default();
+		}
+
+		let received = codeword.clone();
+		tables.decode_main(&mut codeword[..], &erasures[..], &log_walsh2[..], n);
+
+		// `decode_main` only yields the erased symbols
+		for i in 0..k {
+			recovered[i * shard_len + sym_idx] = if erasures[i] { codeword[i] } else { received[i] };
+		}
+	}
+
+	Some(recovered)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::*;
+
+	#[test]
+	fn tables_match_binaryfield() {
+		let tables = tables();
+		let field = binaryfield::BinaryField::new_primitive(binaryfield::moduli::GF_2_8).unwrap();
+		assert_eq!(field.modulus().value(), 1 << FIELD_BITS | GENERATOR as u64);
+
+		let from_cantor = |cantor: GFSymbol| {
+			let poly = BASE.iter().enumerate().filter(|(i, _)| cantor & (1 << i) != 0).fold(0, |acc, (_, b)| acc ^ b);
+			binaryfield::Element::from(poly as u16)
+		};
+		let x = binaryfield::Element::from(2_u16);
+		for a in 1..=MODULO {
+			let log_a = tables.log[a as usize];
+			assert_eq!(field.exp(x, log_a.into()), from_cantor(a));
+			assert_eq!(tables.exp[log_a as usize], a);
+		}
+	}
+
+	#[test]
+	fn tables_match_generator() {
+		let modulus = 1 << FIELD_BITS | GENERATOR as u64;
+		let base = cantor::cantor_basis(modulus).unwrap();
+		assert_eq!(base, BASE.map(u32::from));
+
+		let generated = cantor::Tables::new(modulus, &base[..]);
+		let tables = tables();
+		let widen = |table: &[GFSymbol]| table.iter().copied().map(u32::from).collect::<Vec<_>>();
+		assert_eq!(generated.log, widen(&tables.log[..]));
+		assert_eq!(generated.exp, widen(&tables.exp[..]));
+		assert_eq!(generated.skew, widen(&tables.skew[..]));
+		assert_eq!(generated.b, widen(&tables.b[..]));
+		assert_eq!(generated.log_walsh, widen(&tables.log_walsh[..]));
+	}
+
+	#[test]
+	fn flt_back_and_forth() {
+		let tables = tables();
+		let expected = (0..64_u32).map(|i| (i * 37 + 11) as GFSymbol).collect::<Vec<_>>();
+		let mut data = expected.clone();
+		tables.fft_in_novel_poly_basis(&mut data, 64, 16);
+		assert_ne!(data, expected);
+		tables.inverse_fft_in_novel_poly_basis(&mut data, 64, 16);
+		assert_eq!(data, expected);
+	}
+
+	#[test]
+	fn odd_shard_lengths() {
+		let payload = &BYTES[..37];
+		for (n, k) in [(16, 4), (32, 16), (256, 128), (256, 2)] {
+			let shards = encode_sub(payload, n, k);
+			assert_eq!(shards[0].len(), shard_len(payload.len(), k));
+			assert_eq!(&shards[0][..], &payload[..shards[0].len()]);
+
+			// keep exactly `k` shards, mostly parity
+			let mut received = shards.into_iter().map(Some).collect::<Vec<_>>();
+			for (idx, shard) in received.iter_mut().enumerate() {
+				if idx % (n / k) != n / k - 1 {
+					*shard = None;
+				}
+			}
+			let recovered = reconstruct_sub(received, n, k).unwrap();
+			assert_eq!(&recovered[..payload.len()], payload);
+		}
+	}
+
+	#[test]
+	fn odd_shard_length_roundtrip() {
+		let payload = &BYTES[..(DATA_SHARDS * 9)];
+		let shards = encode(payload);
+		assert!(shards.iter().all(|shard| shard.len() == 9));
+
+		let mut received = shards.into_iter().map(Some).collect::<Vec<_>>();
+		received.iter_mut().skip(1).step_by(2).for_each(|shard| *shard = None);
+		received[0] = None;
+		assert_eq!(reconstruct(received).unwrap(), payload);
+	}
+
+	#[test]
+	fn too_few_shards() {
+		let mut received = encode_sub(&BYTES[..64], 16, 4).into_iter().map(Some).collect::<Vec<_>>();
+		received.iter_mut().skip(3).for_each(|shard| *shard = None);
+		assert_eq!(reconstruct_sub(received, 16, 4), None);
+	}
+}
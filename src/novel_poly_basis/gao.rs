@@ -0,0 +1,275 @@
+// Errors-and-erasures decoding of the novel polynomial basis code with Gao's algorithm.
+//
+// Gao, "A New Algorithm for Decoding Reed-Solomon Codes," 2003.
+//
+// The `i`-th symbols of all shards are the values `f(w_i)` of one polynomial `f` of degree below `k`.
+// Erasures puncture the code to the `n' = n - e` received points: `g0` vanishes on all of them and
+// `g1` interpolates the received values. The extended Euclidean algorithm on `g0` and `g1`, stopped
+// at the first remainder `g` of degree below `(n' + k) / 2`, yields a `v` with `f = g / v` as long as
+// at most `(n' - k) / 2` values are wrong. The corrupted shards are the ones disagreeing with `f`.
+//
+// The Euclidean algorithm runs in the monomial basis of `binaryfield`, the re-encoding of `f` and the
+// check whether a codeword needs correcting at all use the additive FFTs. Most codewords are intact,
+// for those the erasure decoder already yields a valid codeword and the Euclidean algorithm is skipped.
+
+use super::*;
+use binaryfield::{BinaryField, Element, Poly, SubproductTree};
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+	#[error("Received {received} shards, expected {expected}")]
+	ShardCount { received: usize, expected: usize },
+	#[error("Shards differ in length")]
+	LengthMismatch,
+	#[error("Only {received} shards are present, at least {required} are required")]
+	TooFewShards { received: usize, required: usize },
+	#[error("Symbol {0} has more corrupted shards than correctable")]
+	Uncorrectable(usize),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A payload recovered from shards of which some may have wrong contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Corrected {
+	/// The payload, including the zero padding added by [`encode_sub`].
+	pub payload: Vec<u8>,
+	/// Indices of the present shards with wrong contents, ascending.
+	pub corrupted: Vec<usize>,
+}
+
+/// Number of corrupted shards which can be corrected alongside `erasures` missing ones.
+pub fn capacity(n: usize, k: usize, erasures: usize) -> usize {
+	n.saturating_sub(k + erasures) / 2
+}
+
+pub fn reconstruct(received_shards: Vec<Option<WrappedShard>>) -> Result<Corrected> {
+	reconstruct_sub(received_shards, N_VALIDATORS, DATA_SHARDS)
+}
+
+/// Recover the payload from the `n` shards, of which up to [`capacity`] of the present ones
+/// may be corrupted, and report the corrupted ones.
+///
+/// Every symbol is decoded separately, so the capacity applies per symbol index.
+pub fn reconstruct_sub(received_shards: Vec<Option<WrappedShard>>, n: usize, k: usize) -> Result<Corrected> {
+	assert!(is_power_of_2(n), "Algorithm only works for 2^m sizes for N");
+	assert!(is_power_of_2(k), "Algorithm only works for 2^m sizes for K");
+	assert!(k + k <= n, "Only k/n <= 0.5 is supported for now");
+
+	if received_shards.len() != n {
+		return Err(Error::ShardCount { received: received_shards.len(), expected: n });
+	}
+	let shards = received_shards.iter().map(|shard| shard.as_ref().map(AsRef::<[[u8; 2]]>::as_ref)).collect::<Vec<_>>();
+
+	let present = shards.iter().flatten().count();
+	if present < k {
+		return Err(Error::TooFewShards { received: present, required: k });
+	}
+	let symbols = shards.iter().flatten().map(|shard| shard.len()).next().expect("At least k >= 1 are present. qed");
+	if shards.iter().flatten().any(|shard| shard.len() != symbols) {
+		return Err(Error::LengthMismatch);
+	}
+
+	let field = transform::monomial_field();
+	let erasures = shards.iter().map(Option::is_none).collect::<Vec<bool>>();
+	let decoder = Decoder::new(&field, erasures, k);
+
+	let shard_len = symbols * 2;
+	let mut recovered = vec![0u8; k * shard_len];
+	let mut corrupted = vec![false; n];
+	let mut codeword = vec![0 as GFSymbol; n];
+	for sym_idx in 0..symbols {
+		for (symbol, shard) in codeword.iter_mut().zip(&shards) {
+			*symbol = shard.map(|shard| GFSymbol::from_le_bytes(shard[sym_idx])).unwrap_or_default();
+		}
+
+		let decoded = decoder.decode(&codeword[..]).ok_or(Error::Uncorrectable(sym_idx))?;
+		for (i, corrupted) in corrupted.iter_mut().enumerate() {
+			*corrupted |= !decoder.erasures[i] && decoded[i] != codeword[i];
+		}
+		for (i, symbol) in decoded[..k].iter().enumerate() {
+			let offset = i * shard_len + sym_idx * 2;
+			recovered[offset..(offset + 2)].copy_from_slice(&symbol.to_le_bytes());
+		}
+	}
+
+	let corrupted = corrupted.into_iter().enumerate().filter(|(_, corrupted)| *corrupted).map(|(i, _)| i).collect();
+	Ok(Corrected { payload: recovered, corrupted })
+}
+
+/// The state shared by all codewords of one set of erasures.
+struct Decoder<'a> {
+	tables: Tables<'static>,
+	field: &'a BinaryField,
+	k: usize,
+	erasures: Vec<bool>,
+	// evaluations of the error locator polynomial, for the erasure decoder
+	log_walsh2: Vec<GFSymbol>,
+	// indices of the present shards and the subproduct tree of their points, the root is `g0`
+	present: Vec<usize>,
+	tree: SubproductTree<'a>,
+}
+
+impl<'a> Decoder<'a> {
+	fn new(field: &'a BinaryField, erasures: Vec<bool>, k: usize) -> Self {
+		let tables = tables();
+		let mut log_walsh2 = vec![0 as GFSymbol; FIELD_SIZE];
+		tables.eval_error_polynomial(&erasures[..], &mut log_walsh2[..], FIELD_SIZE);
+
+		let present = (0..erasures.len()).filter(|&i| !erasures[i]).collect::<Vec<_>>();
+		let points = present.iter().map(|&i| transform::to_polynomial_basis(i as GFSymbol)).collect::<Vec<_>>();
+		let tree = SubproductTree::new(field, &points[..]);
+		Self { tables, field, k, erasures, log_walsh2, present, tree }
+	}
+
+	/// The corrected codeword, all `n` symbols, `None` if there are too many errors.
+	fn decode(&self, codeword: &[GFSymbol]) -> Option<Vec<GFSymbol>> {
+		let decoded = self.fill_erasures(codeword);
+		if self.is_codeword(&decoded[..]) {
+			return Some(decoded);
+		}
+
+		let f = self.gao(codeword)?;
+		let decoded = self.encode(f);
+		let errors = self.present.iter().filter(|&&i| decoded[i] != codeword[i]).count();
+		if errors > capacity(codeword.len(), self.k, codeword.len() - self.present.len()) {
+			return None;
+		}
+		Some(decoded)
+	}
+
+	// the output of the plain erasure decoder, assuming all present symbols are correct
+	fn fill_erasures(&self, codeword: &[GFSymbol]) -> Vec<GFSymbol> {
+		let n = codeword.len();
+		let mut decoded = codeword.to_vec();
+		if self.present.len() < n {
+			self.tables.decode_main(&mut decoded[..], self.k, &self.erasures[..], &self.log_walsh2[..], n);
+			for ((symbol, &received), &erased) in decoded.iter_mut().zip(codeword).zip(&self.erasures) {
+				if !erased {
+					*symbol = received;
+				}
+			}
+		}
+		decoded
+	}
+
+	// a codeword iff its polynomial has degree below `k`
+	fn is_codeword(&self, decoded: &[GFSymbol]) -> bool {
+		let mut coeffs = decoded.to_vec();
+		self.tables.inverse_fft_in_novel_poly_basis(&mut coeffs[..], decoded.len(), 0);
+		coeffs[self.k..].iter().all(|&coeff| coeff == 0)
+	}
+
+	/// The message polynomial `f` in the monomial basis, `None` if there are too many errors.
+	fn gao(&self, codeword: &[GFSymbol]) -> Option<Poly<'a>> {
+		let field = self.field;
+		let values = self.present.iter().map(|&i| transform::to_polynomial_basis(codeword[i])).collect::<Vec<_>>();
+		let bound = self.present.len() + self.k;
+
+		// partial extended Euclidean algorithm, only the Bezout coefficient of `g1` is needed
+		let (mut r0, mut r1) = (self.tree.root().clone(), self.tree.interpolate(&values[..]));
+		let (mut v0, mut v1) = (Poly::zero(field), Poly::one(field));
+		while r1.degree().is_some_and(|degree| 2 * degree >= bound) {
+			let (q, r) = r0.divmod(&r1);
+			let v = v0.sub(&q.mul(&v1));
+			r0 = std::mem::replace(&mut r1, r);
+			v0 = std::mem::replace(&mut v1, v);
+		}
+
+		let (f, rem) = r1.divmod(&v1);
+		if !rem.is_zero() || f.degree().is_some_and(|degree| degree >= self.k) {
+			return None;
+		}
+		Some(f)
+	}
+
+	// evaluate `f` at all points, with one FFT per `k` of them like `encode_low`
+	fn encode(&self, f: Poly<'a>) -> Vec<GFSymbol> {
+		let k = self.k;
+		let mut coeffs = f.into_coeffs();
+		coeffs.resize(k, Element::zero());
+		let coeffs = transform::from_monomial(self.field, &coeffs[..]);
+
+		let mut decoded = vec![0 as GFSymbol; self.erasures.len()];
+		for (shift, chunk) in (0..).step_by(k).zip(decoded.chunks_mut(k)) {
+			chunk.copy_from_slice(&coeffs[..]);
+			self.tables.fft_in_novel_poly_basis(chunk, k, shift);
+		}
+		decoded
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn corrupt(shard: &mut WrappedShard, seed: u8) {
+		for (i, byte) in AsMut::<[u8]>::as_mut(shard).iter_mut().enumerate() {
+			*byte ^= (i as u8).wrapping_mul(31) | seed | 1;
+		}
+	}
+
+	fn shards(payload: &[u8], n: usize, k: usize, erased: &[usize], corrupted: &[usize]) -> Vec<Option<WrappedShard>> {
+		let mut shards = super::super::encode_sub(payload, n, k).into_iter().map(Some).collect::<Vec<_>>();
+		for &i in erased {
+			shards[i] = None;
+		}
+		for &i in corrupted {
+			corrupt(shards[i].as_mut().unwrap(), i as u8);
+		}
+		shards
+	}
+
+	#[test]
+	fn corrects_errors_and_erasures() {
+		let payload = &BYTES[..1000];
+		for (n, k, erased, corrupted) in [
+			(16, 4, vec![], vec![0, 3, 7, 9, 12, 15]),
+			(16, 4, vec![1, 2, 5, 6], vec![0, 3, 7, 9]),
+			(16, 8, vec![8], vec![2, 14, 4]),
+			(64, 16, (20..40).collect(), (0..7).chain(50..57).collect()),
+		] {
+			assert_eq!(corrupted.len(), capacity(n, k, erased.len()));
+			let received = shards(payload, n, k, &erased[..], &corrupted[..]);
+			let result = reconstruct_sub(received, n, k).unwrap();
+			assert_eq!(&result.payload[..payload.len()], payload);
+
+			let mut expected = corrupted.clone();
+			expected.sort_unstable();
+			assert_eq!(result.corrupted, expected);
+		}
+	}
+
+	#[test]
+	fn intact_shards_match_erasure_decoder() {
+		let payload = &BYTES[..333];
+		let received = shards(payload, 16, 4, &[0, 1, 2, 4, 8, 9, 10, 11, 13, 14, 15], &[]);
+		let expected = super::super::reconstruct_sub(received.clone(), 16, 4).unwrap();
+		assert_eq!(reconstruct_sub(received, 16, 4), Ok(Corrected { payload: expected, corrupted: vec![] }));
+	}
+
+	#[test]
+	fn too_many_errors() {
+		let payload = &BYTES[..64];
+		// one shard more than `k` detects a single error, but cannot correct it
+		let erased = (0..12).filter(|&i| i != 5).collect::<Vec<_>>();
+		let received = shards(payload, 16, 4, &erased[..], &[13]);
+		assert_eq!(reconstruct_sub(received, 16, 4), Err(Error::Uncorrectable(0)));
+
+		let received = shards(payload, 16, 8, &[], &[0, 1, 2, 3, 4, 5]);
+		assert!(matches!(reconstruct_sub(received, 16, 8), Err(Error::Uncorrectable(_))));
+	}
+
+	#[test]
+	fn malformed_input() {
+		let payload = &BYTES[..64];
+		let received = shards(payload, 16, 4, &(0..13).collect::<Vec<_>>()[..], &[]);
+		assert_eq!(reconstruct_sub(received, 16, 4), Err(Error::TooFewShards { received: 3, required: 4 }));
+
+		let mut received = shards(payload, 16, 4, &[], &[]);
+		received[3] = Some(WrappedShard::new(vec![0; 2]));
+		assert_eq!(reconstruct_sub(received.clone(), 16, 4), Err(Error::LengthMismatch));
+		received.pop();
+		assert_eq!(reconstruct_sub(received, 16, 4), Err(Error::ShardCount { received: 15, expected: 16 }));
+	}
+}
@@ -0,0 +1,404 @@
+// List decoding of the novel polynomial basis code beyond half the minimum distance.
+//
+// Guruswami, Sudan, "Improved Decoding of Reed-Solomon and Algebraic-Geometry Codes," 1999.
+//
+// As in `gao`, the `i`-th symbols of all shards are the values `f(w_i)` of a polynomial `f` of degree
+// below `k`. For the `n'` present points `(x_i, y_i)` a bivariate `Q(x, y)` with a zero of multiplicity
+// `m` at each of them and `(1, k - 1)`-weighted degree `D < t m` is interpolated with Kötter's algorithm.
+// Every `f` agreeing with at least `t` points is a factor `y - f(x)` of `Q`, all of them are found with
+// the Roth–Ruckenstein algorithm. Such a `Q` exists as long as `t^2 > n' (k - 1)`.
+//
+// The lists of all symbols are joined into payloads, a shard is consistent with a payload if every one
+// of its symbols agrees. Candidates are not unique in general, the caller decides, e.g. with a hash.
+
+use super::*;
+use binaryfield::{BinaryField, Element, Poly, SubproductTree};
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+	#[error("Received {received} shards, expected {expected}")]
+	ShardCount { received: usize, expected: usize },
+	#[error("Shards differ in length")]
+	LengthMismatch,
+	#[error("Only {received} shards are present, fewer than the threshold of {threshold}")]
+	TooFewShards { received: usize, threshold: usize },
+	#[error("Threshold {threshold} is below the list decoding bound of {minimum}")]
+	ThresholdTooLow { threshold: usize, minimum: usize },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A payload consistent with at least the threshold of the received shards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+	/// The payload, including the zero padding added by [`encode_sub`].
+	pub payload: Vec<u8>,
+	/// Indices of the present shards matching the encoding of the payload, ascending.
+	pub agreeing: Vec<usize>,
+}
+
+// the weight of `y`, codes with `k = 1` are decoded as the ones with `k = 2` containing them
+fn weight(k: usize) -> usize {
+	(k - 1).max(1)
+}
+
+/// The smallest threshold accepted with `present` shards, `floor(sqrt(present (k - 1))) + 1`.
+pub fn min_threshold(present: usize, k: usize) -> usize {
+	let product = present * weight(k);
+	let mut root = (product as f64).sqrt() as usize;
+	while root * root > product {
+		root -= 1;
+	}
+	while (root + 1) * (root + 1) <= product {
+		root += 1;
+	}
+	root + 1
+}
+
+pub fn list_decode(received_shards: Vec<Option<WrappedShard>>, threshold: usize) -> Result<Vec<Candidate>> {
+	list_decode_sub(received_shards, N_VALIDATORS, DATA_SHARDS, threshold)
+}
+
+/// All payloads whose encoding matches at least `threshold` of the `n` received shards.
+///
+/// `threshold` must be at least [`min_threshold`] of the present shards. The closer to it,
+/// the higher the multiplicities and the slower the decoding.
+pub fn list_decode_sub(
+	received_shards: Vec<Option<WrappedShard>>,
+	n: usize,
+	k: usize,
+	threshold: usize,
+) -> Result<Vec<Candidate>> {
+	assert!(is_power_of_2(n), "Algorithm only works for 2^m sizes for N");
+	assert!(is_power_of_2(k), "Algorithm only works for 2^m sizes for K");
+	assert!(k + k <= n, "Only k/n <= 0.5 is supported for now");
+
+	if received_shards.len() != n {
+		return Err(Error::ShardCount { received: received_shards.len(), expected: n });
+	}
+	let shards = received_shards.iter().map(|shard| shard.as_ref().map(AsRef::<[[u8; 2]]>::as_ref)).collect::<Vec<_>>();
+
+	let present = (0..n).filter(|&i| shards[i].is_some()).collect::<Vec<_>>();
+	if present.len() < threshold {
+		return Err(Error::TooFewShards { received: present.len(), threshold });
+	}
+	let minimum = min_threshold(present.len(), k);
+	if threshold < minimum {
+		return Err(Error::ThresholdTooLow { threshold, minimum });
+	}
+	let symbols = shards[present[0]].expect("At least the threshold are present. qed").len();
+	if shards.iter().flatten().any(|shard| shard.len() != symbols) {
+		return Err(Error::LengthMismatch);
+	}
+
+	let field = transform::monomial_field();
+	let decoder = Decoder::new(&field, n, k, &present[..], threshold);
+
+	// the payloads consistent with the symbols so far, and the shards agreeing with them
+	let shard_len = symbols * 2;
+	let mut partial = vec![(vec![0u8; k * shard_len], shards.iter().map(Option::is_some).collect::<Vec<_>>())];
+	let mut received = vec![0 as GFSymbol; present.len()];
+	for sym_idx in 0..symbols {
+		for (symbol, &i) in received.iter_mut().zip(&present) {
+			*symbol = GFSymbol::from_le_bytes(shards[i].expect("Only present shards. qed")[sym_idx]);
+		}
+		let list = decoder.decode(&received[..]);
+
+		let mut next = Vec::with_capacity(partial.len());
+		for (payload, agreeing) in partial.iter() {
+			for codeword in list.iter() {
+				let mut agreeing = agreeing.clone();
+				for (&i, &symbol) in present.iter().zip(&received) {
+					agreeing[i] &= codeword[i] == symbol;
+				}
+				if agreeing.iter().filter(|&&agrees| agrees).count() < threshold {
+					continue;
+				}
+				let mut payload = payload.clone();
+				for (i, symbol) in codeword[..k].iter().enumerate() {
+					let offset = i * shard_len + sym_idx * 2;
+					payload[offset..(offset + 2)].copy_from_slice(&symbol.to_le_bytes());
+				}
+				next.push((payload, agreeing));
+			}
+		}
+		partial = next;
+	}
+
+	Ok(partial
+		.into_iter()
+		.map(|(payload, agreeing)| {
+			let agreeing = (0..n).filter(|&i| agreeing[i]).collect();
+			Candidate { payload, agreeing }
+		})
+		.collect())
+}
+
+/// [`list_decode_sub`], keeping only the candidates whose padded payload passes `check`,
+/// i.e. a comparison with a known hash.
+pub fn list_decode_checked(
+	received_shards: Vec<Option<WrappedShard>>,
+	n: usize,
+	k: usize,
+	threshold: usize,
+	mut check: impl FnMut(&[u8]) -> bool,
+) -> Result<Vec<Candidate>> {
+	let mut candidates = list_decode_sub(received_shards, n, k, threshold)?;
+	candidates.retain(|candidate| check(&candidate.payload[..]));
+	Ok(candidates)
+}
+
+/// A bivariate polynomial, the coefficients of the powers of `y` as polynomials in `x`.
+type Bivariate<'a> = Vec<Poly<'a>>;
+
+/// The state shared by all symbols of one set of present shards.
+struct Decoder<'a> {
+	field: &'a BinaryField,
+	k: usize,
+	multiplicity: usize,
+	// the maximal weighted degree of `Q`
+	degree: usize,
+	// the points of the present shards
+	points: Vec<Element>,
+	// the subproduct tree of all `n` points, to re-encode the candidates
+	tree: SubproductTree<'a>,
+}
+
+impl<'a> Decoder<'a> {
+	fn new(field: &'a BinaryField, n: usize, k: usize, present: &[usize], threshold: usize) -> Self {
+		let w = weight(k);
+		// the smallest multiplicity with more monomials of weighted degree `t m - 1` than constraints
+		let monomials = |degree: usize| (0..=(degree / w)).map(|b| degree - w * b + 1).sum::<usize>();
+		let multiplicity = (1..)
+			.find(|&m| monomials(threshold * m - 1) > present.len() * m * (m + 1) / 2)
+			.expect("Holds for large multiplicities above the bound. qed");
+
+		let points = present.iter().map(|&i| transform::to_polynomial_basis(i as GFSymbol)).collect::<Vec<_>>();
+		let all = (0..n).map(|i| transform::to_polynomial_basis(i as GFSymbol)).collect::<Vec<_>>();
+		let tree = SubproductTree::new(field, &all[..]);
+		Self { field, k, multiplicity, degree: threshold * multiplicity - 1, points, tree }
+	}
+
+	/// The codewords, all `n` symbols, of the polynomials which may agree with the threshold of `received`.
+	fn decode(&self, received: &[GFSymbol]) -> Vec<Vec<GFSymbol>> {
+		let values = received.iter().map(|&symbol| transform::to_polynomial_basis(symbol)).collect::<Vec<_>>();
+		let q = self.interpolate(&values[..]);
+
+		let mut found = vec![];
+		self.roth_ruckenstein(q, vec![], &mut found);
+		found
+			.into_iter()
+			.map(|f| {
+				let values = self.tree.evaluate(&Poly::new(self.field, f));
+				values.into_iter().map(transform::from_polynomial_basis).collect()
+			})
+			.collect()
+	}
+
+	fn weighted_degree(&self, q: &Bivariate<'a>) -> Option<usize> {
+		let w = weight(self.k);
+		q.iter().enumerate().filter_map(|(b, coeff)| coeff.degree().map(|degree| degree + w * b)).max()
+	}
+
+	/// The Hasse derivative `D_(a, b) Q` at `(x, y)`, the coefficient of `x^a y^b` in `Q(x + x_0, y + y_0)`.
+	fn hasse(&self, q: &Bivariate<'a>, (a, b): (usize, usize), (x, y): (Element, Element)) -> Element {
+		let field = self.field;
+		// binomial coefficients are odd iff the bits of the lower index are a subset, by Lucas' theorem
+		let derivative = |poly: &Poly<'a>| {
+			let mut power = Element::one();
+			let mut sum = Element::zero();
+			for (i, &coeff) in poly.coeffs().iter().enumerate().skip(a) {
+				if i & a == a {
+					sum ^= field.mul(coeff, power);
+				}
+				power = field.mul(power, x);
+			}
+			sum
+		};
+		let mut power = Element::one();
+		let mut sum = Element::zero();
+		for (j, coeff) in q.iter().enumerate().skip(b) {
+			if j & b == b {
+				sum ^= field.mul(derivative(coeff), power);
+			}
+			power = field.mul(power, y);
+		}
+		sum
+	}
+
+	/// The `Q` of minimal weighted degree with a zero of multiplicity `m` at every `(x_i, values[i])`,
+	/// with Kötter's algorithm.
+	fn interpolate(&self, values: &[Element]) -> Bivariate<'a> {
+		let field = self.field;
+		let m = self.multiplicity;
+		// one polynomial per leading power of `y`, starting out as `y^j`
+		let mut polys = (0..=(self.degree / weight(self.k)))
+			.map(|j| {
+				let mut q = vec![Poly::zero(field); j + 1];
+				q[j] = Poly::one(field);
+				q
+			})
+			.collect::<Vec<Bivariate<'a>>>();
+
+		for (&x, &y) in self.points.iter().zip(values) {
+			// each constraint only depends on earlier ones, so the multiplication by `x - x_i` preserves them
+			for b in 0..m {
+				for a in 0..(m - b) {
+					let discrepancies = polys.iter().map(|q| self.hasse(q, (a, b), (x, y))).collect::<Vec<_>>();
+					let pivot = (0..polys.len())
+						.filter(|&j| discrepancies[j] != Element::zero())
+						.min_by_key(|&j| self.weighted_degree(&polys[j]));
+					let pivot = match pivot {
+						Some(pivot) => pivot,
+						None => continue,
+					};
+
+					let pivot_poly = polys[pivot].clone();
+					for (j, q) in polys.iter_mut().enumerate() {
+						if j == pivot || discrepancies[j] == Element::zero() {
+							continue;
+						}
+						for (l, coeff) in q.iter_mut().enumerate() {
+							*coeff = coeff.scale(discrepancies[pivot]);
+							if let Some(p) = pivot_poly.get(l) {
+								*coeff = coeff.add(&p.scale(discrepancies[j]));
+							}
+						}
+						q.extend(pivot_poly.iter().skip(q.len()).map(|p| p.scale(discrepancies[j])));
+					}
+					for coeff in polys[pivot].iter_mut() {
+						*coeff = coeff.shl(1).add(&coeff.scale(x));
+					}
+				}
+			}
+		}
+
+		polys.into_iter().min_by_key(|q| self.weighted_degree(q)).expect("At least `y^0` is present. qed")
+	}
+
+	/// Collect the monomial coefficients of all `f` of degree below `k` with `y - f(x)` dividing `q`,
+	/// `prefix` are the ones found so far.
+	fn roth_ruckenstein(&self, q: Bivariate<'a>, prefix: Vec<Element>, found: &mut Vec<Vec<Element>>) {
+		let field = self.field;
+		// divide by the largest power of `x` dividing `q`
+		let shift = q
+			.iter()
+			.filter_map(|coeff| coeff.coeffs().iter().position(|&c| c != Element::zero()))
+			.min()
+			.expect("Substitutions keep `Q` non zero. qed");
+		let q = q
+			.into_iter()
+			.map(|coeff| Poly::new(field, coeff.coeffs().iter().skip(shift).copied().collect()))
+			.collect::<Vec<_>>();
+
+		let at_zero = Poly::new(field, q.iter().map(|coeff| coeff.coeff(0)).collect());
+		for root in at_zero.roots() {
+			let mut prefix = prefix.clone();
+			prefix.push(root);
+			if prefix.len() == self.k {
+				found.push(prefix);
+				continue;
+			}
+
+			// Q(x, root + x y), (root + x y)^j expands to the sum over the `l` with an odd binomial coefficient
+			let substituted = (0..q.len())
+				.map(|l| {
+					(l..q.len())
+						.filter(|&j| j & l == l)
+						.fold(Poly::zero(field), |acc, j| acc.add(&q[j].scale(field.exp(root, (j - l) as u64))))
+						.shl(l)
+				})
+				.collect();
+			self.roth_ruckenstein(substituted, prefix, found);
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use sha2::Digest;
+
+	fn corrupt(shard: &mut WrappedShard, seed: u8) {
+		for (i, byte) in AsMut::<[u8]>::as_mut(shard).iter_mut().enumerate() {
+			*byte ^= (i as u8).wrapping_mul(29) | seed | 1;
+		}
+	}
+
+	fn shards(payload: &[u8], n: usize, k: usize, erased: &[usize], corrupted: &[usize]) -> Vec<Option<WrappedShard>> {
+		let mut shards = super::super::encode_sub(payload, n, k).into_iter().map(Some).collect::<Vec<_>>();
+		for &i in erased {
+			shards[i] = None;
+		}
+		for &i in corrupted {
+			corrupt(shards[i].as_mut().unwrap(), i as u8);
+		}
+		shards
+	}
+
+	#[test]
+	fn thresholds() {
+		assert_eq!(min_threshold(16, 4), 7);
+		assert_eq!(min_threshold(16, 5), 9);
+		assert_eq!(min_threshold(12, 4), 7);
+		assert_eq!(min_threshold(9, 1), 4);
+	}
+
+	#[test]
+	fn beyond_unique_decoding() {
+		let payload = &BYTES[..500];
+		for (n, k, erased, corrupted, threshold) in [
+			(16, 4, vec![], vec![1, 4, 6, 9, 10, 12, 13, 15], 8),
+			(16, 4, vec![2, 3], vec![0, 6, 8, 10, 11, 14], 8),
+			(32, 8, vec![], (0..14).collect(), 18),
+		] {
+			let present = n - erased.len();
+			assert!(corrupted.len() > gao::capacity(n, k, erased.len()));
+			assert_eq!(present - corrupted.len(), threshold);
+
+			let received = shards(payload, n, k, &erased[..], &corrupted[..]);
+			let candidates = list_decode_sub(received, n, k, threshold).unwrap();
+			let expected = (0..n).filter(|i| !erased.contains(i) && !corrupted.contains(i)).collect::<Vec<_>>();
+			let candidate = candidates.iter().find(|candidate| &candidate.payload[..payload.len()] == payload).unwrap();
+			assert_eq!(candidate.agreeing, expected);
+		}
+	}
+
+	#[test]
+	fn ambiguous_shards() {
+		// half the shards of one payload, half of another one of the same length
+		let (a, b) = (&BYTES[..300], &BYTES[300..600]);
+		let mut received = shards(a, 16, 4, &[], &[]);
+		received.splice(8.., shards(b, 16, 4, &[], &[]).into_iter().skip(8));
+
+		let mut candidates = list_decode_sub(received.clone(), 16, 4, 8).unwrap();
+		assert_eq!(candidates.len(), 2);
+		candidates.sort_by_key(|candidate| candidate.agreeing[0]);
+		for (candidate, (payload, agreeing)) in candidates.iter().zip([(a, 0..8), (b, 8..16)]) {
+			assert_eq!(&candidate.payload[..payload.len()], payload);
+			assert_eq!(candidate.agreeing, agreeing.collect::<Vec<_>>());
+		}
+
+		// the hash of the payload picks the right one
+		let digest = sha2::Sha256::digest(b);
+		let check = |payload: &[u8]| sha2::Sha256::digest(&payload[..b.len()]) == digest;
+		assert_eq!(list_decode_checked(received, 16, 4, 8, check), Ok(vec![candidates[1].clone()]));
+	}
+
+	#[test]
+	fn malformed_input() {
+		let payload = &BYTES[..64];
+		let received = shards(payload, 16, 4, &(0..10).collect::<Vec<_>>()[..], &[]);
+		assert_eq!(list_decode_sub(received, 16, 4, 7), Err(Error::TooFewShards { received: 6, threshold: 7 }));
+
+		let received = shards(payload, 16, 4, &[], &[]);
+		let too_low = Err(Error::ThresholdTooLow { threshold: 6, minimum: 7 });
+		assert_eq!(list_decode_sub(received.clone(), 16, 4, 6), too_low);
+
+		let mut received = received;
+		received[3] = Some(WrappedShard::new(vec![0; 2]));
+		assert_eq!(list_decode_sub(received.clone(), 16, 4, 8), Err(Error::LengthMismatch));
+		received.pop();
+		assert_eq!(list_decode_sub(received, 16, 4, 8), Err(Error::ShardCount { received: 15, expected: 16 }));
+	}
+}
@@ -0,0 +1,270 @@
+// The transforms of the codec as a general purpose polynomial toolkit over GF(2^16).
+//
+// Elements are `u16` in the Cantor basis representation of the codec tables, so the `i`-th
+// evaluation point `w_i` is the element with representation `i`. A polynomial of degree below
+// `2^m` is given by its `2^m` coefficients in the Lin–Chung–Han basis `X_j = prod_{i in j} W_i`,
+// the products of the normalized subspace vanishing polynomials `W_i` over the bits of `j`.
+//
+// `fft` and `ifft` convert between coefficients and the values at `w_index..w_(index + 2^m)` in
+// O(n log n). For the monomial coefficients of `binaryfield`, see `to_monomial`/`from_monomial`.
+
+use super::*;
+use binaryfield::{BinaryField, Element, Poly};
+
+fn check_size(len: usize, index: usize) {
+	assert!(is_power_of_2(len), "Transform size must be a power of two");
+	assert_eq!(index % len, 0, "Offset must be a multiple of the transform size");
+	assert!(index + len <= FIELD_SIZE, "Points must lie within the field");
+}
+
+/// Evaluate the polynomial with novel basis coefficients `data` at `w_index, .., w_(index + len - 1)`,
+/// in place.
+///
+/// # Panics
+///
+/// If the length is not a power of two, `index` not a multiple of it, or the points exceed the field.
+pub fn fft(data: &mut [u16], index: usize) {
+	check_size(data.len(), index);
+	tables().fft_in_novel_poly_basis(data, data.len(), index);
+}
+
+/// Inverse of [`fft`]: the novel basis coefficients of the polynomial of degree below `len`
+/// taking the values `data` at `w_index, .., w_(index + len - 1)`, in place.
+///
+/// # Panics
+///
+/// Same as [`fft`].
+pub fn ifft(data: &mut [u16], index: usize) {
+	check_size(data.len(), index);
+	tables().inverse_fft_in_novel_poly_basis(data, data.len(), index);
+}
+
+/// Fast Walsh–Hadamard transform of `data` modulo `2^16 - 1`, in place.
+///
+/// Applied to discrete logarithms, it turns the error locator into a pointwise product.
+/// The transform is its own inverse up to a factor of `len`.
+pub fn walsh(data: &mut [u16]) {
+	assert!(is_power_of_2(data.len()) && data.len() <= FIELD_SIZE);
+	super::walsh(data, data.len())
+}
+
+/// The formal derivative of the polynomial with novel basis coefficients `coeffs`, in place.
+///
+/// # Panics
+///
+/// If the length is not a power of two of at least 2.
+pub fn derivative(coeffs: &mut [u16]) {
+	let n = coeffs.len();
+	assert!(is_power_of_2(n) && (2..=FIELD_SIZE).contains(&n));
+	let tables = tables();
+
+	// the same steps as `decode_main`, the factors `B` normalize the basis
+	for i in (0..n).step_by(2) {
+		let b = MODULO - tables.b[i >> 1];
+		coeffs[i] = tables.mul_table(coeffs[i], b);
+		coeffs[i + 1] = tables.mul_table(coeffs[i + 1], b);
+	}
+	// `formal_derivative` adds the derivative onto the polynomial, which the decoder only
+	// evaluates where the polynomial vanishes, so remove the polynomial itself
+	let polynomial = coeffs.to_vec();
+	formal_derivative(coeffs, n);
+	for (coeff, original) in coeffs.iter_mut().zip(polynomial) {
+		*coeff ^= original;
+	}
+	for i in (0..n).step_by(2) {
+		let b = tables.b[i >> 1];
+		coeffs[i] = tables.mul_table(coeffs[i], b);
+		coeffs[i + 1] = tables.mul_table(coeffs[i + 1], b);
+	}
+}
+
+/// Product of two polynomials in the novel basis, of length the next power of two
+/// of at least `a.len() + b.len() - 1`.
+pub fn mul(a: &[u16], b: &[u16]) -> Vec<u16> {
+	if a.is_empty() || b.is_empty() {
+		return vec![];
+	}
+	let n = (a.len() + b.len() - 1).next_power_of_two();
+	assert!(n <= FIELD_SIZE, "Product exceeds the number of field elements");
+
+	let mut a = a.to_vec();
+	let mut b = b.to_vec();
+	a.resize(n, 0);
+	b.resize(n, 0);
+	fft(&mut a[..], 0);
+	fft(&mut b[..], 0);
+	let tables = tables();
+	for (a, b) in a.iter_mut().zip(b) {
+		*a = if b == 0 { 0 } else { tables.mul_table(*a, tables.log[b as usize]) };
+	}
+	ifft(&mut a[..], 0);
+	a
+}
+
+/// The field of [`binaryfield`] isomorphic to the codec field, `x` corresponds to `x`.
+pub fn monomial_field() -> BinaryField {
+	BinaryField::new_primitive(1 << FIELD_BITS | GENERATOR as u64).expect("The codec modulus is primitive. qed")
+}
+
+/// `a` in the polynomial basis of [`monomial_field`].
+pub fn to_polynomial_basis(a: u16) -> Element {
+	Element::from(cantor::change_basis(a as u32, &basis_change().from_cantor[..]))
+}
+
+/// Inverse of [`to_polynomial_basis`].
+pub fn from_polynomial_basis(a: Element) -> u16 {
+	cantor::change_basis(a.value(), &basis_change().to_cantor[..]) as u16
+}
+
+fn check_field(field: &BinaryField) {
+	assert_eq!(field.modulus().value(), 1 << FIELD_BITS | GENERATOR as u64, "Field must be `monomial_field`");
+	assert_eq!(field.generator(), Element::from(2_u16), "Field must be `monomial_field`");
+}
+
+/// Monomial coefficients of the polynomial with novel basis coefficients `coeffs`, over
+/// [`monomial_field`], with as many coefficients as `coeffs`.
+///
+/// Evaluates via [`fft`] and interpolates with a subproduct tree, in O(n log^2 n).
+pub fn to_monomial(field: &BinaryField, coeffs: &[u16]) -> Vec<Element> {
+	check_field(field);
+	if coeffs.is_empty() {
+		return vec![];
+	}
+	// `X_j` has degree `j`, so the padding does not raise the degree
+	let mut values = coeffs.to_vec();
+	values.resize(coeffs.len().next_power_of_two(), 0);
+	fft(&mut values[..], 0);
+
+	let xs = (0..values.len()).map(|i| to_polynomial_basis(i as u16)).collect::<Vec<_>>();
+	let ys = values.into_iter().map(to_polynomial_basis).collect::<Vec<_>>();
+	let mut monomial = Poly::interpolate(field, &xs[..], &ys[..]).into_coeffs();
+	monomial.resize(coeffs.len(), Element::zero());
+	monomial
+}
+
+/// Novel basis coefficients of the polynomial with monomial coefficients `coeffs` over
+/// [`monomial_field`], padded to the next power of two.
+///
+/// Evaluates with a subproduct tree and interpolates via [`ifft`], in O(n log^2 n).
+pub fn from_monomial(field: &BinaryField, coeffs: &[Element]) -> Vec<u16> {
+	check_field(field);
+	if coeffs.is_empty() {
+		return vec![];
+	}
+	let n = coeffs.len().next_power_of_two();
+	let xs = (0..n).map(|i| to_polynomial_basis(i as u16)).collect::<Vec<_>>();
+	let values = Poly::new(field, coeffs.to_vec()).evaluate_many(&xs[..]);
+	let mut values = values.into_iter().map(from_polynomial_basis).collect::<Vec<_>>();
+	ifft(&mut values[..], 0);
+	values
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn coeffs(len: usize, seed: u32) -> Vec<u16> {
+		(0..len as u32).map(|i| ((i ^ seed).wrapping_mul(0x9E37_79B9) >> 16) as u16).collect()
+	}
+
+	#[test]
+	fn fft_evaluates() {
+		let field = monomial_field();
+		let novel = coeffs(64, 1);
+		let poly = Poly::new(&field, to_monomial(&field, &novel[..]));
+		for index in [0, 64, 1024, 65536 - 64] {
+			let mut values = novel.clone();
+			fft(&mut values[..], index);
+			for (i, &value) in values.iter().enumerate() {
+				let x = to_polynomial_basis((index + i) as u16);
+				assert_eq!(to_polynomial_basis(value), poly.eval(x));
+			}
+			ifft(&mut values[..], index);
+			assert_eq!(values, novel);
+		}
+	}
+
+	#[test]
+	fn basis_polynomials() {
+		let field = monomial_field();
+		let point = |i: usize| to_polynomial_basis(i as u16);
+		// normalized vanishing polynomials of the subspaces spanned by w_1, w_2, .., w_(2^(i-1))
+		let normalized = (0..4)
+			.map(|i| {
+				let w = Poly::from_roots(&field, &(0..(1 << i)).map(point).collect::<Vec<_>>()[..]);
+				w.scale(field.inv(w.eval(point(1 << i))))
+			})
+			.collect::<Vec<_>>();
+
+		for j in 0..16 {
+			let mut unit = [0_u16; 16];
+			unit[j] = 1;
+			let expected = (0..4)
+				.filter(|i| j >> i & 1 != 0)
+				.fold(Poly::one(&field), |acc, i| acc.mul(&normalized[i]));
+			assert_eq!(Poly::new(&field, to_monomial(&field, &unit[..])), expected, "X_{}", j);
+		}
+	}
+
+	#[test]
+	fn monomial_roundtrip() {
+		let field = monomial_field();
+		for len in [1, 2, 16, 256] {
+			let novel = coeffs(len, 7);
+			assert_eq!(from_monomial(&field, &to_monomial(&field, &novel[..])[..]), novel);
+		}
+		let novel = coeffs(100, 7);
+		let mut padded = from_monomial(&field, &to_monomial(&field, &novel[..])[..]);
+		assert_eq!(padded.split_off(100), vec![0; 28]);
+		assert_eq!(padded, novel);
+		// the first basis polynomials, X_0 = 1 and X_1 = W_0 = x / w_1 = x
+		assert_eq!(to_monomial(&field, &[1, 0]), vec![Element::one(), Element::zero()]);
+		assert_eq!(to_monomial(&field, &[0, 1]), vec![Element::zero(), Element::one()]);
+		assert_eq!(from_monomial(&field, &[Element::zero(), Element::zero(), Element::one()]).len(), 4);
+	}
+
+	#[test]
+	fn element_conversion() {
+		let field = monomial_field();
+		let cantor = CantorField::new();
+		for a in (1..=u16::MAX).step_by(101) {
+			assert_eq!(from_polynomial_basis(to_polynomial_basis(a)), a);
+			let b = a.wrapping_mul(31) | 1;
+			let product = galois::GaloisField::mul(&cantor, a, b);
+			assert_eq!(to_polynomial_basis(product), field.mul(to_polynomial_basis(a), to_polynomial_basis(b)));
+		}
+	}
+
+	#[test]
+	fn derivative_matches_monomial() {
+		let field = monomial_field();
+		for len in [2, 8, 128] {
+			let mut novel = coeffs(len, 3);
+			let expected = Poly::new(&field, to_monomial(&field, &novel[..])).derivative();
+			derivative(&mut novel[..]);
+			assert_eq!(Poly::new(&field, to_monomial(&field, &novel[..])), expected);
+		}
+	}
+
+	#[test]
+	fn mul_matches_monomial() {
+		let field = monomial_field();
+		let (a, b) = (coeffs(100, 5), coeffs(29, 11));
+		let product = mul(&a[..], &b[..]);
+		assert_eq!(product.len(), 128);
+
+		let expected = Poly::new(&field, to_monomial(&field, &a[..])).mul(&Poly::new(&field, to_monomial(&field, &b[..])));
+		assert_eq!(Poly::new(&field, to_monomial(&field, &product[..])), expected);
+	}
+
+	#[test]
+	fn walsh_is_involution_up_to_scale() {
+		let original = coeffs(32, 9).into_iter().map(|c| c % MODULO).collect::<Vec<_>>();
+		let mut data = original.clone();
+		walsh(&mut data[..]);
+		walsh(&mut data[..]);
+		for (&twice, &value) in data.iter().zip(&original) {
+			assert_eq!(twice as u32 % MODULO as u32, value as u32 * 32 % MODULO as u32);
+		}
+	}
+}
@@ -0,0 +1,377 @@
+// The surface of Polkadot's `polkadot-erasure-coding`, on top of the codecs of this crate.
+//
+// Chunks are handed out one per validator, any `recovery_threshold` of them reconstruct the data.
+// The data is framed like the SCALE encoding of a `Vec<u8>`, a compact length prefix, so the
+// reconstruction strips the zero padding. The branches commit to the chunks with the sha2 tree
+// of `merkle` instead of a trie of BLAKE2 hashes.
+//
+// `status_quo` takes the parameters as they are. `novel_poly_basis` needs powers of two, so it
+// encodes into the next power of two of shards with the previous power of two of data shards,
+// of which the first `n_validators` are handed out.
+//
+// Upstream is generic over `T: Encode` and `T: Decode` and erasure codes the SCALE encoding of `T`.
+// This module takes and yields raw bytes instead, framed as upstream frames `data.to_vec()`,
+// so an empty `data` is coded as the single byte `0x00`. Callers of other types SCALE encode
+// before `obtain_chunks` and decode after `reconstruct` on their own.
+
+use super::*;
+use merkle::{Hash, MerkleTree};
+use std::convert::{TryFrom, TryInto};
+
+/// The maximum number of validators, the number of elements of GF(2^16).
+pub const MAX_VALIDATORS: usize = 1 << 16;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+	#[error("There are too many validators")]
+	TooManyValidators,
+	#[error("Expected at least 2 validators")]
+	NotEnoughValidators,
+	/// Not raised, chunks are placed by index and the count is only checked against the threshold.
+	#[error("Validator count mismatches between encoding and decoding")]
+	WrongValidatorCount,
+	#[error("Not enough chunks present")]
+	NotEnoughChunks,
+	/// Not raised, chunks beyond `n_validators` are ignored.
+	#[error("Too many chunks present")]
+	TooManyChunks,
+	#[error("Chunks are not uniform, mismatch in length or are zero sized")]
+	NonUniformChunks,
+	#[error("Uneven length is not valid for field GF(2^16)")]
+	UnevenLength,
+	#[error("Chunk is out of bounds: {chunk_index} not included in 0..{n_validators}")]
+	ChunkIndexOutOfBounds { chunk_index: usize, n_validators: usize },
+	#[error("Reconstructed payload invalid")]
+	BadPayload,
+	#[error("Invalid branch proof")]
+	InvalidBranchProof,
+	#[error("Branch is out of bounds")]
+	BranchOutOfBounds,
+	#[error("An unknown error has appeared when reconstructing erasure code chunks")]
+	UnknownReconstruction,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The codec behind the chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+	StatusQuo,
+	NovelPolyBasis,
+}
+
+impl Default for Backend {
+	/// [`Backend::NovelPolyBasis`], unless the `polkadot-status-quo` feature is enabled.
+	fn default() -> Self {
+		if cfg!(feature = "polkadot-status-quo") {
+			Backend::StatusQuo
+		} else {
+			Backend::NovelPolyBasis
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CodeParams {
+	data_shards: usize,
+	parity_shards: usize,
+}
+
+fn code_params(n_validators: usize) -> Result<CodeParams> {
+	if n_validators > MAX_VALIDATORS {
+		return Err(Error::TooManyValidators);
+	}
+	if n_validators < 2 {
+		return Err(Error::NotEnoughValidators);
+	}
+	let n_faulty = (n_validators - 1) / 3;
+	let n_good = n_validators - n_faulty;
+	Ok(CodeParams { data_shards: n_faulty + 1, parity_shards: n_good - 1 })
+}
+
+/// The number of chunks required to reconstruct, `f + 1` of `n_validators = 3f + 1`.
+pub fn recovery_threshold(n_validators: usize) -> Result<usize> {
+	code_params(n_validators).map(|params| params.data_shards)
+}
+
+// the shard counts of `novel_poly_basis`, a superset of the chunks with at most as many data shards
+fn novel_params(n_validators: usize, params: CodeParams) -> (usize, usize) {
+	(n_validators.next_power_of_two(), 1 << params.data_shards.ilog2())
+}
+
+// the SCALE compact encoding of `len`
+fn encode_len(len: usize) -> Vec<u8> {
+	match len as u64 {
+		len @ 0..=0x3F => vec![(len as u8) << 2],
+		len @ 0x40..=0x3FFF => ((len as u16) << 2 | 0b01).to_le_bytes().to_vec(),
+		len @ 0x4000..=0x3FFF_FFFF => ((len as u32) << 2 | 0b10).to_le_bytes().to_vec(),
+		len => {
+			let bytes = 8 - len.leading_zeros() as usize / 8;
+			let mut out = vec![((bytes - 4) as u8) << 2 | 0b11];
+			out.extend_from_slice(&len.to_le_bytes()[..bytes]);
+			out
+		}
+	}
+}
+
+// the length and the length of its encoding, `None` unless canonical
+fn decode_len(data: &[u8]) -> Option<(usize, usize)> {
+	let first = *data.first()?;
+	let (len, prefix, min) = match first & 0b11 {
+		0b00 => (first as u64 >> 2, 1, 0),
+		0b01 => (u16::from_le_bytes(data.get(..2)?.try_into().ok()?) as u64 >> 2, 2, 0x40),
+		0b10 => (u32::from_le_bytes(data.get(..4)?.try_into().ok()?) as u64 >> 2, 4, 0x4000),
+		_ => {
+			let bytes = (first >> 2) as usize + 4;
+			if bytes > 8 {
+				return None;
+			}
+			let mut le = [0u8; 8];
+			le[..bytes].copy_from_slice(data.get(1..(1 + bytes))?);
+			(u64::from_le_bytes(le), 1 + bytes, 0x4000_0000_u64.max(1 << ((bytes - 1) * 8)))
+		}
+	};
+	if len < min {
+		return None;
+	}
+	Some((usize::try_from(len).ok()?, prefix))
+}
+
+impl Backend {
+	/// Split `data` into one chunk per validator.
+	pub fn obtain_chunks(self, n_validators: usize, data: &[u8]) -> Result<Vec<Vec<u8>>> {
+		let params = code_params(n_validators)?;
+		let mut framed = encode_len(data.len());
+		framed.extend_from_slice(data);
+
+		let shards = match self {
+			Backend::StatusQuo => status_quo::encode_sub(&framed[..], n_validators, params.data_shards),
+			Backend::NovelPolyBasis => {
+				let (n, k) = novel_params(n_validators, params);
+				novel_poly_basis::encode_sub(&framed[..], n, k)
+			}
+		};
+		Ok(shards.into_iter().take(n_validators).map(WrappedShard::into_inner).collect())
+	}
+
+	/// Reconstruct the data from at least [`recovery_threshold`] chunks, given with their indices.
+	pub fn reconstruct<'a, I>(self, n_validators: usize, chunks: I) -> Result<Vec<u8>>
+	where
+		I: IntoIterator<Item = (&'a [u8], usize)>,
+	{
+		let params = code_params(n_validators)?;
+		let mut received_shards = vec![None; n_validators];
+		let mut shard_len = None;
+		for (chunk_data, chunk_index) in chunks.into_iter().take(n_validators) {
+			if chunk_index >= n_validators {
+				return Err(Error::ChunkIndexOutOfBounds { chunk_index, n_validators });
+			}
+			let shard_len = *shard_len.get_or_insert(chunk_data.len());
+			if shard_len % 2 != 0 {
+				return Err(Error::UnevenLength);
+			}
+			if shard_len != chunk_data.len() || shard_len == 0 {
+				return Err(Error::NonUniformChunks);
+			}
+			received_shards[chunk_index] = Some(WrappedShard::new(chunk_data.to_vec()));
+		}
+		if received_shards.iter().flatten().count() < params.data_shards {
+			return Err(Error::NotEnoughChunks);
+		}
+
+		let payload = match self {
+			Backend::StatusQuo => status_quo::reconstruct_sub(received_shards, n_validators, params.data_shards),
+			Backend::NovelPolyBasis => {
+				let (n, k) = novel_params(n_validators, params);
+				received_shards.resize(n, None);
+				novel_poly_basis::reconstruct_sub(received_shards, n, k)
+			}
+		};
+		let payload = payload.ok_or(Error::UnknownReconstruction)?;
+
+		let (len, prefix) = decode_len(&payload[..]).ok_or(Error::BadPayload)?;
+		let data = payload.get(prefix..).and_then(|data| data.get(..len)).ok_or(Error::BadPayload)?;
+		Ok(data.to_vec())
+	}
+}
+
+/// Split `data` into one chunk per validator, with the [`Backend::default`].
+pub fn obtain_chunks(n_validators: usize, data: &[u8]) -> Result<Vec<Vec<u8>>> {
+	Backend::default().obtain_chunks(n_validators, data)
+}
+
+/// Reconstruct the data from at least [`recovery_threshold`] chunks, with the [`Backend::default`].
+pub fn reconstruct<'a, I>(n_validators: usize, chunks: I) -> Result<Vec<u8>>
+where
+	I: IntoIterator<Item = (&'a [u8], usize)>,
+{
+	Backend::default().reconstruct(n_validators, chunks)
+}
+
+/// The inclusion proof of a chunk, its hash and the path to the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proof {
+	chunk_hash: Hash,
+	path: merkle::Proof,
+}
+
+impl Proof {
+	pub fn path(&self) -> &merkle::Proof {
+		&self.path
+	}
+}
+
+/// The hash of a chunk, as yielded by [`branch_hash`].
+pub fn chunk_hash(chunk: &[u8]) -> Hash {
+	merkle::hash_leaf(chunk)
+}
+
+/// Iterator over the chunks with their proofs, see [`branches`].
+pub struct Branches<'a, I> {
+	tree: MerkleTree,
+	chunks: &'a [I],
+	current_pos: usize,
+}
+
+impl<'a, I: AsRef<[u8]>> Branches<'a, I> {
+	/// The root all branches lead to.
+	pub fn root(&self) -> Hash {
+		self.tree.root()
+	}
+}
+
+impl<'a, I: AsRef<[u8]>> Iterator for Branches<'a, I> {
+	type Item = (Proof, &'a [u8]);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let chunk = self.chunks.get(self.current_pos)?.as_ref();
+		let proof = Proof { chunk_hash: chunk_hash(chunk), path: self.tree.proof(self.current_pos) };
+		self.current_pos += 1;
+		Some((proof, chunk))
+	}
+}
+
+/// Commit to `chunks`, yielding every chunk with its proof.
+///
+/// # Panics
+///
+/// If there are no chunks.
+pub fn branches<I: AsRef<[u8]>>(chunks: &[I]) -> Branches<'_, I> {
+	Branches { tree: MerkleTree::new(chunks), chunks, current_pos: 0 }
+}
+
+/// Verify `proof` for the chunk at `index` against `root`, yielding the hash of the chunk.
+pub fn branch_hash(root: &Hash, proof: &Proof, index: usize) -> Result<Hash> {
+	match merkle::root_from_leaf(index, proof.chunk_hash, &proof.path) {
+		None => Err(Error::BranchOutOfBounds),
+		Some(computed) if &computed == root => Ok(proof.chunk_hash),
+		Some(_) => Err(Error::InvalidBranchProof),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	const BACKENDS: [Backend; 2] = [Backend::StatusQuo, Backend::NovelPolyBasis];
+
+	#[test]
+	fn length_prefix() {
+		for len in [0, 1, 63, 64, 0x3FFF, 0x4000, 0x3FFF_FFFF, 0x4000_0000, 1 << 40, usize::MAX] {
+			let encoded = encode_len(len);
+			assert_eq!(decode_len(&encoded[..]), Some((len, encoded.len())), "{}", len);
+		}
+		assert_eq!(encode_len(1), vec![0x04]);
+		assert_eq!(encode_len(64), vec![0x01, 0x01]);
+		// not canonical
+		assert_eq!(decode_len(&[0x01, 0x00]), None);
+		assert_eq!(decode_len(&[0x03, 0xFF, 0xFF, 0xFF, 0x00]), None);
+		assert_eq!(decode_len(&[0x02, 0x00]), None);
+	}
+
+	#[test]
+	fn params() {
+		assert_eq!(recovery_threshold(2), Ok(1));
+		assert_eq!(recovery_threshold(4), Ok(2));
+		assert_eq!(recovery_threshold(10), Ok(4));
+		assert_eq!(recovery_threshold(100), Ok(34));
+		assert_eq!(recovery_threshold(1), Err(Error::NotEnoughValidators));
+		assert_eq!(recovery_threshold(MAX_VALIDATORS + 1), Err(Error::TooManyValidators));
+		for n_validators in 2..=200 {
+			let params = code_params(n_validators).unwrap();
+			let (n, k) = novel_params(n_validators, params);
+			assert!(k <= params.data_shards && k + k <= n && n_validators <= n);
+		}
+	}
+
+	#[test]
+	fn roundtrip() {
+		let data = &BYTES[..777];
+		for backend in BACKENDS {
+			for n_validators in [2, 3, 10, 16, 100] {
+				let chunks = backend.obtain_chunks(n_validators, data).unwrap();
+				assert_eq!(chunks.len(), n_validators);
+
+				// the last `recovery_threshold` chunks, in reverse
+				let threshold = recovery_threshold(n_validators).unwrap();
+				let available = chunks.iter().enumerate().rev().take(threshold).map(|(i, chunk)| (&chunk[..], i));
+				assert_eq!(backend.reconstruct(n_validators, available).unwrap(), data);
+			}
+		}
+	}
+
+	#[test]
+	fn empty_data() {
+		for backend in BACKENDS {
+			let chunks = backend.obtain_chunks(10, &[]).unwrap();
+			assert_eq!(chunks.len(), 10);
+			let available = chunks.iter().enumerate().skip(4).map(|(i, chunk)| (&chunk[..], i));
+			assert_eq!(backend.reconstruct(10, available).unwrap(), Vec::<u8>::new());
+		}
+	}
+
+	#[test]
+	fn reconstruct_errors() {
+		for backend in BACKENDS {
+			let chunks = backend.obtain_chunks(10, &BYTES[..100]).unwrap();
+			let with_indices = || chunks.iter().enumerate().map(|(i, chunk)| (&chunk[..], i));
+
+			assert_eq!(backend.obtain_chunks(1, &BYTES[..100]), Err(Error::NotEnoughValidators));
+			assert_eq!(backend.reconstruct(10, with_indices().take(3)), Err(Error::NotEnoughChunks));
+			let out_of_bounds = with_indices().map(|(chunk, i)| (chunk, i + 1));
+			assert_eq!(
+				backend.reconstruct(10, out_of_bounds),
+				Err(Error::ChunkIndexOutOfBounds { chunk_index: 10, n_validators: 10 })
+			);
+			let short = with_indices().map(|(chunk, i)| (if i == 2 { &chunk[2..] } else { chunk }, i));
+			assert_eq!(backend.reconstruct(10, short), Err(Error::NonUniformChunks));
+			let uneven = with_indices().map(|(chunk, i)| (&chunk[1..], i));
+			assert_eq!(backend.reconstruct(10, uneven), Err(Error::UnevenLength));
+			assert_eq!(backend.reconstruct(10, std::iter::once((&[][..], 0))), Err(Error::NonUniformChunks));
+
+			// all zero chunks encode the empty data, others need not carry a valid length
+			let zeros = vec![vec![0u8; chunks[0].len()]; 10];
+			let zeros = zeros.iter().enumerate().map(|(i, chunk)| (&chunk[..], i));
+			assert_eq!(backend.reconstruct(10, zeros).unwrap(), Vec::<u8>::new());
+			let garbage = vec![vec![0xFFu8; chunks[0].len()]; 10];
+			let garbage = garbage.iter().enumerate().map(|(i, chunk)| (&chunk[..], i));
+			assert_eq!(backend.reconstruct(10, garbage), Err(Error::BadPayload));
+		}
+	}
+
+	#[test]
+	fn branches_verify() {
+		let chunks = obtain_chunks(10, &BYTES[..500]).unwrap();
+		let branches = branches(&chunks[..]);
+		let root = branches.root();
+
+		let proofs = branches.map(|(proof, chunk)| (proof, chunk.to_vec())).collect::<Vec<_>>();
+		assert_eq!(proofs.len(), 10);
+		for (index, (proof, chunk)) in proofs.iter().enumerate() {
+			assert_eq!(branch_hash(&root, proof, index), Ok(chunk_hash(&chunk[..])));
+			assert_eq!(branch_hash(&root, proof, (index + 1) % 10), Err(Error::InvalidBranchProof));
+			assert_eq!(branch_hash(&root, proof, 16), Err(Error::BranchOutOfBounds));
+			assert_eq!(branch_hash(&[0u8; 32], proof, index), Err(Error::InvalidBranchProof));
+		}
+	}
+}
@@ -0,0 +1,274 @@
+// Incremental reconstruction, for shards that arrive one at a time.
+//
+// Shards are validated against the code parameters as they come in,
+// the payload is recovered as soon as `DATA_SHARDS` of them are present.
+// Anything arriving afterwards can only be checked against the re-encoded shards.
+
+use super::*;
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+	#[error("Shard index {index} is out of bounds, there are only {n} shards")]
+	IndexOutOfBounds { index: usize, n: usize },
+	#[error("Shard {index} has length {len}, expected {expected}")]
+	LengthMismatch { index: usize, len: usize, expected: usize },
+	#[error("Shard {0} is empty")]
+	Empty(usize),
+	#[error("Shard {0} was already received")]
+	Duplicate(usize),
+	#[error("Reconstruction failed despite sufficient shards")]
+	ReconstructionFailed,
+	#[error("Verification requires the payload to be reconstructed first")]
+	Incomplete,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Outcome of adding a shard to a [`Reconstructor`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum Progress<'a> {
+	/// More shards are required.
+	Pending { received: usize, required: usize },
+	/// The shard completed the set, borrows the reconstructed payload including its zero padding.
+	Complete(&'a [u8]),
+	/// The payload was reconstructed before, the shard was not used.
+	AlreadyComplete,
+}
+
+/// A [`Reconstructor`] driven by a backend's plain `encode` and `reconstruct` functions.
+pub type BackendReconstructor =
+	Reconstructor<fn(&[u8]) -> Vec<WrappedShard>, fn(Vec<Option<WrappedShard>>) -> Option<Vec<u8>>>;
+
+/// Collects shards one by one and reconstructs the payload once enough arrived.
+pub struct Reconstructor<E, R> {
+	encode: E,
+	reconstruct: R,
+	shards: Vec<Option<WrappedShard>>,
+	shard_len: Option<usize>,
+	received: usize,
+	payload: Option<Vec<u8>>,
+	// lazily re-encoded from `payload`, for verification only
+	encoded: Option<Vec<WrappedShard>>,
+}
+
+impl<E, R> Reconstructor<E, R>
+where
+	E: Fn(&[u8]) -> Vec<WrappedShard>,
+	R: Fn(Vec<Option<WrappedShard>>) -> Option<Vec<u8>>,
+{
+	/// Create a reconstructor for the `N_VALIDATORS` shards produced by `encode`.
+	pub fn new(encode: E, reconstruct: R) -> Self {
+		Self {
+			encode,
+			reconstruct,
+			shards: vec![None; N_VALIDATORS],
+			shard_len: None,
+			received: 0,
+			payload: None,
+			encoded: None,
+		}
+	}
+
+	/// Number of distinct shards received so far.
+	pub fn received(&self) -> usize {
+		self.received
+	}
+
+	/// Number of shards required to reconstruct.
+	pub fn required(&self) -> usize {
+		DATA_SHARDS
+	}
+
+	/// Whether the payload has been reconstructed.
+	pub fn is_complete(&self) -> bool {
+		self.payload.is_some()
+	}
+
+	/// The reconstructed payload, if complete.
+	pub fn payload(&self) -> Option<&[u8]> {
+		self.payload.as_deref()
+	}
+
+	/// The length all shards must have, set by the first accepted shard.
+	pub fn shard_len(&self) -> Option<usize> {
+		self.shard_len
+	}
+
+	/// Drop the shard with `index` again, e.g. once it turned out to be bogus.
+	///
+	/// Once no shards are left, the next one pushed sets the shard length anew.
+	pub fn discard(&mut self, index: usize) -> Option<WrappedShard> {
+		let shard = self.shards.get_mut(index)?.take()?;
+		self.received -= 1;
+		if self.received == 0 {
+			self.shard_len = None;
+		}
+		Some(shard)
+	}
+
+	fn validate(&self, index: usize, shard: &WrappedShard) -> Result<()> {
+		if index >= self.shards.len() {
+			return Err(Error::IndexOutOfBounds { index, n: self.shards.len() });
+		}
+		let len = AsRef::<[u8]>::as_ref(shard).len();
+		if len == 0 {
+			return Err(Error::Empty(index));
+		}
+		match self.shard_len {
+			Some(expected) if expected != len => Err(Error::LengthMismatch { index, len, expected }),
+			_ => Ok(()),
+		}
+	}
+
+	/// Add the shard with `index`.
+	///
+	/// Reconstructs and yields the payload with the `DATA_SHARDS`-th valid shard. If that fails,
+	/// all shards are kept and every further shard retries, unless bogus ones are [`discard`]ed.
+	/// The first accepted shard fixes the length of all others, see [`Reconstructor::shard_len`].
+	///
+	/// [`discard`]: Reconstructor::discard
+	pub fn push(&mut self, index: usize, shard: WrappedShard) -> Result<Progress<'_>> {
+		self.validate(index, &shard)?;
+
+		if self.is_complete() {
+			return Ok(Progress::AlreadyComplete);
+		}
+		if self.shards[index].is_some() {
+			return Err(Error::Duplicate(index));
+		}
+
+		self.shard_len = Some(AsRef::<[u8]>::as_ref(&shard).len());
+		self.shards[index] = Some(shard);
+		self.received += 1;
+
+		if self.received < self.required() {
+			return Ok(Progress::Pending { received: self.received, required: self.required() });
+		}
+
+		let payload = (self.reconstruct)(self.shards.clone()).ok_or(Error::ReconstructionFailed)?;
+		// only the payload is needed from here on
+		self.shards.iter_mut().for_each(|shard| *shard = None);
+		Ok(Progress::Complete(self.payload.insert(payload)))
+	}
+
+	/// Check a shard against the reconstructed payload.
+	///
+	/// Yields `true` if the shard is exactly the one the payload encodes to at `index`.
+	pub fn verify(&mut self, index: usize, shard: &WrappedShard) -> Result<bool> {
+		self.validate(index, shard)?;
+
+		let payload = self.payload.as_ref().ok_or(Error::Incomplete)?;
+		let encode = &self.encode;
+		let encoded = self.encoded.get_or_insert_with(|| encode(&payload[..]));
+
+		Ok(AsRef::<[u8]>::as_ref(&encoded[index]) == AsRef::<[u8]>::as_ref(shard))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn incremental<E, R>(encode: E, reconstruct: R)
+	where
+		E: Fn(&[u8]) -> Vec<WrappedShard> + Copy,
+		R: Fn(Vec<Option<WrappedShard>>) -> Option<Vec<u8>>,
+	{
+		let payload = &BYTES[..100];
+		let shards = encode(payload);
+
+		let mut reconstructor = Reconstructor::new(encode, reconstruct);
+		let order = [13, 2, 7, 11, 0, 5];
+
+		for (i, &idx) in order[..(DATA_SHARDS - 1)].iter().enumerate() {
+			assert_eq!(
+				reconstructor.push(idx, shards[idx].clone()),
+				Ok(Progress::Pending { received: i + 1, required: DATA_SHARDS })
+			);
+		}
+		assert_eq!(reconstructor.push(order[0], shards[order[0]].clone()), Err(Error::Duplicate(order[0])));
+		assert_eq!(reconstructor.verify(0, &shards[0]), Err(Error::Incomplete));
+
+		let idx = order[DATA_SHARDS - 1];
+		match reconstructor.push(idx, shards[idx].clone()).unwrap() {
+			Progress::Complete(recovered) => assert_eq!(&recovered[..payload.len()], payload),
+			other => panic!("Expected completion, got {:?}", other),
+		}
+		assert!(reconstructor.is_complete());
+
+		let idx = order[DATA_SHARDS];
+		assert_eq!(reconstructor.push(idx, shards[idx].clone()), Ok(Progress::AlreadyComplete));
+		assert_eq!(reconstructor.verify(idx, &shards[idx]), Ok(true));
+
+		let mut tampered = shards[idx].clone();
+		AsMut::<[u8]>::as_mut(&mut tampered)[0] ^= 0xFF;
+		assert_eq!(reconstructor.verify(idx, &tampered), Ok(false));
+	}
+
+	#[test]
+	fn status_quo_incremental() {
+		incremental(status_quo::encode, status_quo::reconstruct);
+	}
+
+	#[test]
+	fn novel_poly_basis_incremental() {
+		incremental(novel_poly_basis::encode, novel_poly_basis::reconstruct);
+	}
+
+	#[test]
+	fn rejects_bad_shards() {
+		let shards = status_quo::encode(&BYTES[..64]);
+		let mut reconstructor = Reconstructor::new(status_quo::encode, status_quo::reconstruct);
+
+		assert_eq!(
+			reconstructor.push(N_VALIDATORS, shards[0].clone()),
+			Err(Error::IndexOutOfBounds { index: N_VALIDATORS, n: N_VALIDATORS })
+		);
+		assert_eq!(reconstructor.push(1, WrappedShard::new(vec![])), Err(Error::Empty(1)));
+
+		reconstructor.push(0, shards[0].clone()).unwrap();
+		assert_eq!(
+			reconstructor.push(1, WrappedShard::new(vec![0u8; 4])),
+			Err(Error::LengthMismatch { index: 1, len: 4, expected: 16 })
+		);
+		assert_eq!(reconstructor.received(), 1);
+
+		// a bogus first shard can be discarded, which frees the shard length again
+		let mut reconstructor = Reconstructor::new(status_quo::encode, status_quo::reconstruct);
+		reconstructor.push(0, WrappedShard::new(vec![0u8; 4])).unwrap();
+		assert_eq!(reconstructor.shard_len(), Some(4));
+		assert!(reconstructor.discard(0).is_some());
+		assert!(reconstructor.discard(0).is_none());
+		assert_eq!(reconstructor.shard_len(), None);
+		assert!(reconstructor.push(0, shards[0].clone()).is_ok());
+	}
+
+	#[test]
+	fn failed_reconstruction_keeps_shards() {
+		let payload = &BYTES[..100];
+		let shards = status_quo::encode(payload);
+		let attempts = std::cell::Cell::new(0);
+		let mut reconstructor = Reconstructor::new(status_quo::encode, |received| {
+			attempts.set(attempts.get() + 1);
+			if attempts.get() == 1 {
+				None
+			} else {
+				status_quo::reconstruct(received)
+			}
+		});
+
+		for (idx, shard) in shards.iter().enumerate().take(DATA_SHARDS - 1) {
+			reconstructor.push(idx, shard.clone()).unwrap();
+		}
+		assert_eq!(reconstructor.push(DATA_SHARDS, shards[DATA_SHARDS].clone()), Err(Error::ReconstructionFailed));
+		assert!(!reconstructor.is_complete());
+		assert_eq!(reconstructor.received(), DATA_SHARDS);
+
+		// the retry still has all the shards
+		match reconstructor.push(N_VALIDATORS - 1, shards[N_VALIDATORS - 1].clone()).unwrap() {
+			Progress::Complete(recovered) => assert_eq!(&recovered[..payload.len()], payload),
+			other => panic!("Expected completion, got {:?}", other),
+		}
+		assert_eq!(attempts.get(), 2);
+	}
+}
@@ -1,19 +1,16 @@
 use super::*;
 
-use reed_solomon_erasure::galois_16::ReedSolomon;
+use reed_solomon_erasure::galois_16::{self, ReedSolomon};
 
 pub fn to_shards(payload: &[u8]) -> Vec<WrappedShard> {
-	let base_len = payload.len();
-
-	// how many bytes we actually need.
-	let needed_shard_len = (base_len + DATA_SHARDS - 1) / DATA_SHARDS;
-
-	// round up, ing GF(2^16) there are only 2 byte values, so each shard must a multiple of 2
-	let needed_shard_len = needed_shard_len + (needed_shard_len & 0x01);
+	to_shards_sub(payload, N_VALIDATORS, DATA_SHARDS)
+}
 
-	let shard_len = needed_shard_len;
+/// The `n` shards of which the first `k` hold the zero padded `payload`, the others are zero.
+pub fn to_shards_sub(payload: &[u8], n: usize, k: usize) -> Vec<WrappedShard> {
+	let shard_len = shard_len(payload.len(), k);
 
-	let mut shards = vec![WrappedShard::new(vec![0u8; shard_len]); N_VALIDATORS];
+	let mut shards = vec![WrappedShard::new(vec![0u8; shard_len]); n];
 	for (data_chunk, blank_shard) in payload.chunks(shard_len).zip(&mut shards) {
 		// fill the empty shards with the corresponding piece of the payload,
 		// zero-padded to fit in the shards.
@@ -30,33 +27,161 @@ pub fn rs() -> ReedSolomon {
 }
 
 pub fn encode(data: &[u8]) -> Vec<WrappedShard> {
-	let encoder = rs();
-	let mut shards = to_shards(data);
+	encode_sub(data, N_VALIDATORS, DATA_SHARDS)
+}
+
+/// Encode `data` into `n` shards, of which the first `k` are the zero padded payload.
+///
+/// Any `0 < k < n` with `n` up to `2^16` is supported.
+pub fn encode_sub(data: &[u8], n: usize, k: usize) -> Vec<WrappedShard> {
+	let encoder = ReedSolomon::new(k, n - k).expect("Callers pass supported shard counts. qed");
+	let mut shards = to_shards_sub(data, n, k);
 	encoder.encode(&mut shards).unwrap();
 	shards
 }
 
-pub fn reconstruct(mut received_shards: Vec<Option<WrappedShard>>) -> Option<Vec<u8>> {
-	let r = rs();
+backend_wrappers!();
 
-	// Try to reconstruct missing shards
-	r.reconstruct_data(&mut received_shards).expect("Sufficient shards must be received. qed");
+/// [`encode_into`] for any `n` and `k` [`encode_sub`] supports.
+pub fn encode_into_sub(data: &[u8], shards: &mut [&mut [u8]], n: usize, k: usize) {
+	let shard_len = shard_len(data.len(), k);
+	encode_into_with(data, shards, n, k, shard_len, |data_shards, parity_shards| {
+		encode_parity_sub(data_shards, parity_shards, n, k)
+	});
+}
 
-	// Convert back to normal shard arrangement
-	// let l = received_shards.len();
+/// [`encode_parity`] for any `n` and `k` [`encode_sub`] supports, the shards must have an even length.
+pub fn encode_parity_sub(data_shards: &[&[u8]], parity_shards: &mut [&mut [u8]], n: usize, k: usize) {
+	assert_eq!(data_shards.len(), k);
+	assert_eq!(parity_shards.len(), n - k);
 
-	// let result_data_shards= received_shards
-	// 	.into_iter()
-	// 	.filter_map(|x| x)
-	// 	.collect::<Vec<WrappedShard>>();
+	let encoder = ReedSolomon::new(k, n - k).expect("Callers pass supported shard counts. qed");
+	let data_shards = data_shards.iter().map(|shard| as_symbols(shard)).collect::<Vec<_>>();
+	let mut parity_shards = parity_shards.iter_mut().map(|shard| as_symbols_mut(shard)).collect::<Vec<_>>();
+	encoder.encode_sep(&data_shards[..], &mut parity_shards[..]).expect("Shard count and sizes were checked. qed");
+}
+
+pub fn reconstruct(received_shards: Vec<Option<WrappedShard>>) -> Option<Vec<u8>> {
+	reconstruct_sub(received_shards, N_VALIDATORS, DATA_SHARDS)
+}
 
-	let result = received_shards.into_iter().filter_map(|x| x).take(DATA_SHARDS).fold(
-		Vec::with_capacity(12 << 20),
-		|mut acc, x| {
-			acc.extend_from_slice(x.into_inner().as_slice());
-			acc
-		},
-	);
+/// Recover the payload from at least `k` of the `n` shards.
+///
+/// Yields `None` if too few shards are present or their lengths do not match.
+/// The result contains the zero padding added by [`encode_sub`].
+pub fn reconstruct_sub(mut received_shards: Vec<Option<WrappedShard>>, n: usize, k: usize) -> Option<Vec<u8>> {
+	reconstruct_shards_sub(&mut received_shards[..], n, k)
+}
+
+/// [`reconstruct`] of borrowed shards, see [`reconstruct_shards_sub`].
+pub(crate) fn reconstruct_shards(received_shards: &mut [Option<WrappedShard>]) -> Option<Vec<u8>> {
+	reconstruct_shards_sub(received_shards, N_VALIDATORS, DATA_SHARDS)
+}
+
+/// [`reconstruct_sub`] of borrowed shards, the erased data shards are recovered in place.
+fn reconstruct_shards_sub(received_shards: &mut [Option<WrappedShard>], n: usize, k: usize) -> Option<Vec<u8>> {
+	let r = ReedSolomon::new(k, n - k).ok()?;
+
+	// Try to reconstruct missing shards
+	r.reconstruct_data(received_shards).ok()?;
+
+	let shard_len = received_shards.iter().flatten().map(|shard| AsRef::<[u8]>::as_ref(shard).len()).next()?;
+	let result = received_shards.iter().flatten().take(k).fold(Vec::with_capacity(k * shard_len), |mut acc, x| {
+		acc.extend_from_slice(x.as_ref());
+		acc
+	});
 
 	Some(result)
 }
+
+/// The GF((2^8)^2) of `reed_solomon_erasure::galois_16`, with log/exp tables for `exp` and `log`.
+///
+/// The element `[hi, lo]` has the bit representation `hi << 8 | lo`. The generator is the
+/// primitive element with the smallest representation.
+pub struct Galois16 {
+	exp: Vec<[u8; 2]>,
+	log: Vec<u16>,
+}
+
+impl Galois16 {
+	pub fn new() -> Self {
+		use galois_16::Field;
+		use reed_solomon_erasure::Field as _;
+
+		const ORDER: usize = u16::MAX as usize;
+
+		let one = Field::one();
+		let exp = (2..=u16::MAX)
+			.map(|candidate| {
+				let g = candidate.to_be_bytes();
+				let mut powers = Vec::with_capacity(ORDER);
+				let mut power = one;
+				while powers.is_empty() || power != one {
+					powers.push(power);
+					power = Field::mul(power, g);
+				}
+				powers
+			})
+			.find(|powers| powers.len() == ORDER)
+			.expect("The multiplicative group of a field is cyclic. qed");
+
+		let mut log = vec![0_u16; ORDER + 1];
+		for (n, power) in exp.iter().enumerate() {
+			log[u16::from_be_bytes(*power) as usize] = n as u16;
+		}
+		Self { exp, log }
+	}
+}
+
+impl Default for Galois16 {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl galois::GaloisField for Galois16 {
+	type Element = [u8; 2];
+
+	fn bits(&self) -> u32 {
+		16
+	}
+
+	fn element(&self, value: u32) -> [u8; 2] {
+		debug_assert!(value <= u16::MAX as u32);
+		(value as u16).to_be_bytes()
+	}
+
+	fn value(&self, a: [u8; 2]) -> u32 {
+		u16::from_be_bytes(a) as u32
+	}
+
+	fn one(&self) -> [u8; 2] {
+		self.exp[0]
+	}
+
+	fn add(&self, a: [u8; 2], b: [u8; 2]) -> [u8; 2] {
+		[a[0] ^ b[0], a[1] ^ b[1]]
+	}
+
+	fn mul(&self, a: [u8; 2], b: [u8; 2]) -> [u8; 2] {
+		<galois_16::Field as reed_solomon_erasure::Field>::mul(a, b)
+	}
+
+	fn inv(&self, a: [u8; 2]) -> [u8; 2] {
+		self.div(self.one(), a)
+	}
+
+	fn div(&self, a: [u8; 2], b: [u8; 2]) -> [u8; 2] {
+		assert_ne!(b, [0, 0]);
+		<galois_16::Field as reed_solomon_erasure::Field>::div(a, b)
+	}
+
+	fn exp(&self, n: u64) -> [u8; 2] {
+		self.exp[(n % self.order()) as usize]
+	}
+
+	fn log(&self, a: [u8; 2]) -> u64 {
+		assert_ne!(a, [0, 0]);
+		self.log[u16::from_be_bytes(a) as usize] as u64
+	}
+}
@@ -0,0 +1,345 @@
+// Streaming variants of the encoding and reconstruction routines.
+//
+// The payload is consumed in stripes of `DATA_SHARDS * shard_len` bytes, each stripe
+// is encoded by itself and the resulting shard pieces are appended to one sink per shard.
+// Decoding reads one piece per shard source and reconstructs stripe by stripe.
+// At most a single stripe and its shards are held in memory at any time.
+
+use super::*;
+
+use std::io::{self, Read, Write};
+
+/// Read from `reader` until `buf` is full or the reader is exhausted.
+///
+/// Returns the number of bytes read, which is only less than `buf.len()` at the end of the stream.
+fn read_stripe<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+	let mut filled = 0;
+	while filled < buf.len() {
+		match reader.read(&mut buf[filled..]) {
+			Ok(0) => break,
+			Ok(n) => filled += n,
+			Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+			Err(e) => return Err(e),
+		}
+	}
+	Ok(filled)
+}
+
+/// Encode everything `reader` yields with `encode`, stripe by stripe.
+///
+/// Every stripe carries `DATA_SHARDS * shard_len` bytes of payload, the last one zero padded,
+/// and the `i`-th shard of each stripe is appended to `sinks[i]`.
+/// `shard_len` must be a non-zero multiple of 2, there must be one sink per validator.
+///
+/// Returns the number of payload bytes read, required to strip the padding when decoding.
+pub fn encode<E, R, W>(encode: E, mut reader: R, sinks: &mut [W], shard_len: usize) -> io::Result<u64>
+where
+	E: Fn(&[u8]) -> Vec<WrappedShard>,
+	R: Read,
+	W: Write,
+{
+	if sinks.len() != N_VALIDATORS {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			format!("Expected {} sinks, got {}", N_VALIDATORS, sinks.len()),
+		));
+	}
+	if shard_len == 0 || shard_len & 0x01 == 0x01 {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			format!("Shard length must be a non-zero multiple of 2, got {}", shard_len),
+		));
+	}
+
+	let mut stripe = vec![0u8; DATA_SHARDS * shard_len];
+	let mut total = 0_u64;
+	loop {
+		let len = read_stripe(&mut reader, &mut stripe[..])?;
+		if len == 0 {
+			break;
+		}
+		total += len as u64;
+
+		// zero pad the last stripe, so all shards have the same length
+		stripe[len..].iter_mut().for_each(|byte| *byte = 0);
+
+		let shards = encode(&stripe[..]);
+		debug_assert_eq!(shards.len(), sinks.len());
+		for (shard, sink) in shards.iter().zip(sinks.iter_mut()) {
+			let shard: &[u8] = shard.as_ref();
+			debug_assert_eq!(shard.len(), shard_len);
+			sink.write_all(shard)?;
+		}
+
+		if len < stripe.len() {
+			break;
+		}
+	}
+
+	for sink in sinks.iter_mut() {
+		sink.flush()?;
+	}
+
+	Ok(total)
+}
+
+/// Reconstruct a payload of `payload_len` bytes from the shard `sources`, stripe by stripe.
+///
+/// `sources[i]` yields the `i`-th shard as written by [`encode`] with the same `shard_len`,
+/// or is `None` if that shard is unavailable. A source that fails or ends early is treated
+/// as erased from then on, so the erasure pattern stays fixed until another source drops out.
+/// The recovered payload is written to `sink` as soon as each stripe is decoded.
+///
+/// `reconstruct` borrows the shards of a stripe, so their buffers are reused for the next one.
+pub fn decode<D, R, W>(
+	reconstruct: D,
+	mut sources: Vec<Option<R>>,
+	mut sink: W,
+	shard_len: usize,
+	payload_len: u64,
+) -> io::Result<()>
+where
+	D: Fn(&mut [Option<WrappedShard>]) -> Option<Vec<u8>>,
+	R: Read,
+	W: Write,
+{
+	if sources.len() != N_VALIDATORS {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			format!("Expected {} sources, got {}", N_VALIDATORS, sources.len()),
+		));
+	}
+	if shard_len == 0 || shard_len & 0x01 == 0x01 {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			format!("Shard length must be a non-zero multiple of 2, got {}", shard_len),
+		));
+	}
+
+	let stripe_len = (DATA_SHARDS * shard_len) as u64;
+	let mut remaining = payload_len;
+	// one buffer per source, refilled in place for every stripe
+	let mut received = vec![None; N_VALIDATORS];
+	while remaining > 0 {
+		for (source, shard) in sources.iter_mut().zip(received.iter_mut()) {
+			let reader = match source.as_mut() {
+				Some(reader) => reader,
+				None => {
+					*shard = None;
+					continue;
+				}
+			};
+			let piece = shard.get_or_insert_with(|| WrappedShard::new(vec![0u8; shard_len]));
+			match read_stripe(reader, piece.as_mut()) {
+				Ok(len) if len == shard_len => {}
+				// erase the source for all following stripes
+				_ => {
+					*source = None;
+					*shard = None;
+				}
+			}
+		}
+
+		let available = received.iter().filter(|shard| shard.is_some()).count();
+		if available < DATA_SHARDS {
+			return Err(io::Error::new(
+				io::ErrorKind::UnexpectedEof,
+				format!("Only {} of the required {} shards are available", available, DATA_SHARDS),
+			));
+		}
+
+		let stripe = reconstruct(&mut received[..])
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Failed to reconstruct stripe"))?;
+
+		let len = std::cmp::min(remaining, stripe_len) as usize;
+		if stripe.len() < len {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "Reconstructed stripe is too short"));
+		}
+		sink.write_all(&stripe[..len])?;
+		remaining -= len as u64;
+	}
+
+	sink.flush()
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	/// A reader that only ever yields a few bytes at a time.
+	struct Trickle<'a>(&'a [u8]);
+
+	impl Read for Trickle<'_> {
+		fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+			let n = std::cmp::min(std::cmp::min(buf.len(), 7), self.0.len());
+			buf[..n].copy_from_slice(&self.0[..n]);
+			self.0 = &self.0[n..];
+			Ok(n)
+		}
+	}
+
+	fn stream_matches_stripes<E, R>(encode: E, reconstruct: R)
+	where
+		E: Fn(&[u8]) -> Vec<WrappedShard> + Copy,
+		R: Fn(Vec<Option<WrappedShard>>) -> Option<Vec<u8>>,
+	{
+		const SHARD_LEN: usize = 64;
+		let payload = &BYTES[..1337];
+
+		let mut sinks = vec![Vec::<u8>::new(); N_VALIDATORS];
+		let total = super::encode(encode, Trickle(payload), &mut sinks[..], SHARD_LEN).unwrap();
+		assert_eq!(total, payload.len() as u64);
+
+		let stripe_len = DATA_SHARDS * SHARD_LEN;
+		let stripes = payload.len().div_ceil(stripe_len);
+		assert!(sinks.iter().all(|sink| sink.len() == stripes * SHARD_LEN));
+
+		let mut recovered = Vec::new();
+		for (stripe_idx, chunk) in payload.chunks(stripe_len).enumerate() {
+			let mut padded = chunk.to_vec();
+			padded.resize(stripe_len, 0);
+
+			let range = (stripe_idx * SHARD_LEN)..((stripe_idx + 1) * SHARD_LEN);
+			for (shard, sink) in encode(&padded[..]).iter().zip(sinks.iter()) {
+				assert_eq!(AsRef::<[u8]>::as_ref(shard), &sink[range.clone()]);
+			}
+
+			// drop all but the last `DATA_SHARDS` shards
+			let received = sinks
+				.iter()
+				.enumerate()
+				.map(|(idx, sink)| {
+					if idx < N_VALIDATORS - DATA_SHARDS {
+						None
+					} else {
+						Some(WrappedShard::new(sink[range.clone()].to_vec()))
+					}
+				})
+				.collect::<Vec<_>>();
+			recovered.extend(reconstruct(received).unwrap());
+		}
+		assert_eq!(&recovered[..payload.len()], payload);
+	}
+
+	#[test]
+	fn status_quo_stream() {
+		stream_matches_stripes(status_quo::encode, status_quo::reconstruct);
+	}
+
+	#[test]
+	fn novel_poly_basis_stream() {
+		stream_matches_stripes(novel_poly_basis::encode, novel_poly_basis::reconstruct);
+	}
+
+	/// A reader that fails after yielding `.1` bytes.
+	struct Faulty<'a>(&'a [u8], usize);
+
+	impl Read for Faulty<'_> {
+		fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+			if self.1 == 0 {
+				return Err(io::Error::new(io::ErrorKind::ConnectionReset, "gone"));
+			}
+			let n = std::cmp::min(std::cmp::min(buf.len(), self.1), self.0.len());
+			buf[..n].copy_from_slice(&self.0[..n]);
+			self.0 = &self.0[n..];
+			self.1 -= n;
+			Ok(n)
+		}
+	}
+
+	fn stream_roundtrip<E, R>(encode: E, reconstruct: R)
+	where
+		E: Fn(&[u8]) -> Vec<WrappedShard>,
+		R: Fn(&mut [Option<WrappedShard>]) -> Option<Vec<u8>>,
+	{
+		const SHARD_LEN: usize = 32;
+		let payload = &BYTES[..2000];
+
+		let mut sinks = vec![Vec::<u8>::new(); N_VALIDATORS];
+		let total = super::encode(encode, payload, &mut sinks[..], SHARD_LEN).unwrap();
+
+		// lose a few right away, and one in the middle of the stream
+		let sources = sinks
+			.iter()
+			.enumerate()
+			.map(|(idx, sink)| match idx {
+				0 | 3 | 5 | 8 | 9 | 11 | 12 | 15 => None,
+				1 => Some(Faulty(&sink[..], 3 * SHARD_LEN + 5)),
+				_ => Some(Faulty(&sink[..], usize::MAX)),
+			})
+			.collect::<Vec<_>>();
+
+		let mut recovered = Vec::new();
+		super::decode(reconstruct, sources, &mut recovered, SHARD_LEN, total).unwrap();
+		assert_eq!(&recovered[..], payload);
+	}
+
+	#[test]
+	fn status_quo_stream_roundtrip() {
+		stream_roundtrip(status_quo::encode, status_quo::reconstruct_shards);
+	}
+
+	#[test]
+	fn novel_poly_basis_stream_roundtrip() {
+		stream_roundtrip(novel_poly_basis::encode, novel_poly_basis::reconstruct_shards);
+	}
+
+	#[test]
+	fn reuses_shard_buffers() {
+		const SHARD_LEN: usize = 16;
+		let payload = &BYTES[..1000];
+		let mut sinks = vec![Vec::<u8>::new(); N_VALIDATORS];
+		let total = super::encode(status_quo::encode, payload, &mut sinks[..], SHARD_LEN).unwrap();
+		let sources = sinks.iter().map(|sink| Some(&sink[..])).collect::<Vec<_>>();
+
+		let buffers = std::cell::RefCell::new(Vec::new());
+		let reconstruct = |received: &mut [Option<WrappedShard>]| {
+			let current = received
+				.iter()
+				.map(|shard| shard.as_ref().map(|shard| AsRef::<[u8]>::as_ref(shard).as_ptr()))
+				.collect::<Vec<_>>();
+			let mut buffers = buffers.borrow_mut();
+			if !buffers.is_empty() {
+				assert_eq!(*buffers, current);
+			}
+			*buffers = current;
+			status_quo::reconstruct_shards(received)
+		};
+
+		let mut recovered = Vec::new();
+		super::decode(reconstruct, sources, &mut recovered, SHARD_LEN, total).unwrap();
+		assert_eq!(&recovered[..], payload);
+	}
+
+	#[test]
+	fn too_few_sources() {
+		const SHARD_LEN: usize = 16;
+		let mut sinks = vec![Vec::<u8>::new(); N_VALIDATORS];
+		let total = super::encode(status_quo::encode, &BYTES[..500], &mut sinks[..], SHARD_LEN).unwrap();
+
+		let sources = sinks
+			.iter()
+			.enumerate()
+			.map(|(idx, sink)| if idx < DATA_SHARDS - 1 { Some(&sink[..]) } else { None })
+			.collect::<Vec<_>>();
+		let mut recovered = Vec::new();
+		let err = super::decode(status_quo::reconstruct_shards, sources, &mut recovered, SHARD_LEN, total).unwrap_err();
+		assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+		assert!(recovered.is_empty());
+	}
+
+	#[test]
+	fn empty_input() {
+		let mut sinks = vec![Vec::<u8>::new(); N_VALIDATORS];
+		let total = super::encode(status_quo::encode, io::empty(), &mut sinks[..], 16).unwrap();
+		assert_eq!(total, 0);
+		assert!(sinks.iter().all(Vec::is_empty));
+	}
+
+	#[test]
+	fn bad_parameters() {
+		let mut sinks = vec![Vec::<u8>::new(); N_VALIDATORS];
+		assert!(super::encode(status_quo::encode, &BYTES[..10], &mut sinks[..], 15).is_err());
+		assert!(super::encode(status_quo::encode, &BYTES[..10], &mut sinks[1..], 16).is_err());
+	}
+}
@@ -1,4 +1,7 @@
 // A shard with a even number of elements, which can sliced into 2 byte haps
+
+use alloc::vec::Vec;
+
 #[derive(Clone)]
 pub struct WrappedShard {
 	inner: Vec<u8>,
@@ -32,29 +35,39 @@ impl AsMut<[u8]> for WrappedShard {
 	}
 }
 
+/// View an even length byte slice as 2 byte symbols.
+pub fn as_symbols(bytes: &[u8]) -> &[[u8; 2]] {
+	assert_eq!(bytes.len() & 0x01, 0);
+	if bytes.is_empty() {
+		return &[];
+	}
+	unsafe { ::core::slice::from_raw_parts(&bytes[0] as *const _ as _, bytes.len() / 2) }
+}
+
+/// View an even length byte slice as mutable 2 byte symbols.
+pub fn as_symbols_mut(bytes: &mut [u8]) -> &mut [[u8; 2]] {
+	let len = bytes.len();
+	assert_eq!(len & 0x01, 0);
+
+	if bytes.is_empty() {
+		return &mut [];
+	}
+	unsafe { ::core::slice::from_raw_parts_mut(&mut bytes[0] as *mut _ as _, len / 2) }
+}
+
 impl AsRef<[[u8; 2]]> for WrappedShard {
 	fn as_ref(&self) -> &[[u8; 2]] {
-		assert_eq!(self.inner.len() & 0x01, 0);
-		if self.inner.is_empty() {
-			return &[];
-		}
-		unsafe { ::std::slice::from_raw_parts(&self.inner[0] as *const _ as _, self.inner.len() / 2) }
+		as_symbols(&self.inner)
 	}
 }
 
 impl AsMut<[[u8; 2]]> for WrappedShard {
 	fn as_mut(&mut self) -> &mut [[u8; 2]] {
-		let len = self.inner.len();
-		assert_eq!(len & 0x01, 0);
-
-		if self.inner.is_empty() {
-			return &mut [];
-		}
-		unsafe { ::std::slice::from_raw_parts_mut(&mut self.inner[0] as *mut _ as _, len / 2) }
+		as_symbols_mut(&mut self.inner)
 	}
 }
 
-impl std::iter::FromIterator<[u8; 2]> for WrappedShard {
+impl core::iter::FromIterator<[u8; 2]> for WrappedShard {
 	fn from_iter<I: IntoIterator<Item = [u8; 2]>>(iterable: I) -> Self {
 		let iter = iterable.into_iter();
 